@@ -0,0 +1,154 @@
+use crate::constants;
+
+/// Global alert level, steps down one stage at a time while nobody sees the player
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum AlertLevel {
+    Calm,
+    Cautious,
+    Search,
+    Alert,
+}
+
+impl AlertLevel {
+    /// How long the level lasts without the player being seen
+    pub fn duration(&self) -> f32 {
+        match self {
+            AlertLevel::Calm => 0.,
+            AlertLevel::Cautious => constants::CAUTIOUS_DURATION,
+            AlertLevel::Search => constants::SEARCH_DURATION,
+            AlertLevel::Alert => constants::ALERT_DURATION,
+        }
+    }
+
+    pub fn lower(&self) -> Self {
+        match self {
+            AlertLevel::Calm | AlertLevel::Cautious => AlertLevel::Calm,
+            AlertLevel::Search => AlertLevel::Cautious,
+            AlertLevel::Alert => AlertLevel::Search,
+        }
+    }
+
+    pub fn speed_scale(&self) -> f32 {
+        match self {
+            AlertLevel::Calm => 1.,
+            AlertLevel::Cautious => 1.15,
+            AlertLevel::Search | AlertLevel::Alert => 1.3,
+        }
+    }
+
+    #[inline]
+    pub fn uses_large_look(&self) -> bool {
+        *self != AlertLevel::Calm
+    }
+
+    /// Green and blue color channels of floor and walls, lower values are more red
+    pub fn tint(&self) -> f32 {
+        match self {
+            AlertLevel::Calm => 1.,
+            AlertLevel::Cautious => 0.85,
+            AlertLevel::Search => 0.7,
+            AlertLevel::Alert => 0.5,
+        }
+    }
+}
+
+/// Alert which is raised over the radio by a guard once its timer runs out
+pub struct PendingAlert {
+    pub guard_idx: usize,
+    pub timer: f32,
+}
+
+/// Spreads alerts between guards: a guard raises the alarm after a reaction time,
+/// guards within the radio radius pick it up and relay it further after a delay
+pub struct AlertSystem {
+    pub pending_alerts: Vec<PendingAlert>,
+    pub alert_level: AlertLevel,
+    pub level_timer: f32,
+    pub search_focus: Option<glam::Vec2>,
+}
+
+impl AlertSystem {
+    pub fn new() -> Self {
+        Self {
+            pending_alerts: vec![],
+            alert_level: AlertLevel::Calm,
+            level_timer: 0.,
+            search_focus: None,
+        }
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.pending_alerts.clear();
+        self.set_level(AlertLevel::Calm);
+        self.search_focus = None;
+    }
+
+    #[inline]
+    pub fn set_level(&mut self, alert_level: AlertLevel) {
+        self.alert_level = alert_level;
+        self.level_timer = alert_level.duration();
+    }
+
+    /// Steps the alert level down once its timer runs out, the timer is reset while the player is seen.
+    /// Returns true if the level changed.
+    pub fn update_level(&mut self, dt: f32, is_player_seen: bool) -> bool {
+        if self.alert_level == AlertLevel::Calm {
+            return false;
+        }
+
+        if is_player_seen {
+            self.level_timer = self.alert_level.duration();
+            return false;
+        }
+
+        self.level_timer -= dt;
+        if self.level_timer > 0. {
+            return false;
+        }
+
+        self.set_level(self.alert_level.lower());
+        true
+    }
+
+    #[inline]
+    pub fn is_pending(&self, guard_idx: usize) -> bool {
+        self.pending_alerts
+            .iter()
+            .any(|alert| alert.guard_idx == guard_idx)
+    }
+
+    /// Guard at `guard_idx` raises the alarm after `delay` seconds
+    pub fn raise(&mut self, guard_idx: usize, delay: f32) {
+        if !self.is_pending(guard_idx) {
+            self.pending_alerts.push(PendingAlert {
+                guard_idx,
+                timer: delay,
+            });
+        }
+    }
+
+    /// Returns indices of guards whose alerts went out this frame
+    pub fn update(&mut self, dt: f32) -> Vec<usize> {
+        self.pending_alerts
+            .iter_mut()
+            .for_each(|alert| alert.timer -= dt);
+
+        let raised = self
+            .pending_alerts
+            .iter()
+            .filter(|alert| alert.timer <= 0.)
+            .map(|alert| alert.guard_idx)
+            .collect::<Vec<usize>>();
+
+        self.pending_alerts.retain(|alert| alert.timer > 0.);
+
+        raised
+    }
+}
+
+impl Default for AlertSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,28 +1,27 @@
-use crate::sprite_component::SpriteComponent;
-
 pub struct AnimationComponent {
-    pub animation: Vec<SpriteComponent>,
+    pub n_frames: usize,
     pub index: usize,
+    pub color: ggez::graphics::Color,
     pub duration: f32,
     pub duration_counter: f32,
     pub animation_state: AnimationState,
 }
 
 impl AnimationComponent {
-    pub fn new(animation: Vec<SpriteComponent>, duration: f32) -> Self {
+    pub fn new(n_frames: usize, duration: f32, color: ggez::graphics::Color) -> Self {
         Self {
-            animation,
+            n_frames,
             index: 0,
+            color,
             duration,
             duration_counter: 0.,
             animation_state: AnimationState::Idle,
         }
     }
 
+    #[inline]
     pub fn set_color(&mut self, color: ggez::graphics::Color) {
-        self.animation
-            .iter_mut()
-            .for_each(|sprite| sprite.set_color(color));
+        self.color = color;
     }
 
     pub fn set_animation_state(&mut self, state: AnimationState) {
@@ -33,19 +32,13 @@ impl AnimationComponent {
         self.animation_state = state;
     }
 
-    pub fn get_curr_frame(&self) -> &SpriteComponent {
-        self.animation
-            .get(self.index)
-            .expect("index is out of range")
-    }
-
     pub fn update(&mut self, dt: f32) {
         if self.animation_state == AnimationState::Idle {
             return;
         }
 
         if self.duration_counter >= self.duration {
-            self.index = (self.index + 1) % self.animation.len();
+            self.index = (self.index + 1) % self.n_frames;
             self.duration_counter = 0.;
         }
 
@@ -1,21 +1,16 @@
+use crate::constants;
+use crate::load_error::LoadError;
+use crate::tile_component::TileKind;
+
 use ggez::{graphics, Context};
 
 pub struct Assets {
-    pub stand: graphics::Image,
-    pub walk_l1: graphics::Image,
-    pub walk_l2: graphics::Image,
-    pub walk_l3: graphics::Image,
-    pub walk_l4: graphics::Image,
-    pub walk_r1: graphics::Image,
-    pub walk_r2: graphics::Image,
-    pub walk_r3: graphics::Image,
-    pub walk_r4: graphics::Image,
+    /// Frames of the walk animation, indexed by `AnimationComponent::index`
+    pub walk_animation: Vec<graphics::Image>,
 
     pub dead: graphics::Image,
 
-    pub wall: graphics::Image,
-    pub box1: graphics::Image,
-    pub box2: graphics::Image,
+    pub tileset: Tileset,
 
     pub teleport: graphics::Image,
     pub exit: graphics::Image,
@@ -24,9 +19,6 @@ pub struct Assets {
 
     pub footstep: graphics::Image,
 
-    pub floor1: graphics::Image,
-    pub floor2: graphics::Image,
-
     pub checkmark: graphics::Image,
 
     pub marker: graphics::Image,
@@ -34,55 +26,61 @@ pub struct Assets {
 }
 
 impl Assets {
-    pub fn load(ctx: &mut Context, quad_ctx: &mut ggez::miniquad::GraphicsContext) -> Self {
-        let stand = graphics::Image::new(ctx, quad_ctx, "textures/stand.png").unwrap();
-        let walk_l1 = graphics::Image::new(ctx, quad_ctx, "textures/walk_l1.png").unwrap();
-        let walk_l2 = graphics::Image::new(ctx, quad_ctx, "textures/walk_l2.png").unwrap();
-        let walk_l3 = graphics::Image::new(ctx, quad_ctx, "textures/walk_l3.png").unwrap();
-        let walk_l4 = graphics::Image::new(ctx, quad_ctx, "textures/walk_l4.png").unwrap();
-        let walk_r1 = graphics::Image::new(ctx, quad_ctx, "textures/walk_r1.png").unwrap();
-        let walk_r2 = graphics::Image::new(ctx, quad_ctx, "textures/walk_r2.png").unwrap();
-        let walk_r3 = graphics::Image::new(ctx, quad_ctx, "textures/walk_r3.png").unwrap();
-        let walk_r4 = graphics::Image::new(ctx, quad_ctx, "textures/walk_r4.png").unwrap();
-
-        let dead = graphics::Image::new(ctx, quad_ctx, "textures/dead.png").unwrap();
+    pub fn load(
+        ctx: &mut Context,
+        quad_ctx: &mut ggez::miniquad::GraphicsContext,
+    ) -> Result<Self, LoadError> {
+        let stand = load_image(ctx, quad_ctx, "textures/stand.png")?;
+        let walk_l1 = load_image(ctx, quad_ctx, "textures/walk_l1.png")?;
+        let walk_l2 = load_image(ctx, quad_ctx, "textures/walk_l2.png")?;
+        let walk_l3 = load_image(ctx, quad_ctx, "textures/walk_l3.png")?;
+        let walk_l4 = load_image(ctx, quad_ctx, "textures/walk_l4.png")?;
+        let walk_r1 = load_image(ctx, quad_ctx, "textures/walk_r1.png")?;
+        let walk_r2 = load_image(ctx, quad_ctx, "textures/walk_r2.png")?;
+        let walk_r3 = load_image(ctx, quad_ctx, "textures/walk_r3.png")?;
+        let walk_r4 = load_image(ctx, quad_ctx, "textures/walk_r4.png")?;
+
+        let walk_animation = vec![
+            stand.clone(),
+            walk_l1.clone(),
+            walk_l2.clone(),
+            walk_l3.clone(),
+            walk_l4,
+            walk_l3,
+            walk_l2,
+            walk_l1,
+            stand,
+            walk_r1.clone(),
+            walk_r2.clone(),
+            walk_r3.clone(),
+            walk_r4,
+            walk_r3,
+            walk_r2,
+            walk_r1,
+        ];
 
-        let wall = graphics::Image::new(ctx, quad_ctx, "textures/wall.png").unwrap();
-        let box1 = graphics::Image::new(ctx, quad_ctx, "textures/box1.png").unwrap();
-        let box2 = graphics::Image::new(ctx, quad_ctx, "textures/box2.png").unwrap();
+        let dead = load_image(ctx, quad_ctx, "textures/dead.png")?;
 
-        let teleport = graphics::Image::new(ctx, quad_ctx, "textures/oni.png").unwrap();
-        let exit = graphics::Image::new(ctx, quad_ctx, "textures/exit.png").unwrap();
+        let tileset = Tileset::load(ctx, quad_ctx, constants::DEFAULT_TILESET)?;
 
-        let ultimate_ninja =
-            graphics::Image::new(ctx, quad_ctx, "textures/ultimate_ninja.png").unwrap();
+        let teleport = load_image(ctx, quad_ctx, "textures/oni.png")?;
+        let exit = load_image(ctx, quad_ctx, "textures/exit.png")?;
 
-        let footstep = graphics::Image::new(ctx, quad_ctx, "textures/footstep.png").unwrap();
+        let ultimate_ninja = load_image(ctx, quad_ctx, "textures/ultimate_ninja.png")?;
 
-        let floor1 = graphics::Image::new(ctx, quad_ctx, "textures/floor1.png").unwrap();
-        let floor2 = graphics::Image::new(ctx, quad_ctx, "textures/floor2.png").unwrap();
+        let footstep = load_image(ctx, quad_ctx, "textures/footstep.png")?;
 
-        let checkmark = graphics::Image::new(ctx, quad_ctx, "textures/checkmark.png").unwrap();
+        let checkmark = load_image(ctx, quad_ctx, "textures/checkmark.png")?;
 
-        let marker = graphics::Image::new(ctx, quad_ctx, "textures/marker.png").unwrap();
-        let arrow = graphics::Image::new(ctx, quad_ctx, "textures/arrow.png").unwrap();
+        let marker = load_image(ctx, quad_ctx, "textures/marker.png")?;
+        let arrow = load_image(ctx, quad_ctx, "textures/arrow.png")?;
 
-        Assets {
-            stand,
-            walk_l1,
-            walk_l2,
-            walk_l3,
-            walk_l4,
-            walk_r1,
-            walk_r2,
-            walk_r3,
-            walk_r4,
+        Ok(Assets {
+            walk_animation,
 
             dead,
 
-            wall,
-            box1,
-            box2,
+            tileset,
 
             teleport,
             exit,
@@ -91,13 +89,71 @@ impl Assets {
 
             footstep,
 
-            floor1,
-            floor2,
-
             checkmark,
 
             marker,
             arrow,
+        })
+    }
+}
+
+fn load_image(
+    ctx: &mut Context,
+    quad_ctx: &mut ggez::miniquad::GraphicsContext,
+    filename: &str,
+) -> Result<graphics::Image, LoadError> {
+    graphics::Image::new(ctx, quad_ctx, filename).map_err(|err| LoadError::InvalidImage {
+        filename: filename.to_owned(),
+        reason: err.to_string(),
+    })
+}
+
+/// Textures of the level blocks, selected by the `tileset` field of the level header
+pub struct Tileset {
+    pub name: String,
+    pub wall: graphics::Image,
+    pub box1: graphics::Image,
+    pub box2: graphics::Image,
+    pub floor1: graphics::Image,
+    pub floor2: graphics::Image,
+}
+
+impl Tileset {
+    /// The default tileset lives directly in `textures/`, others in `textures/tilesets/<name>/`
+    pub fn load(
+        ctx: &mut Context,
+        quad_ctx: &mut ggez::miniquad::GraphicsContext,
+        name: &str,
+    ) -> Result<Self, LoadError> {
+        let dir = if name == constants::DEFAULT_TILESET {
+            "textures".to_owned()
+        } else {
+            format!("textures/tilesets/{}", name)
+        };
+
+        let wall = load_image(ctx, quad_ctx, &format!("{}/wall.png", dir))?;
+        let box1 = load_image(ctx, quad_ctx, &format!("{}/box1.png", dir))?;
+        let box2 = load_image(ctx, quad_ctx, &format!("{}/box2.png", dir))?;
+        let floor1 = load_image(ctx, quad_ctx, &format!("{}/floor1.png", dir))?;
+        let floor2 = load_image(ctx, quad_ctx, &format!("{}/floor2.png", dir))?;
+
+        Ok(Tileset {
+            name: name.to_owned(),
+            wall,
+            box1,
+            box2,
+            floor1,
+            floor2,
+        })
+    }
+
+    pub fn image(&self, kind: TileKind) -> &graphics::Image {
+        match kind {
+            TileKind::Wall => &self.wall,
+            TileKind::Box1 => &self.box1,
+            TileKind::Box2 => &self.box2,
+            TileKind::Floor1 => &self.floor1,
+            TileKind::Floor2 => &self.floor2,
         }
     }
 }
@@ -0,0 +1,212 @@
+//! Stand-in leaderboard server for development and tests, it speaks the same protocol as the game's
+//! HTTP backend: `GET /leaderboard?level=<id>&hash=<hash>` returns the times of a level and
+//! `PUT /put` submits a time.
+//!
+//! Usage: `leaderboard_server [--address 127.0.0.1:8080] [--api-key <key>] [--file <leaderboard.json>]`
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+use ultimate_ninja::leaderboard_backend::PlayerEntry;
+
+/// Larger bodies are refused before they are read, an entry is a few hundred bytes
+const MAX_BODY_SIZE: usize = 64 * 1024;
+/// Connections are handled one at a time, so a silent client is dropped after this
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Reason why a request is answered without being handled
+enum BadRequest {
+    Malformed,
+    TooLarge,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    api_key: Option<String>,
+    body: Vec<u8>,
+}
+
+struct Leaderboard {
+    entries: Vec<PlayerEntry>,
+    filename: Option<String>,
+}
+
+impl Leaderboard {
+    fn load(filename: Option<String>) -> Self {
+        let entries = filename
+            .as_ref()
+            .and_then(|filename| std::fs::read_to_string(filename).ok())
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+
+        Self { entries, filename }
+    }
+
+    fn submit(&mut self, entry: PlayerEntry) {
+        self.entries.push(entry);
+        self.entries.sort_by(|e1, e2| e1.time.total_cmp(&e2.time));
+
+        if let Some(filename) = &self.filename {
+            if let Err(err) = std::fs::write(
+                filename,
+                serde_json::to_string_pretty(&self.entries).unwrap(),
+            ) {
+                eprintln!("could not write {}: {}", filename, err);
+            }
+        }
+    }
+
+    fn level_entries(&self, level_id: &str, level_hash: &str) -> Vec<&PlayerEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.level_id == level_id && entry.level_hash == level_hash)
+            .collect()
+    }
+}
+
+fn main() {
+    let args = std::env::args().collect::<Vec<String>>();
+    let arg_value = |name: &str| {
+        args.windows(2)
+            .find(|args| args[0] == name)
+            .map(|args| args[1].clone())
+    };
+
+    let address = arg_value("--address").unwrap_or("127.0.0.1:8080".to_owned());
+    let api_key = arg_value("--api-key");
+    let mut leaderboard = Leaderboard::load(arg_value("--file"));
+
+    let listener = TcpListener::bind(&address)
+        .unwrap_or_else(|err| panic!("could not bind to {}: {}", address, err));
+    // The bound address, in case port 0 picked any free port
+    let address = listener
+        .local_addr()
+        .map_or(address, |addr| addr.to_string());
+    println!("Leaderboard server listening on http://{}", address);
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let result = stream
+                    .set_read_timeout(Some(READ_TIMEOUT))
+                    .and_then(|_| stream.set_write_timeout(Some(READ_TIMEOUT)))
+                    .and_then(|_| handle_connection(stream, &mut leaderboard, api_key.as_deref()));
+                if let Err(err) = result {
+                    eprintln!("connection error: {}", err);
+                }
+            }
+            Err(err) => eprintln!("accept error: {}", err),
+        }
+    }
+}
+
+fn handle_connection(
+    mut stream: TcpStream,
+    leaderboard: &mut Leaderboard,
+    api_key: Option<&str>,
+) -> std::io::Result<()> {
+    let request = match read_request(&mut stream)? {
+        Ok(request) => request,
+        Err(BadRequest::Malformed) => {
+            return write_response(&mut stream, 400, "Bad Request", "invalid request")
+        }
+        Err(BadRequest::TooLarge) => {
+            return write_response(&mut stream, 413, "Payload Too Large", "body too large")
+        }
+    };
+
+    if api_key.is_some() && request.api_key.as_deref() != api_key {
+        return write_response(&mut stream, 401, "Unauthorized", "invalid api key");
+    }
+
+    let (path, query) = request
+        .path
+        .split_once('?')
+        .unwrap_or((request.path.as_str(), ""));
+    let query = url::form_urlencoded::parse(query.as_bytes()).collect::<HashMap<_, _>>();
+    let query_value = |name: &str| query.get(name).map_or("", |value| value.as_ref());
+
+    match (request.method.as_str(), path) {
+        ("GET", "/leaderboard") => {
+            let entries = leaderboard.level_entries(query_value("level"), query_value("hash"));
+            let body = serde_json::to_string(&entries).unwrap();
+            write_response(&mut stream, 200, "OK", &body)
+        }
+        ("PUT", "/put") => match serde_json::from_slice::<PlayerEntry>(&request.body) {
+            Ok(entry) => {
+                println!("{}", entry);
+                leaderboard.submit(entry);
+                write_response(&mut stream, 200, "OK", "{}")
+            }
+            Err(err) => write_response(&mut stream, 400, "Bad Request", &err.to_string()),
+        },
+        _ => write_response(&mut stream, 404, "Not Found", "not found"),
+    }
+}
+
+fn read_request(stream: &mut TcpStream) -> std::io::Result<Result<Request, BadRequest>> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Ok(Err(BadRequest::Malformed));
+    };
+
+    let mut api_key = None;
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = line.split_once(':') {
+            match name.trim().to_ascii_lowercase().as_str() {
+                "api-key" => api_key = Some(value.trim().to_owned()),
+                "content-length" => content_length = value.trim().parse().unwrap_or(0),
+                _ => (),
+            }
+        }
+    }
+
+    if content_length > MAX_BODY_SIZE {
+        return Ok(Err(BadRequest::TooLarge));
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    Ok(Ok(Request {
+        method: method.to_owned(),
+        path: path.to_owned(),
+        api_key,
+        body,
+    }))
+}
+
+fn write_response(
+    stream: &mut TcpStream,
+    status: u16,
+    reason: &str,
+    body: &str,
+) -> std::io::Result<()> {
+    let content_type = if status == 200 {
+        "application/json"
+    } else {
+        "text/plain"
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
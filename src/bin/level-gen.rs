@@ -0,0 +1,67 @@
+//! Generates a level from a seed and prints it in the level file format.
+//!
+//! Usage: `level-gen [--seed <n>] [--difficulty <0..1>] [<output file>]`, the default seed is
+//! the current time and the default difficulty is 0.5. The seed is printed so that the level
+//! can be generated again.
+
+use ultimate_ninja::level;
+use ultimate_ninja::level_generator::{self, GeneratorParams};
+
+fn main() {
+    let mut seed = None;
+    let mut difficulty = 0.5;
+    let mut output = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => seed = Some(parse_arg(&arg, args.next())),
+            "--difficulty" => difficulty = parse_arg(&arg, args.next()),
+            _ if output.is_none() && !arg.starts_with("--") => output = Some(arg),
+            _ => usage_error(&format!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    let seed = seed.unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or_default()
+    });
+
+    let level = match level_generator::generate_level(&GeneratorParams::with_difficulty(
+        seed, difficulty,
+    )) {
+        Ok(level_data) => level::level_to_string(&level_data),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
+    match output {
+        Some(filename) => {
+            if let Err(err) = std::fs::write(&filename, level) {
+                eprintln!("{}: {}", filename, err);
+                std::process::exit(1);
+            }
+            eprintln!("seed {}: written to {}", seed, filename);
+        }
+        None => {
+            print!("{}", level);
+            eprintln!("seed {}", seed);
+        }
+    }
+}
+
+fn parse_arg<T: std::str::FromStr>(name: &str, value: Option<String>) -> T {
+    value
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_else(|| usage_error(&format!("{} expects a number", name)))
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!("usage: level-gen [--seed <n>] [--difficulty <0..1>] [<output file>]");
+    std::process::exit(2);
+}
@@ -0,0 +1,129 @@
+//! Checks level files and prints the problems with their line and column.
+//!
+//! Usage: `level-lint [<campaign manifest, level file or directory>...]`, the default is the
+//! campaign shipped with the game. The levels of a `.json` campaign manifest are relative to it
+//! and its tutorial chapters get the tutorial checks, other files are checked as regular levels.
+//! The exit code is 1 if any level has errors, warnings do not fail the check.
+
+use std::path::{Path, PathBuf};
+use ultimate_ninja::campaign::{Campaign, LevelSource};
+use ultimate_ninja::level_lint;
+
+const BUILTIN_MANIFEST: &str = "resources/campaigns/main.json";
+const RESOURCES_DIR: &str = "resources";
+
+/// Level file with its ID and whether the tutorial checks apply to it
+struct LintTarget {
+    filename: PathBuf,
+    level_id: String,
+    is_tutorial: bool,
+}
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+
+    let mut targets = vec![];
+    if args.is_empty() {
+        targets.extend(campaign_levels(
+            Path::new(BUILTIN_MANIFEST),
+            Path::new(RESOURCES_DIR),
+        ));
+    }
+    for path in args.iter().map(Path::new) {
+        if path.extension().is_some_and(|ext| ext == "json") {
+            targets.extend(campaign_levels(
+                path,
+                path.parent().unwrap_or(Path::new("")),
+            ));
+            continue;
+        }
+
+        match level_files(path) {
+            Ok(files) => targets.extend(files.into_iter().map(|filename| {
+                LintTarget {
+                    level_id: filename
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                    filename,
+                    is_tutorial: false,
+                }
+            })),
+            Err(err) => {
+                eprintln!("{}: {}", path.display(), err);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    let (mut n_errors, mut n_warnings) = (0, 0);
+    for target in &targets {
+        let source = match std::fs::read_to_string(&target.filename) {
+            Ok(source) => source,
+            Err(err) => {
+                eprintln!("{}: {}", target.filename.display(), err);
+                n_errors += 1;
+                continue;
+            }
+        };
+
+        for diagnostic in level_lint::lint_level(&source, &target.level_id, target.is_tutorial) {
+            match diagnostic.severity {
+                level_lint::Severity::Error => n_errors += 1,
+                level_lint::Severity::Warning => n_warnings += 1,
+            }
+
+            println!("{}:{}", target.filename.display(), diagnostic);
+        }
+    }
+
+    println!(
+        "{} levels checked: {} errors, {} warnings",
+        targets.len(),
+        n_errors,
+        n_warnings
+    );
+
+    if n_errors > 0 {
+        std::process::exit(1);
+    }
+}
+
+/// The levels of the campaign in the order of its manifest
+fn campaign_levels(manifest: &Path, levels_dir: &Path) -> Vec<LintTarget> {
+    let campaign = match Campaign::load_file(manifest, levels_dir) {
+        Ok(campaign) => campaign,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        }
+    };
+
+    campaign
+        .levels
+        .into_iter()
+        .map(|level| LintTarget {
+            filename: match level.source {
+                LevelSource::Resource(filename) => levels_dir.join(filename),
+                LevelSource::File(path) => path,
+            },
+            level_id: level.id,
+            is_tutorial: level.is_tutorial,
+        })
+        .collect()
+}
+
+/// The level file, or the `.txt` files of the directory in name order
+fn level_files(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_owned()]);
+    }
+
+    let mut files = std::fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<PathBuf>>>()?;
+    files.retain(|file| file.extension().is_some_and(|ext| ext == "txt"));
+    files.sort();
+
+    Ok(files)
+}
@@ -1,5 +1,4 @@
 use crate::collision_component::AABBCollisionComponent;
-use crate::constants;
 use crate::util;
 
 use crate::Game;
@@ -8,15 +7,22 @@ pub struct CameraComponent {
     pub center: glam::Vec2,
     pub size: glam::Vec2,
     pub lerp_delta: f32,
+    pub world_size: glam::Vec2,
 }
 
 impl CameraComponent {
     #[inline]
-    pub fn new(center: glam::Vec2, size: glam::Vec2, lerp_delta: f32) -> Self {
+    pub fn new(
+        center: glam::Vec2,
+        size: glam::Vec2,
+        lerp_delta: f32,
+        world_size: glam::Vec2,
+    ) -> Self {
         Self {
             center,
             size,
             lerp_delta,
+            world_size,
         }
     }
 
@@ -25,22 +31,33 @@ impl CameraComponent {
         self.lerp_delta = lerp_delta;
     }
 
+    #[inline]
+    pub fn set_world_size(&mut self, world_size: glam::Vec2) {
+        self.world_size = world_size;
+    }
+
     #[inline]
     pub fn world_position(&self, global_pos: glam::Vec2) -> glam::Vec2 {
         global_pos - self.center + self.size
     }
 
+    /// Inverse of `world_position`
+    #[inline]
+    pub fn global_position(&self, screen_pos: glam::Vec2) -> glam::Vec2 {
+        screen_pos + self.center - self.size
+    }
+
     pub fn update(&mut self, center: glam::Vec2) {
         let mut target_center = center;
         target_center.x = util::clamp(
             target_center.x,
             self.size.x,
-            constants::MAX_WORLD_X as f32 - self.size.x,
+            self.world_size.x - self.size.x,
         );
         target_center.y = util::clamp(
             target_center.y,
             self.size.y,
-            constants::MAX_WORLD_Y as f32 - self.size.y,
+            self.world_size.y - self.size.y,
         );
 
         self.center = util::vec_lerp(self.center, target_center, self.lerp_delta);
@@ -59,7 +76,8 @@ impl CameraComponent {
 }
 
 pub fn system(game_state: &mut Game) {
+    let alpha = game_state.tick_alpha();
     game_state
         .camera
-        .update(game_state.player.transform.position);
+        .update(game_state.world.player.transform.interpolate(alpha));
 }
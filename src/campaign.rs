@@ -0,0 +1,282 @@
+use crate::constants;
+use crate::level;
+use crate::load_error::LoadError;
+use crate::util;
+
+use serde::Deserialize;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Chapter of the manifest, the levels are listed or bundled from a directory of the user
+#[derive(Deserialize)]
+struct ChapterManifest {
+    name: String,
+    #[serde(default)]
+    tutorial: bool,
+    #[serde(default)]
+    levels: Vec<String>,
+    #[serde(default)]
+    user_directory: Option<String>, // Relative to the home directory, every `.txt` file in name order
+}
+
+#[derive(Deserialize)]
+struct CampaignManifest {
+    id: String,
+    name: String,
+    chapters: Vec<ChapterManifest>,
+}
+
+/// Where the level file is read from
+pub enum LevelSource {
+    Resource(String), // Shipped with the game
+    File(PathBuf),    // Level of a pack or a user directory
+}
+
+impl LevelSource {
+    pub fn filename(&self) -> String {
+        match self {
+            LevelSource::Resource(filename) => filename.clone(),
+            LevelSource::File(path) => path.display().to_string(),
+        }
+    }
+}
+
+pub struct CampaignLevel {
+    pub id: String, // The file name without the extension
+    pub chapter: String,
+    pub is_tutorial: bool,
+    pub source: LevelSource,
+    pub hash: String,
+}
+
+/// Ordered levels of a campaign, loaded from its manifest
+pub struct Campaign {
+    pub id: String,
+    pub name: String,
+    pub levels: Vec<CampaignLevel>,
+}
+
+impl Campaign {
+    /// The campaign shipped with the game
+    pub fn load_builtin(ctx: &mut ggez::Context) -> Result<Self, LoadError> {
+        let filename = constants::CAMPAIGN_MANIFEST;
+        let manifest = read_resource(ctx, filename)?;
+
+        Self::from_manifest(&manifest, filename, |level_filename| {
+            let source = LevelSource::Resource(level_filename.to_owned());
+            let level = read_resource(ctx, level_filename)?;
+
+            Ok((source, level))
+        })
+    }
+
+    /// Level pack in a directory, the level files are relative to its manifest
+    pub fn load_dir(dir: &Path) -> Result<Self, LoadError> {
+        Self::load_file(&dir.join(constants::CAMPAIGN_PACK_MANIFEST), dir)
+    }
+
+    /// Manifest read from the file system, the level files are relative to `levels_dir`
+    pub fn load_file(path: &Path, levels_dir: &Path) -> Result<Self, LoadError> {
+        let manifest = read_file(path)?;
+
+        Self::from_manifest(&manifest, &path.display().to_string(), |level_filename| {
+            let path = levels_dir.join(level_filename);
+            let level = read_file(&path)?;
+
+            Ok((LevelSource::File(path), level))
+        })
+    }
+
+    fn from_manifest(
+        manifest: &str,
+        filename: &str,
+        mut read_level: impl FnMut(&str) -> Result<(LevelSource, String), LoadError>,
+    ) -> Result<Self, LoadError> {
+        let invalid_campaign = |reason: String| LoadError::InvalidCampaign {
+            filename: filename.to_owned(),
+            reason,
+        };
+
+        let manifest: CampaignManifest =
+            serde_json::from_str(manifest).map_err(|err| invalid_campaign(err.to_string()))?;
+
+        // The ID is part of the file names of ghosts, replays and splits
+        if manifest.id.is_empty()
+            || !manifest
+                .id
+                .chars()
+                .all(|char| char.is_ascii_alphanumeric() || char == '_' || char == '-')
+        {
+            return Err(invalid_campaign(format!(
+                "invalid campaign id: '{}', expected letters, digits, '_' or '-'",
+                manifest.id
+            )));
+        }
+
+        let mut levels: Vec<CampaignLevel> = vec![];
+        for chapter in &manifest.chapters {
+            let user_levels = chapter
+                .user_directory
+                .as_ref()
+                .map(|dir| user_level_files(Path::new(&util::home_filename(dir))))
+                .unwrap_or_default();
+
+            let chapter_levels = chapter
+                .levels
+                .iter()
+                .map(|level_filename| read_level(level_filename))
+                .chain(user_levels.into_iter().map(|path| {
+                    let level = read_file(&path)?;
+                    Ok((LevelSource::File(path), level))
+                }));
+
+            for chapter_level in chapter_levels {
+                let (source, level) = chapter_level?;
+                let id = level_id(&source.filename());
+
+                if levels.iter().any(|other| other.id == id) {
+                    return Err(invalid_campaign(format!("duplicate level id: '{}'", id)));
+                }
+
+                // The level number and the timed run start after the tutorial
+                if chapter.tutorial && levels.last().is_some_and(|other| !other.is_tutorial) {
+                    return Err(invalid_campaign(format!(
+                        "tutorial chapter '{}' after a chapter which is not a tutorial",
+                        chapter.name
+                    )));
+                }
+
+                levels.push(CampaignLevel {
+                    id,
+                    chapter: chapter.name.clone(),
+                    is_tutorial: chapter.tutorial,
+                    source,
+                    hash: level::content_hash(level.as_bytes()),
+                });
+            }
+        }
+
+        if levels.is_empty() {
+            return Err(invalid_campaign("campaign has no levels".to_owned()));
+        }
+
+        Ok(Self {
+            id: manifest.id,
+            name: manifest.name,
+            levels,
+        })
+    }
+
+    pub fn level_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// The tutorial levels come first
+    pub fn tutorial_count(&self) -> usize {
+        self.levels
+            .iter()
+            .take_while(|level| level.is_tutorial)
+            .count()
+    }
+
+    /// ID of the level which is unique across campaigns
+    pub fn level_key(&self, level_index: usize) -> String {
+        format!("{}_{}", self.id, self.levels[level_index].id)
+    }
+
+    /// The campaign hash covers every level which counts towards the total time
+    pub fn hash(&self) -> String {
+        let level_hashes = self.levels[self.tutorial_count()..]
+            .iter()
+            .map(|level| level.hash.as_str())
+            .collect::<String>();
+
+        level::content_hash(level_hashes.as_bytes())
+    }
+
+    pub fn read_level(
+        &self,
+        ctx: &mut ggez::Context,
+        level_index: usize,
+    ) -> Result<String, LoadError> {
+        match &self.levels[level_index].source {
+            LevelSource::Resource(filename) => read_resource(ctx, filename),
+            LevelSource::File(path) => read_file(path),
+        }
+    }
+}
+
+/// The shipped campaign and every level pack in the campaigns directory of the user,
+/// a broken pack is skipped so that it does not lock the player out of the game
+pub fn load_campaigns(ctx: &mut ggez::Context) -> Result<Vec<Campaign>, LoadError> {
+    let mut campaigns = vec![Campaign::load_builtin(ctx)?];
+
+    let packs_dir = util::home_filename(constants::CAMPAIGNS_DIRNAME);
+    let mut pack_dirs = std::fs::read_dir(&packs_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.is_dir())
+                .collect::<Vec<PathBuf>>()
+        })
+        .unwrap_or_default();
+    pack_dirs.sort();
+
+    for pack_dir in pack_dirs {
+        match Campaign::load_dir(&pack_dir) {
+            Ok(campaign) if campaigns.iter().any(|other| other.id == campaign.id) => {
+                eprintln!(
+                    "skipping level pack {}: duplicate campaign id '{}'",
+                    pack_dir.display(),
+                    campaign.id
+                );
+            }
+            Ok(campaign) => campaigns.push(campaign),
+            Err(err) => eprintln!("skipping level pack {}: {}", pack_dir.display(), err),
+        }
+    }
+
+    Ok(campaigns)
+}
+
+fn level_id(filename: &str) -> String {
+    let filename = filename.rsplit(['/', '\\']).next().unwrap_or(filename);
+
+    filename.split('.').next().unwrap_or(filename).to_owned()
+}
+
+fn user_level_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+                .collect::<Vec<PathBuf>>()
+        })
+        .unwrap_or_default();
+    files.sort();
+
+    files
+}
+
+fn read_resource(ctx: &mut ggez::Context, filename: &str) -> Result<String, LoadError> {
+    let missing_file = |reason: String| LoadError::MissingFile {
+        filename: filename.to_owned(),
+        reason,
+    };
+
+    let mut file =
+        ggez::filesystem::open(ctx, filename).map_err(|err| missing_file(err.to_string()))?;
+    let mut data = String::new();
+    file.read_to_string(&mut data)
+        .map_err(|err| missing_file(err.to_string()))?;
+
+    Ok(data)
+}
+
+fn read_file(path: &Path) -> Result<String, LoadError> {
+    std::fs::read_to_string(path).map_err(|err| LoadError::MissingFile {
+        filename: path.display().to_string(),
+        reason: err.to_string(),
+    })
+}
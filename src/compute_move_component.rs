@@ -1,20 +1,14 @@
 use crate::constants;
+use crate::rng::GameRng;
+use crate::transform_component::TransformComponent;
 use crate::util;
-use crate::TransformComponent;
-use quad_rand as qrand;
 
 pub struct ComputeMoveComponent {
     pub rays: Vec<glam::Vec2>,
-    pub ray_lines: Vec<ggez::graphics::Mesh>,
 }
 
 impl ComputeMoveComponent {
-    pub fn new(
-        ctx: &mut ggez::Context,
-        quad_ctx: &mut ggez::miniquad::GraphicsContext,
-        n_rays: usize,
-        ray_length: f32,
-    ) -> Self {
+    pub fn new(n_rays: usize, ray_length: f32) -> Self {
         let ray = glam::vec2(0., 1.) * ray_length;
         let angle_delta = 2. * constants::PI / (n_rays as f32);
 
@@ -25,22 +19,7 @@ impl ComputeMoveComponent {
             })
             .collect::<Vec<glam::Vec2>>();
 
-        // Build a line mesh for each ray
-        let ray_lines = rays
-            .iter()
-            .map(|ray| {
-                ggez::graphics::Mesh::new_line(
-                    ctx,
-                    quad_ctx,
-                    &[glam::vec2(0., 0.), glam::vec2(ray.x, ray.y)],
-                    2.0,
-                    ggez::graphics::Color::WHITE,
-                )
-                .unwrap()
-            })
-            .collect::<Vec<ggez::graphics::Mesh>>();
-
-        Self { rays, ray_lines }
+        Self { rays }
     }
 
     // Retruns a move direction vector, it is not guaranteed to be normalized
@@ -49,6 +28,7 @@ impl ComputeMoveComponent {
         source: &TransformComponent,
         dist_to_wall_norm: f32,
         rect_objects: &Vec<(&ggez::graphics::Rect, isize)>,
+        rng: &mut GameRng,
     ) -> glam::Vec2 {
         if dist_to_wall_norm < 1. {
             return util::vec_from_angle(source.angle + (1. - dist_to_wall_norm) * constants::PI);
@@ -67,8 +47,8 @@ impl ComputeMoveComponent {
             })
             .collect();
 
-        let source_grid_idx = util::compute_grid_index(&source.position);
-        let row = (constants::MAX_WORLD_X as usize / constants::GRID_CELL_SIZE) as isize;
+        let source_grid_idx = util::compute_grid_index(&source.position, source.world_width);
+        let row = util::grid_row_len(source.world_width);
         let source_grid_indices = vec![
             source_grid_idx - row - 1,
             source_grid_idx - row,
@@ -83,7 +63,9 @@ impl ComputeMoveComponent {
 
         let ray_grid_indices = ray_lines
             .iter()
-            .map(|(_, _, dest_x, dest_y)| util::compute_grid_index(&glam::vec2(*dest_x, *dest_y)))
+            .map(|(_, _, dest_x, dest_y)| {
+                util::compute_grid_index(&glam::vec2(*dest_x, *dest_y), source.world_width)
+            })
             .chain(source_grid_indices.into_iter())
             .collect::<Vec<isize>>();
 
@@ -119,11 +101,11 @@ impl ComputeMoveComponent {
             .collect::<Vec<glam::Vec2>>();
 
         if available_rays.len() == 0 {
-            let rand_idx = qrand::gen_range(0, self.rays.len());
+            let rand_idx = rng.gen_index(self.rays.len());
             return self.rays[rand_idx];
         }
 
-        let rand_idx = qrand::gen_range(0, available_rays.len());
+        let rand_idx = rng.gen_index(available_rays.len());
         return available_rays[rand_idx];
     }
 }
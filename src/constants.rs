@@ -1,6 +1,33 @@
 pub const TARGET_FPS: f32 = 60.;
+/// Duration of one simulation tick, the simulation always advances by this amount
+pub const FIXED_DT: f32 = 1. / TARGET_FPS; // sec
+/// Longer frames are cut to this, so that the simulation does not fall behind after a stall
+pub const MAX_FRAME_TIME: f32 = 0.25; // sec
+
+pub const LEVEL_FORMAT_VERSION: u32 = 2;
+pub const LEVEL_HEADER_END: &str = "---";
+pub const DEFAULT_TILESET: &str = "default";
+pub const CAMPAIGN_MANIFEST: &str = "campaigns/main.json";
+pub const CAMPAIGN_PACK_MANIFEST: &str = "campaign.json";
 
 pub const CONFIG_FILENAME: &str = "ultimate_ninja_settings.json";
+pub const REPLAY_FILENAME_PREFIX: &str = "ultimate_ninja_replay";
+pub const GHOST_FILENAME_PREFIX: &str = "ultimate_ninja_ghost";
+pub const SPLITS_FILENAME_PREFIX: &str = "ultimate_ninja_splits";
+pub const CAMPAIGNS_DIRNAME: &str = "ultimate_ninja_campaigns";
+pub const EDITOR_DIRNAME: &str = "ultimate_ninja_levels";
+pub const EDITOR_DEFAULT_FILENAME: &str = "custom.txt";
+pub const RUNS_DIRNAME: &str = "ultimate_ninja_runs";
+pub const LEADERBOARD_FILENAME: &str = "ultimate_ninja_leaderboard.json";
+pub const OUTBOX_FILENAME: &str = "ultimate_ninja_outbox.json";
+
+pub const LEADERBOARD_SIZE: usize = 10;
+pub const NETWORK_TIMEOUT: f32 = 10.; // sec
+pub const SUBMIT_RETRY_DELAY: f32 = 2.; // sec, doubled after every failed attempt
+pub const SUBMIT_MAX_RETRY_DELAY: f32 = 120.; // sec
+
+pub const SPLITS_GAME_NAME: &str = "Ultimate Ninja";
+pub const SPLITS_CATEGORY_NAME: &str = "Any%";
 
 // pub const WIDTH: u32 = 1920;
 // pub const HEIGHT: u32 = 1080;
@@ -12,8 +39,10 @@ pub const HEIGHT: u32 = 600;
 pub const LEVEL_BLOCK_SIZE: u32 = 40; // size 40 produces 20x15 grid (at 800x600)
 
 pub const GRID_CELL_SIZE: usize = 5 * LEVEL_BLOCK_SIZE as usize;
+/// Length of the debug grid line mesh, it is scaled to the size of the loaded level
+pub const GRID_LINE_LENGTH: f32 = 100.;
 
-/// Number of characters in level text files
+/// Size of the world (in level blocks) before a level is loaded, each level header sets its own
 pub const LEVEL_SIZE: (u32, u32) = (30, 20);
 pub const MAX_WORLD_X: u32 = LEVEL_BLOCK_SIZE * LEVEL_SIZE.0;
 pub const MAX_WORLD_Y: u32 = LEVEL_BLOCK_SIZE * LEVEL_SIZE.1;
@@ -33,9 +62,22 @@ pub const PLAYER_ATTACK_RANGE: f32 = 42.;
 pub const PLAYER_SPEED_SLOW: f32 = 1.;
 pub const PLAYER_SPEED: f32 = 3.;
 pub const PLAYER_SPEED_FAST: f32 = 4.5;
+pub const PLAYER_SPEED_DRAG: f32 = 1.5;
+pub const BODY_DRAG_DISTANCE: f32 = 2. * ENTITY_SIZE;
+pub const HIDING_SPOT_BRIGHTNESS: f32 = 0.15;
+
+pub const THROW_COST: f32 = 15.;
+pub const THROW_DISTANCE: f32 = 240.;
+pub const THROW_SPEED: f32 = 320.; // px per sec
+pub const THROW_ARC_HEIGHT: f32 = 30.;
+pub const THROW_NOISE_RADIUS: f32 = 160.;
+pub const THROW_PROJECTILE_SIZE: f32 = 3.;
 pub const SOUND_RADIUS_SCALE: f32 = 0.5;
 
 pub const TARGET_SPEED: f32 = 1.;
+pub const TARGET_SPEED_FLEE: f32 = 2.;
+pub const TARGET_TURN_SPEED: f32 = 12.;
+pub const TARGET_FLEE_DISTANCE: u32 = 8; // level blocks
 pub const GUARD_SPEED_SLOW: f32 = 0.8;
 pub const GUARD_SPEED: f32 = 1.4;
 pub const GUARD_SPEED_MEDIUM: f32 = 2.5;
@@ -45,14 +87,39 @@ pub const GUARD_FOV_SMALL: f32 = PI / 18.;
 pub const GUARD_VIEW_DISTANCE: f32 = 170.;
 pub const GUARD_VIEW_DISTANCE_MEDIUM: f32 = 250.;
 pub const GUARD_VIEW_DISTANCE_LONG: f32 = 500.;
+pub const GUARD_TURN_SPEED: f32 = 10.;
+pub const GUARD_INVESTIGATE_TIMEOUT: f32 = 10.; // sec
+pub const GUARD_SEARCH_TIME: (f32, f32) = (4., 6.); // sec
+pub const PATH_POINT_RADIUS: f32 = 4.;
+
+pub const ALERT_REACTION_TIME: f32 = 1.5; // sec
+pub const ALERT_RELAY_DELAY: f32 = 1.; // sec
+pub const ALERT_RADIO_RADIUS: f32 = 400.;
+pub const BODY_INSPECT_TIME: f32 = 3.; // sec
+pub const ALERT_DURATION: f32 = 15.; // sec
+pub const SEARCH_DURATION: f32 = 20.; // sec
+pub const CAUTIOUS_DURATION: f32 = 30.; // sec
+
+pub const SUSPICION_RATE: f32 = 1.5; // per sec, when fully exposed
+pub const SUSPICION_MIN_RATE: f32 = 0.4; // per sec, at the edge of the view
+pub const SUSPICION_MOVE_SCALE: f32 = 0.25; // extra rate per unit of player speed
+pub const SUSPICION_ALERTED_SCALE: f32 = 2.;
+pub const SUSPICION_DECAY_RATE: f32 = 0.25; // per sec
+pub const SUSPICION_BAR_SIZE: glam::Vec2 = glam::vec2(24., 4.);
+pub const SUSPICION_BAR_OFFSET: f32 = 20.;
 
 pub const ANIMATION_SPEED: f32 = 0.1575;
+pub const WALK_ANIMATION_FRAMES: usize = 16;
 
 pub const DOUBLE_PRESS_TIME: f32 = 0.5; // sec
 pub const HOLD_THRESHOLD_TIME: f32 = 0.4; // sec
 
 pub const TELEPORT_COST: f32 = 25.;
 
+pub const STAMINA_MAX: f32 = 100.;
+pub const STAMINA_DECREASE_RATE: f32 = 60.; // per sec
+pub const STAMINA_INCREASE_RATE: f32 = 60.; // per sec
+
 pub const N_FOV_RAYS: u32 = 15;
 
 pub const BTN_DIM_RECT: glam::Vec2 = glam::vec2(WIDTH as f32 * 0.3, HEIGHT as f32 * 0.125);
@@ -71,7 +138,18 @@ pub const BTN_BOTTOM_LEFT_POS2: glam::Vec2 =
     glam::vec2(WIDTH as f32 * 0.025, HEIGHT as f32 * 0.7166);
 pub const BTN_BOTTOM_LEFT_POS3: glam::Vec2 =
     glam::vec2(WIDTH as f32 * 0.025, HEIGHT as f32 * 0.833);
+pub const BTN_BOTTOM_LEFT_POS4: glam::Vec2 = glam::vec2(WIDTH as f32 * 0.3, HEIGHT as f32 * 0.7166);
 
 pub const CAMERA_DEFAULT_LERP_DELTA: f32 = 0.33;
 
 pub const LEVEL_ANIMATION_TIME: f32 = 4.;
+
+pub const EDITOR_PAN_SPEED: f32 = 600.; // px/sec
+pub const EDITOR_MAX_WAIT: f32 = 5.; // sec
+pub const EDITOR_GENERATOR_DIFFICULTY: f32 = 0.5; // From 0 (easy) to 1 (hard)
+pub const EDITOR_CURSOR_COLOR: ggez::graphics::Color = ggez::graphics::Color::new(1., 1., 1., 0.8);
+pub const EDITOR_ROUTE_COLOR: ggez::graphics::Color = ggez::graphics::Color::new(1., 0.8, 0.2, 0.8);
+
+pub const GHOST_SAMPLE_TICKS: u32 = 6;
+pub const GHOST_SPLIT_WINDOW: usize = 20; // Samples searched after the last split
+pub const GHOST_COLOR: ggez::graphics::Color = ggez::graphics::Color::new(0., 0., 0., 0.3);
@@ -1,12 +1,15 @@
-use crate::SpriteComponent;
-
 pub struct DeadComponent {
-    pub sprite: SpriteComponent,
+    pub color: ggez::graphics::Color,
     pub is_dead: bool,
+    pub is_discovered: bool, // Body was already reported by a guard
 }
 
 impl DeadComponent {
-    pub fn new(sprite: SpriteComponent, is_dead: bool) -> Self {
-        Self { sprite, is_dead }
+    pub fn new(color: ggez::graphics::Color, is_dead: bool) -> Self {
+        Self {
+            color,
+            is_dead,
+            is_discovered: false,
+        }
     }
 }
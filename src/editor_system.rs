@@ -0,0 +1,354 @@
+use crate::constants;
+use crate::level::{self, LevelData, LevelHeader};
+use crate::level_generator::{self, GeneratorParams};
+use crate::level_lint;
+use crate::patrol_component::{PatrolComponent, PatrolMode, Waypoint};
+use crate::util;
+
+/// Glyphs which can be painted, `-` is the empty floor
+pub const PALETTE: &[char] = &[
+    '-', 'x', 'b', 'B', '1', '2', 'c', 'p', 't', 'g', 's', 'h', 'e',
+];
+
+/// Glyphs which appear once in a level, painting one moves it
+const UNIQUE_GLYPHS: &[char] = &['p', 't', 'e'];
+const GUARD_GLYPHS: &[char] = &['g', 's', 'h'];
+const SOLID_GLYPHS: &[char] = &['x', 'b', 'B'];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum EditorTool {
+    Paint,
+    /// Select a guard and click blocks to append waypoints to its route
+    Patrol,
+}
+
+/// Level being edited, it is written back in the level text format
+pub struct EditorSystem {
+    pub level: LevelData,
+    pub filename: String,
+    pub glyph: char,
+    pub tool: EditorTool,
+    pub selected_guard: Option<(u32, u32)>,
+    pub cursor: Option<(u32, u32)>,
+    pub camera_center: glam::Vec2,
+    pub pan_dir: glam::Vec2,
+    pub painting: Option<char>, // Glyph painted while a mouse button is held
+    pub is_playtest: bool,
+    pub is_modified: bool,
+    /// The file could not be parsed, saving asks for a confirmation before it overwrites the file
+    pub is_overwrite_protected: bool,
+    pub status: String,
+}
+
+impl EditorSystem {
+    /// Opens the level file, a missing file starts a new level which is saved under that name
+    pub fn open(filename: &str) -> Self {
+        let (level, is_overwrite_protected, status) = match std::fs::read_to_string(filename) {
+            Ok(source) => match level::parse_level(&source) {
+                Ok(level) => (level, false, format!("Opened {}", filename)),
+                Err(err) => (
+                    new_level(constants::LEVEL_SIZE),
+                    true,
+                    format!("Could not parse {}: {}", filename, err),
+                ),
+            },
+            Err(_) => (
+                new_level(constants::LEVEL_SIZE),
+                false,
+                format!("New level {}", filename),
+            ),
+        };
+
+        Self {
+            camera_center: level.header.world_size() / 2.,
+            level,
+            filename: filename.to_owned(),
+            glyph: 'x',
+            tool: EditorTool::Paint,
+            selected_guard: None,
+            cursor: None,
+            pan_dir: glam::Vec2::ZERO,
+            painting: None,
+            is_playtest: false,
+            is_modified: false,
+            is_overwrite_protected,
+            status,
+        }
+    }
+
+    /// ID of the level for the lint checks, the file name without the extension
+    pub fn level_id(&self) -> &str {
+        let filename = self.filename.rsplit(['/', '\\']).next().unwrap_or("");
+
+        filename.split('.').next().unwrap_or(filename)
+    }
+
+    pub fn level_text(&self) -> String {
+        level::level_to_string(&self.level)
+    }
+
+    pub fn diagnostics(&self) -> Vec<level_lint::Diagnostic> {
+        level_lint::lint_level(&self.level_text(), self.level_id(), false)
+    }
+
+    /// The level cannot be played or shared while it has errors
+    pub fn first_error(&self) -> Option<level_lint::Diagnostic> {
+        self.diagnostics()
+            .into_iter()
+            .find(|diagnostic| diagnostic.severity == level_lint::Severity::Error)
+    }
+
+    /// Writes the level to its file, the first save over a file which could not be parsed
+    /// only asks for a confirmation
+    pub fn save(&mut self) {
+        if self.is_overwrite_protected {
+            self.is_overwrite_protected = false;
+            self.status = format!(
+                "{} could not be parsed, save again to overwrite it",
+                self.filename
+            );
+            return;
+        }
+
+        if let Some(dir) = std::path::Path::new(&self.filename).parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+
+        self.status = match std::fs::write(&self.filename, self.level_text()) {
+            Ok(()) => {
+                self.is_modified = false;
+                format!("Saved {}", self.filename)
+            }
+            Err(err) => format!("Could not save {}: {}", self.filename, err),
+        };
+    }
+
+    /// Replaces the level with a generated one, the seed is kept in the level name
+    pub fn generate(&mut self, seed: u64) -> bool {
+        let params = GeneratorParams::with_difficulty(seed, constants::EDITOR_GENERATOR_DIFFICULTY);
+        match level_generator::generate_level(&params) {
+            Ok(level) => {
+                self.level = level;
+                self.selected_guard = None;
+                self.is_modified = true;
+                self.status = format!("Generated level from seed {}", seed);
+                true
+            }
+            Err(err) => {
+                self.status = format!("Could not generate a level: {}", err);
+                false
+            }
+        }
+    }
+
+    pub fn glyph_at(&self, cell: (u32, u32)) -> Option<char> {
+        self.level
+            .rows
+            .get(cell.1 as usize)
+            .and_then(|row| row.get(cell.0 as usize))
+            .copied()
+    }
+
+    /// Level block under the world position
+    pub fn cell_at(&self, position: glam::Vec2) -> Option<(u32, u32)> {
+        if position.x < 0. || position.y < 0. {
+            return None;
+        }
+
+        let cell = (
+            position.x as u32 / constants::LEVEL_BLOCK_SIZE,
+            position.y as u32 / constants::LEVEL_BLOCK_SIZE,
+        );
+        self.glyph_at(cell).map(|_| cell)
+    }
+
+    /// Returns true if the level changed
+    pub fn paint(&mut self, cell: (u32, u32), glyph: char) -> bool {
+        let Some(old_glyph) = self.glyph_at(cell).filter(|&old_glyph| old_glyph != glyph) else {
+            return false;
+        };
+
+        if UNIQUE_GLYPHS.contains(&glyph) {
+            self.level
+                .rows
+                .iter_mut()
+                .flatten()
+                .filter(|other| **other == glyph)
+                .for_each(|other| *other = '-');
+        }
+
+        // The route belongs to the guard glyph, it goes away with it
+        if GUARD_GLYPHS.contains(&old_glyph) && !GUARD_GLYPHS.contains(&glyph) {
+            self.level
+                .patrols
+                .retain(|(guard_cell, _)| *guard_cell != cell);
+            if self.selected_guard == Some(cell) {
+                self.selected_guard = None;
+            }
+        }
+
+        self.level.rows[cell.1 as usize][cell.0 as usize] = glyph;
+        self.is_modified = true;
+
+        true
+    }
+
+    /// Selects the guard on the block, or adds the block to the route of the selected guard.
+    /// Returns true if the level changed
+    pub fn patrol_click(&mut self, cell: (u32, u32)) -> bool {
+        let Some(glyph) = self.glyph_at(cell) else {
+            return false;
+        };
+
+        if GUARD_GLYPHS.contains(&glyph) {
+            self.selected_guard = Some(cell);
+            self.status = format!("Guard at {},{} selected", cell.0, cell.1);
+            return false;
+        }
+
+        let Some(guard_cell) = self.selected_guard else {
+            self.status = "Select a guard first".to_owned();
+            return false;
+        };
+        if SOLID_GLYPHS.contains(&glyph) {
+            self.status = format!("Block {},{} is not walkable", cell.0, cell.1);
+            return false;
+        }
+
+        let waypoint = Waypoint::new(cell, 0., None);
+        match self.patrol_mut(guard_cell) {
+            Some(patrol) => patrol.waypoints.push(waypoint),
+            None => self.level.patrols.push((
+                guard_cell,
+                PatrolComponent::new(vec![waypoint], PatrolMode::Loop),
+            )),
+        }
+        self.is_modified = true;
+
+        true
+    }
+
+    /// Removes the last waypoint of the selected guard, the route goes away with its last waypoint
+    pub fn remove_waypoint(&mut self) -> bool {
+        let Some(guard_cell) = self.selected_guard else {
+            return false;
+        };
+        let Some(patrol) = self.patrol_mut(guard_cell) else {
+            return false;
+        };
+
+        patrol.waypoints.pop();
+        if patrol.waypoints.is_empty() {
+            self.level.patrols.retain(|(cell, _)| *cell != guard_cell);
+        }
+        self.is_modified = true;
+
+        true
+    }
+
+    pub fn toggle_patrol_mode(&mut self) -> bool {
+        let Some(patrol) = self.selected_guard.and_then(|cell| self.patrol_mut(cell)) else {
+            return false;
+        };
+
+        patrol.mode = match patrol.mode {
+            PatrolMode::Loop => PatrolMode::PingPong,
+            PatrolMode::PingPong => PatrolMode::Loop,
+        };
+        self.is_modified = true;
+
+        true
+    }
+
+    /// Adds a second to the wait time of the last waypoint, it wraps around to no wait
+    pub fn cycle_wait_time(&mut self) -> bool {
+        let Some(waypoint) = self.last_waypoint_mut() else {
+            return false;
+        };
+
+        waypoint.wait_time = (waypoint.wait_time.floor() + 1.) % (constants::EDITOR_MAX_WAIT + 1.);
+        self.is_modified = true;
+
+        true
+    }
+
+    /// Turns the look direction of the last waypoint by 90 degrees, after a full turn it is removed
+    pub fn cycle_look_angle(&mut self) -> bool {
+        let Some(waypoint) = self.last_waypoint_mut() else {
+            return false;
+        };
+
+        waypoint.look_angle = match waypoint.look_angle {
+            None => Some(0.),
+            Some(angle) if angle >= 270. => None,
+            Some(angle) => Some((angle / 90.).floor() * 90. + 90.),
+        };
+        self.is_modified = true;
+
+        true
+    }
+
+    pub fn patrol(&self, guard_cell: (u32, u32)) -> Option<&PatrolComponent> {
+        self.level
+            .patrols
+            .iter()
+            .find(|(cell, _)| *cell == guard_cell)
+            .map(|(_, patrol)| patrol)
+    }
+
+    fn patrol_mut(&mut self, guard_cell: (u32, u32)) -> Option<&mut PatrolComponent> {
+        self.level
+            .patrols
+            .iter_mut()
+            .find(|(cell, _)| *cell == guard_cell)
+            .map(|(_, patrol)| patrol)
+    }
+
+    fn last_waypoint_mut(&mut self) -> Option<&mut Waypoint> {
+        let guard_cell = self.selected_guard?;
+        self.patrol_mut(guard_cell)?.waypoints.last_mut()
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        let world_size = self.level.header.world_size();
+
+        self.camera_center = (self.camera_center + self.pan_dir * constants::EDITOR_PAN_SPEED * dt)
+            .clamp(glam::Vec2::ZERO, world_size);
+    }
+}
+
+/// New levels are saved into the level directory of the user
+pub fn default_filename() -> String {
+    util::home_filename(&format!(
+        "{}/{}",
+        constants::EDITOR_DIRNAME,
+        constants::EDITOR_DEFAULT_FILENAME
+    ))
+}
+
+/// Empty level which is enclosed by walls
+pub fn new_level(size: (u32, u32)) -> LevelData {
+    let rows = (0..size.1)
+        .map(|y| {
+            (0..size.0)
+                .map(|x| {
+                    if x == 0 || y == 0 || x == size.0 - 1 || y == size.1 - 1 {
+                        'x'
+                    } else {
+                        '-'
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    LevelData {
+        header: LevelHeader {
+            version: constants::LEVEL_FORMAT_VERSION,
+            size,
+            ..LevelHeader::default()
+        },
+        rows,
+        patrols: vec![],
+    }
+}
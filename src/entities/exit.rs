@@ -1,38 +1,31 @@
 use crate::constants;
-use crate::sprite_component::SpriteComponent;
 use crate::transform_component::TransformComponent;
-use crate::util;
-use crate::Game;
+use crate::world::World;
 
 pub struct Exit {
     pub transform: TransformComponent,
-    pub sprite: SpriteComponent,
+    pub scale: f32,
     pub scale_rotation_counter: f32,
     pub player_exited: bool,
 }
 
 impl Exit {
-    pub fn new(position: glam::Vec2, sprite: SpriteComponent) -> Self {
+    pub fn new(position: glam::Vec2, world_width: u32) -> Self {
         Self {
-            transform: TransformComponent::new(
-                position,
-                constants::ENTITY_SIZE,
-                util::compute_grid_index(&position),
-            ),
-            sprite,
+            transform: TransformComponent::new(position, constants::ENTITY_SIZE, world_width),
+            scale: 0.4,
             scale_rotation_counter: 0.,
             player_exited: false,
         }
     }
 
     pub fn update(&mut self, dt: f32) {
-        self.sprite
-            .set_scale(self.scale_rotation_counter.sin() * 0.05 + 0.4);
+        self.scale = self.scale_rotation_counter.sin() * 0.05 + 0.4;
 
         self.scale_rotation_counter = (self.scale_rotation_counter + dt) % (2. * constants::PI);
     }
 }
 
-pub fn system(game_state: &mut Game, dt: f32) {
-    game_state.exit.update(dt);
+pub fn system(world: &mut World, dt: f32) {
+    world.exit.update(dt);
 }
@@ -2,24 +2,25 @@ pub mod guard_basic;
 pub mod guard_heavy;
 pub mod guard_scout;
 
+use crate::alert_system::AlertLevel;
 use crate::animation_component::AnimationComponent;
 use crate::animation_component::AnimationState;
 use crate::compute_move_component::ComputeMoveComponent;
 use crate::constants;
 use crate::dead_component::DeadComponent;
 use crate::entities;
-use crate::entities::wall::Wall;
 use crate::entities::AABBCollisionComponent;
 use crate::look_component::LookComponent;
 use crate::move_component::MoveComponent;
-use crate::sprite_component::SpriteComponent;
+use crate::navigation_system::NavigationSystem;
+use crate::noise_system::NoiseEvent;
+use crate::path_component::PathComponent;
+use crate::patrol_component::PatrolComponent;
+use crate::rng::GameRng;
+use crate::suspicion_component::SuspicionComponent;
 use crate::transform_component::TransformComponent;
 use crate::util;
-use crate::Assets;
-use crate::Game;
-use crate::GameState;
-
-use quad_rand as qrand;
+use crate::world::{Sound, World, WorldEvent};
 
 pub struct Guard {
     pub guard_state: GuardState,
@@ -31,6 +32,9 @@ pub struct Guard {
     pub compute_move_component: ComputeMoveComponent,
     pub look_components: Vec<LookComponent>,
     pub look_idx: usize,
+    pub patrol: Option<PatrolComponent>,
+    pub path: PathComponent,
+    pub suspicion: SuspicionComponent,
 
     pub look_color: ggez::graphics::Color,
     pub move_dir: glam::Vec2,
@@ -38,44 +42,46 @@ pub struct Guard {
     pub move_interval: f32,
     pub wall_move_interval: f32,
 
+    pub reported_body: Option<glam::Vec2>,
+    pub is_alerted: bool,
     pub is_tutorial: bool,
 }
 
 #[derive(PartialEq)]
 pub enum GuardState {
     HeardPlayer(glam::Vec2),
+    Investigate(glam::Vec2),
+    InspectBody(glam::Vec2),
+    LookAroundBody(glam::Vec2, f32),
     Lookout(f32),
     Walk,
     Alert,
 }
 
+/// Progress of a guard walking to a destination
+#[derive(PartialEq)]
+enum WalkProgress {
+    Walking,
+    Arrived,
+    Unreachable,
+}
+
 impl Guard {
     pub fn new(
-        ctx: &mut ggez::Context,
-        quad_ctx: &mut ggez::miniquad::GraphicsContext,
         position: glam::Vec2,
-        assets: &Assets,
         color: ggez::graphics::Color,
         is_tutorial: bool,
+        world_width: u32,
     ) -> Self {
         Self {
             guard_state: GuardState::Walk,
-            transform: TransformComponent::new(
-                position,
-                constants::ENTITY_SIZE,
-                util::compute_grid_index(&position),
-            ),
+            transform: TransformComponent::new(position, constants::ENTITY_SIZE, world_width),
             animation: util::build_walk_animation(
-                &assets,
                 util::compute_animation_duration(constants::GUARD_SPEED),
                 color,
             ),
             dead_component: DeadComponent::new(
-                SpriteComponent::new(
-                    assets.dead.clone(),
-                    ggez::graphics::Color::new(0.5, 0.5, 0.5, 0.75),
-                )
-                .scale(constants::SPRITE_SCALE),
+                ggez::graphics::Color::new(0.5, 0.5, 0.5, 0.75),
                 false,
             ),
             move_component: MoveComponent::new(constants::GUARD_SPEED),
@@ -85,27 +91,25 @@ impl Guard {
                 constants::ENTITY_SIZE,
                 constants::ENTITY_SIZE,
             )),
-            compute_move_component: ComputeMoveComponent::new(
-                ctx,
-                quad_ctx,
-                8,
-                constants::GUARD_VIEW_DISTANCE,
-            ),
-            look_components: vec![LookComponent::new_with_mesh(
-                ctx,
-                quad_ctx,
+            compute_move_component: ComputeMoveComponent::new(8, constants::GUARD_VIEW_DISTANCE),
+            look_components: vec![LookComponent::new(
                 glam::vec2(0., 1.),
                 constants::GUARD_FOV,
                 constants::GUARD_VIEW_DISTANCE,
                 constants::N_FOV_RAYS,
             )],
             look_idx: 0,
+            patrol: None,
+            path: PathComponent::new(),
+            suspicion: SuspicionComponent::new(),
             look_color: ggez::graphics::Color::WHITE,
             move_dir: glam::Vec2::ZERO,
             max_move_interval: 0.,
             move_interval: 0.,
             wall_move_interval: 0.,
 
+            reported_body: None,
+            is_alerted: false,
             is_tutorial,
         }
     }
@@ -144,6 +148,32 @@ impl Guard {
         self.look_color = look_color;
     }
 
+    #[inline]
+    fn set_alerted(&mut self) {
+        self.is_alerted = true;
+        self.guard_state = GuardState::Alert;
+        self.set_look_color(ggez::graphics::Color::RED);
+    }
+
+    /// Guards which did not get the alert over the radio are at most cautious
+    #[inline]
+    pub fn get_alert_level(&self, alert_level: AlertLevel) -> AlertLevel {
+        if self.is_alerted {
+            alert_level
+        } else {
+            alert_level.min(AlertLevel::Cautious)
+        }
+    }
+
+    #[inline]
+    fn calm_down(&mut self) {
+        self.is_alerted = false;
+
+        if self.guard_state == GuardState::Alert {
+            self.guard_state = GuardState::Walk;
+        }
+    }
+
     #[inline]
     pub fn set_colliding_vec_components(&mut self, colliding_axis: (bool, bool)) {
         self.aabb.colliding_axis = colliding_axis;
@@ -159,29 +189,20 @@ impl Guard {
         self.dead_component.is_dead = is_dead;
     }
 
-    pub fn get_curr_animation_frame(&self) -> &SpriteComponent {
-        if self.dead_component.is_dead {
-            return &self.dead_component.sprite;
-        }
-
-        self.animation.get_curr_frame()
-    }
-
     fn set_lookout(
         &mut self,
         speed_low: f32,
         speed_high: f32,
         duration_low: f32,
         duration_high: f32,
+        rng: &mut GameRng,
     ) {
-        let lookout_speed = qrand::gen_range(speed_low, speed_high)
-            * (qrand::gen_range::<f32>(0., 1.) >= 0.5)
-                .then(|| 1.)
-                .unwrap_or(-1.);
+        let lookout_dir = if rng.gen_bool() { 1. } else { -1. };
+        let lookout_speed = rng.gen_range(speed_low, speed_high) * lookout_dir;
 
         self.guard_state = GuardState::Lookout(lookout_speed);
 
-        self.max_move_interval = qrand::gen_range(duration_low, duration_high);
+        self.max_move_interval = rng.gen_range(duration_low, duration_high);
         self.move_interval = self.max_move_interval;
     }
 
@@ -190,6 +211,7 @@ impl Guard {
         rect_objects: &Vec<(&ggez::graphics::Rect, isize)>,
         move_interval_low: f32,
         move_interval_high: f32,
+        rng: &mut GameRng,
     ) {
         let base_look_component = &mut self.look_components[0];
         base_look_component.update(&self.transform, rect_objects);
@@ -208,12 +230,12 @@ impl Guard {
         if self.move_interval <= 0. || is_close_to_wall && self.wall_move_interval <= 0. {
             let new_dir = self
                 .compute_move_component
-                .get_move_direction(&self.transform, wall_ray_scale, rect_objects)
+                .get_move_direction(&self.transform, wall_ray_scale, rect_objects, rng)
                 .normalize_or_zero();
 
             self.move_dir = new_dir;
 
-            self.max_move_interval = qrand::gen_range(move_interval_low, move_interval_high);
+            self.max_move_interval = rng.gen_range(move_interval_low, move_interval_high);
             self.move_interval = self.max_move_interval;
 
             if is_close_to_wall {
@@ -242,9 +264,113 @@ impl Guard {
         }
     }
 
-    fn do_heard_player(&mut self, dir_to_player: glam::Vec2, guards_alerted: bool) {
+    /// Walks towards `destination` along a path from the navigation system
+    fn go_to(
+        &mut self,
+        destination: glam::Vec2,
+        dt: f32,
+        speed: f32,
+        navigation: &NavigationSystem,
+    ) -> WalkProgress {
+        if self.path.destination != Some(destination) {
+            match navigation.find_path(self.transform.position, destination) {
+                Some(points) => self.path.set_path(points, destination),
+                None => {
+                    // Walking straight at it would pin the guard against a wall
+                    self.path.clear();
+                    self.set_speed(0.);
+
+                    return WalkProgress::Unreachable;
+                }
+            }
+        }
+
+        let next_point = match self
+            .path
+            .next_point(self.transform.position, constants::PATH_POINT_RADIUS)
+        {
+            Some(next_point) => next_point,
+            None => {
+                self.path.clear();
+                self.set_speed(0.);
+
+                return WalkProgress::Arrived;
+            }
+        };
+
+        self.move_dir = (next_point - self.transform.position).normalize_or_zero();
+        let lerped_dir = util::vec_lerp(
+            self.move_component.direction,
+            self.move_dir,
+            (dt * constants::GUARD_TURN_SPEED).min(1.),
+        );
+
+        self.move_component.set_direction_normalized(lerped_dir);
+        self.set_angle(self.move_component.direction);
+        self.set_speed(speed);
+
+        WalkProgress::Walking
+    }
+
+    /// Walks along the patrol route, returns true while the guard waits at a waypoint
+    fn do_patrol(&mut self, dt: f32, speed: f32, navigation: &NavigationSystem) -> bool {
+        let (waypoint_position, look_dir, is_waiting) = match self.patrol.as_mut() {
+            Some(patrol) => {
+                let waypoint = patrol.current_waypoint();
+                let (waypoint_position, look_dir) = (waypoint.position(), waypoint.look_dir());
+
+                let is_waiting = patrol.is_waiting();
+                if is_waiting {
+                    patrol.update_wait(dt);
+                }
+
+                (waypoint_position, look_dir, is_waiting)
+            }
+            None => return false,
+        };
+
+        if is_waiting {
+            self.set_speed(0.);
+
+            if let Some(look_dir) = look_dir {
+                let lerped_dir = util::vec_lerp(
+                    self.move_component.direction,
+                    look_dir,
+                    (dt * constants::GUARD_TURN_SPEED).min(1.),
+                );
+
+                self.move_dir = look_dir;
+                self.move_component.set_direction_normalized(lerped_dir);
+                self.set_angle(self.move_component.direction);
+            }
+
+            return true;
+        }
+
+        match self.go_to(waypoint_position, dt, speed, navigation) {
+            WalkProgress::Arrived => {
+                if let Some(patrol) = self.patrol.as_mut() {
+                    patrol.start_waiting();
+                }
+
+                true
+            }
+            WalkProgress::Unreachable => {
+                if let Some(patrol) = self.patrol.as_mut() {
+                    patrol.advance();
+                }
+
+                false
+            }
+            WalkProgress::Walking => false,
+        }
+    }
+
+    fn do_heard_player(&mut self, sound_position: glam::Vec2, guards_alerted: bool) {
         self.set_speed(0.);
 
+        let dir_to_player = (sound_position - self.transform.position).normalize_or_zero();
+
         let lerp_to_player = util::vec_lerp(
             self.move_dir,
             dir_to_player,
@@ -260,11 +386,83 @@ impl Guard {
             if guards_alerted {
                 self.guard_state = GuardState::Alert;
             } else {
-                self.set_lookout(0.01, 0.7, 3., 4.);
+                self.start_investigate(sound_position);
             }
         }
     }
 
+    /// Fills or decays the suspicion meter, which is also shown through the look color
+    fn update_suspicion(&mut self, fill_rate: Option<f32>, dt: f32) {
+        self.suspicion.update(fill_rate, dt);
+
+        if !self.is_alerted {
+            let calm = 1. - self.suspicion.suspicion;
+            self.set_look_color(ggez::graphics::Color::new(1., calm, calm, 1.));
+        }
+    }
+
+    fn start_investigate(&mut self, position: glam::Vec2) {
+        self.guard_state = GuardState::Investigate(position);
+        self.path.clear();
+
+        self.max_move_interval = constants::GUARD_INVESTIGATE_TIMEOUT;
+        self.move_interval = self.max_move_interval;
+    }
+
+    /// Walks to the investigated position, returns true once the guard gets there
+    /// (or gives up) and should start searching the area
+    fn do_investigate(
+        &mut self,
+        position: glam::Vec2,
+        dt: f32,
+        speed: f32,
+        navigation: &NavigationSystem,
+    ) -> bool {
+        if self.go_to(position, dt, speed, navigation) != WalkProgress::Walking
+            || self.move_interval <= 0.
+        {
+            self.path.clear();
+            return true;
+        }
+
+        false
+    }
+
+    fn start_inspect_body(&mut self, body_position: glam::Vec2) {
+        self.start_investigate(body_position);
+        self.guard_state = GuardState::InspectBody(body_position);
+    }
+
+    /// Walks to the body, then starts looking around it
+    fn do_inspect_body(
+        &mut self,
+        body_position: glam::Vec2,
+        dt: f32,
+        speed: f32,
+        navigation: &NavigationSystem,
+        rng: &mut GameRng,
+    ) {
+        if self.do_investigate(body_position, dt, speed, navigation) {
+            let lookout_speed = rng.gen_range(0.5, 0.9);
+            self.guard_state = GuardState::LookAroundBody(body_position, lookout_speed);
+            self.set_large_look_component();
+
+            self.max_move_interval = constants::BODY_INSPECT_TIME;
+            self.move_interval = self.max_move_interval;
+        }
+    }
+
+    /// Looks around the body, once done the body is reported
+    fn do_look_around_body(&mut self, body_position: glam::Vec2, lookout_speed: f32, dt: f32) {
+        let lookout_dir = util::vec_from_angle(self.transform.angle + dt * lookout_speed);
+        self.do_lookout(lookout_dir);
+
+        if self.move_interval <= 0. {
+            self.reported_body = Some(body_position);
+            self.guard_state = GuardState::Walk;
+        }
+    }
+
     fn do_lookout(&mut self, lookout_dir: glam::Vec2) {
         self.set_speed(0.);
         self.move_dir = lookout_dir;
@@ -273,7 +471,7 @@ impl Guard {
         self.set_angle(self.move_component.direction);
     }
 
-    fn update(&mut self, dt: f32, player_sound: &TransformComponent) {
+    fn update(&mut self, dt: f32, noise_events: &[NoiseEvent], rng: &mut GameRng) {
         if self.dead_component.is_dead {
             return;
         }
@@ -295,15 +493,33 @@ impl Guard {
             self.animation.set_animation_state(AnimationState::Active);
         }
 
-        if !matches!(self.guard_state, GuardState::HeardPlayer(_))
-            && util::check_collision(&self.transform, player_sound)
-        {
-            let dir_to_player =
-                (player_sound.position - self.transform.position).normalize_or_zero();
-            self.guard_state = GuardState::HeardPlayer(dir_to_player);
-
-            self.max_move_interval = qrand::gen_range(0.3, 0.4);
-            self.move_interval = self.max_move_interval;
+        let heard_noise = noise_events
+            .iter()
+            .filter(|noise| noise.is_heard_at(self.transform.position, self.transform.size))
+            .min_by(|n1, n2| {
+                let dist1 = (n1.position - self.transform.position).length_squared();
+                let dist2 = (n2.position - self.transform.position).length_squared();
+
+                dist1.total_cmp(&dist2)
+            });
+
+        if let Some(noise) = heard_noise {
+            match self.guard_state {
+                // Busy with the body, sounds are ignored
+                GuardState::HeardPlayer(_)
+                | GuardState::InspectBody(_)
+                | GuardState::LookAroundBody(_, _) => {}
+                // Already on the way, just follow the sound
+                GuardState::Investigate(_) => {
+                    self.guard_state = GuardState::Investigate(noise.position);
+                }
+                _ => {
+                    self.guard_state = GuardState::HeardPlayer(noise.position);
+
+                    self.max_move_interval = rng.gen_range(0.3, 0.4);
+                    self.move_interval = self.max_move_interval;
+                }
+            }
         }
 
         self.move_interval = (self.move_interval - dt).max(-1.);
@@ -311,129 +527,298 @@ impl Guard {
     }
 }
 
-pub fn alert_all(ctx: &mut ggez::Context, game_state: &mut Game) {
-    game_state
+/// Alerts the guard at `guard_idx` and relays the alert over the radio to the guards nearby
+fn raise_alert(world: &mut World, guard_idx: usize) {
+    let guard_position = {
+        let mut guards = world.get_all_guards_mut();
+        let guard = &mut guards[guard_idx];
+
+        // Guard was killed before the alarm went out
+        if guard.is_dead() {
+            return;
+        }
+
+        guard.set_alerted();
+        guard.transform.position
+    };
+
+    if world.alert_system.alert_level != AlertLevel::Alert {
+        world
+            .events
+            .push(WorldEvent::Sound(Sound::DeadTargetDetected, None));
+    }
+    world.alert_system.set_level(AlertLevel::Alert);
+    world
+        .alert_system
+        .search_focus
+        .get_or_insert(guard_position);
+
+    let relay_guards = world
+        .get_all_guards()
+        .iter()
+        .enumerate()
+        .filter(|(_, guard)| {
+            !guard.is_dead()
+                && !guard.is_alerted
+                && (guard.transform.position - guard_position).length_squared()
+                    <= constants::ALERT_RADIO_RADIUS.powi(2)
+        })
+        .map(|(idx, _)| idx)
+        .collect::<Vec<usize>>();
+
+    relay_guards
+        .into_iter()
+        .for_each(|idx| world.alert_system.raise(idx, constants::ALERT_RELAY_DELAY));
+}
+
+/// Adjusts the alerted guards to the lowered alert level
+fn lower_alert_level(world: &mut World) {
+    let alert_level = world.alert_system.alert_level;
+    let search_focus = world.alert_system.search_focus;
+
+    world
         .get_all_guards_mut()
-        .iter_mut()
+        .into_iter()
+        .filter(|guard| !guard.is_dead() && guard.is_alerted)
         .for_each(|guard| {
-            guard.guard_state = GuardState::Alert;
-            guard.set_look_color(ggez::graphics::Color::RED);
+            if alert_level == AlertLevel::Search {
+                guard.start_investigate(search_focus.unwrap_or(guard.transform.position));
+            } else {
+                guard.calm_down();
+            }
         });
-
-    game_state.are_guards_alerted = true;
-    game_state.sound_collection.play(ctx, 6).unwrap_or_default();
 }
 
-pub fn system(ctx: &mut ggez::Context, game_state: &mut Game, dt: f32) {
-    if is_transform_detected(
-        &game_state.walls,
-        &game_state.get_all_guards(),
-        &game_state.player.transform,
-        game_state.player.is_stealth,
-    ) {
-        game_state.game_state = GameState::GameOver;
-        game_state.sound_collection.play(ctx, 4).unwrap_or_default();
+/// Marks the bodies at `body_position` as discovered, so they are not reported again
+fn mark_body_discovered(world: &mut World, body_position: glam::Vec2) {
+    let is_at_position = |transform: &TransformComponent| {
+        (transform.position - body_position).length_squared()
+            <= (constants::LEVEL_BLOCK_SIZE as f32).powi(2)
+    };
+
+    if world.target.is_dead() && is_at_position(&world.target.transform) {
+        world.target.dead_component.is_discovered = true;
     }
 
-    if !game_state.are_guards_alerted
-        && game_state.target.is_dead()
-        && is_transform_detected(
-            &game_state.walls,
-            &game_state.get_all_guards(),
-            &game_state.target.transform,
-            false,
+    world
+        .get_all_guards_mut()
+        .into_iter()
+        .filter(|guard| guard.is_dead() && is_at_position(&guard.transform))
+        .for_each(|guard| guard.dead_component.is_discovered = true);
+}
+
+/// Returns indices of guards which are not alerted or busy and see an undiscovered body
+/// that is not stashed in a hiding spot,
+/// together with the position of that body
+fn find_body_spotters(world: &World) -> Vec<(usize, glam::Vec2)> {
+    let aabb_objects = world
+        .walls
+        .iter()
+        .map(|wall| &wall.aabb.rect)
+        .collect::<Vec<&ggez::graphics::Rect>>();
+
+    let guards = world.get_all_guards();
+    let target = &world.target;
+    let bodies = guards
+        .iter()
+        .filter(|guard| guard.is_dead() && !guard.dead_component.is_discovered)
+        .map(|guard| &guard.transform)
+        .chain(
+            (target.is_dead() && !target.dead_component.is_discovered).then_some(&target.transform),
         )
-    {
-        alert_all(ctx, game_state);
-    }
+        .filter(|transform| !world.is_hidden(transform.position))
+        .collect::<Vec<&TransformComponent>>();
 
-    let guards = game_state.get_all_guards();
-    let alive_guards = game_state
-        .get_all_guards()
+    guards
         .iter()
-        .filter(|guard| !guard.is_dead())
-        .map(|guard| *guard)
-        .collect::<Vec<&Guard>>();
-    let dead_guards = guards.iter().filter(|guard| guard.is_dead());
-
-    let dead_guard_detected = dead_guards
-        .map(|dead_guard| {
-            is_transform_detected(
-                &game_state.walls,
-                &alive_guards,
-                &dead_guard.transform,
-                false,
-            )
+        .enumerate()
+        .filter(|(idx, guard)| {
+            !guard.is_dead()
+                && !guard.is_alerted
+                && !world.alert_system.is_pending(*idx)
+                && !matches!(
+                    guard.guard_state,
+                    GuardState::InspectBody(_) | GuardState::LookAroundBody(_, _)
+                )
+        })
+        .filter_map(|(idx, guard)| {
+            bodies
+                .iter()
+                .find(|body| {
+                    util::check_spotted(
+                        &guard.look_components[guard.look_idx],
+                        &guard.transform,
+                        body,
+                        &aabb_objects,
+                    )
+                })
+                .map(|body| (idx, body.position))
         })
-        .any(|is_detected| is_detected);
+        .collect()
+}
+
+pub fn system(world: &mut World, dt: f32) {
+    let fill_rates = compute_suspicion_fill_rates(world);
+    let player_spotters = fill_rates
+        .iter()
+        .enumerate()
+        .filter(|(_, fill_rate)| fill_rate.is_some())
+        .map(|(idx, _)| idx)
+        .collect::<Vec<usize>>();
+
+    let mut is_player_detected = false;
+    for (guard, fill_rate) in world.get_all_guards_mut().into_iter().zip(fill_rates) {
+        if guard.is_dead() {
+            continue;
+        }
+
+        guard.update_suspicion(fill_rate, dt);
+        is_player_detected |= guard.suspicion.is_full();
+    }
+
+    if is_player_detected {
+        world.is_player_detected = true;
+        world
+            .events
+            .push(WorldEvent::Sound(Sound::PlayerDead, None));
+    }
 
-    if !game_state.are_guards_alerted && dead_guard_detected {
-        alert_all(ctx, game_state);
+    let alert_level = world.alert_system.alert_level;
+    if !player_spotters.is_empty() && alert_level != AlertLevel::Calm {
+        world.alert_system.search_focus = Some(world.player.transform.position);
     }
 
-    let aabb_objects = game_state
+    // Guards which spot the player while the others are still searching call the alert again
+    if matches!(alert_level, AlertLevel::Search | AlertLevel::Cautious) {
+        player_spotters.iter().for_each(|idx| {
+            world
+                .alert_system
+                .raise(*idx, constants::ALERT_REACTION_TIME)
+        });
+    }
+
+    if world
+        .alert_system
+        .update_level(dt, !player_spotters.is_empty())
+    {
+        lower_alert_level(world);
+    }
+
+    find_body_spotters(world)
+        .into_iter()
+        .for_each(|(idx, body_position)| {
+            world.get_all_guards_mut()[idx].start_inspect_body(body_position)
+        });
+
+    // Guards which finished inspecting a body raise the alarm
+    let body_reports = world
+        .get_all_guards_mut()
+        .into_iter()
+        .enumerate()
+        .filter_map(|(idx, guard)| guard.reported_body.take().map(|body| (idx, body)))
+        .collect::<Vec<(usize, glam::Vec2)>>();
+
+    body_reports.into_iter().for_each(|(idx, body_position)| {
+        mark_body_discovered(world, body_position);
+
+        world.alert_system.search_focus = Some(body_position);
+        world.alert_system.raise(idx, 0.);
+    });
+
+    world
+        .alert_system
+        .update(dt)
+        .into_iter()
+        .for_each(|idx| raise_alert(world, idx));
+
+    let aabb_objects = world
         .walls
         .iter()
         .map(|wall| (&wall.aabb.rect, wall.transform.grid_index))
         .collect::<Vec<(&ggez::graphics::Rect, isize)>>();
 
     let (p_position, p_sound_radius) = (
-        game_state.player.transform.position,
-        game_state.player.get_sound_radius(),
+        world.player.transform.position,
+        world.player.get_sound_radius(),
     );
-    let player_sound = TransformComponent::new(p_position, p_sound_radius, -1);
+    world.noise_system.emit(p_position, p_sound_radius);
+    let noise_events = world.noise_system.take_events();
+    let alert_level = world.alert_system.alert_level;
 
-    game_state.guards_basic.iter_mut().for_each(|guard| {
+    world.guards_basic.iter_mut().for_each(|guard| {
         guard.update(
             dt,
             &aabb_objects,
-            &player_sound,
-            game_state.are_guards_alerted,
+            &noise_events,
+            guard.guard.get_alert_level(alert_level),
+            &world.navigation_system,
+            &mut world.rng,
         )
     });
 
-    game_state.guards_scout.iter_mut().for_each(|guard| {
+    world.guards_scout.iter_mut().for_each(|guard| {
         guard.update(
             dt,
             &aabb_objects,
-            &player_sound,
-            game_state.are_guards_alerted,
+            &noise_events,
+            guard.guard.get_alert_level(alert_level),
+            &world.navigation_system,
+            &mut world.rng,
         )
     });
 
-    game_state.guards_heavy.iter_mut().for_each(|guard| {
+    world.guards_heavy.iter_mut().for_each(|guard| {
         guard.update(
             dt,
             &aabb_objects,
-            &player_sound,
-            game_state.are_guards_alerted,
+            &noise_events,
+            guard.guard.get_alert_level(alert_level),
+            &world.navigation_system,
+            &mut world.rng,
         )
     });
 }
 
-pub fn is_transform_detected(
-    walls: &Vec<Wall>,
-    guards: &Vec<&Guard>,
-    transform: &TransformComponent,
-    is_stealth: bool,
-) -> bool {
-    // If transform is stealth, it cannot be detected
-    if is_stealth {
-        return false;
+/// Computes how fast the suspicion of each guard fills, `None` if the guard does not see the player.
+/// Suspicion fills faster the closer and more central the player is in the view and the faster they move.
+fn compute_suspicion_fill_rates(world: &World) -> Vec<Option<f32>> {
+    let player = &world.player;
+    if player.is_stealth {
+        return vec![None; world.get_all_guards().len()];
     }
 
-    let aabb_objects = walls
+    let aabb_objects = world
+        .walls
         .iter()
         .map(|wall| &wall.aabb.rect)
         .collect::<Vec<&ggez::graphics::Rect>>();
 
-    guards.iter().any(|guard| {
-        !guard.is_dead()
-            && util::check_spotted(
+    let rate_scale = 1. + player.get_move_magnitude() * constants::SUSPICION_MOVE_SCALE;
+
+    world
+        .get_all_guards()
+        .iter()
+        .map(|guard| {
+            if guard.is_dead() {
+                return None;
+            }
+
+            util::compute_exposure(
                 &guard.look_components[guard.look_idx],
                 &guard.transform,
-                transform,
+                &player.transform,
                 &aabb_objects,
             )
-    })
+            .map(|exposure| {
+                (constants::SUSPICION_MIN_RATE
+                    + exposure * (constants::SUSPICION_RATE - constants::SUSPICION_MIN_RATE))
+                    * rate_scale
+                    * if guard.is_alerted {
+                        constants::SUSPICION_ALERTED_SCALE
+                    } else {
+                        1.
+                    }
+            })
+        })
+        .collect()
 }
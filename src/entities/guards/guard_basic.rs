@@ -1,11 +1,11 @@
+use crate::alert_system::AlertLevel;
 use crate::constants;
 use crate::entities::guards::Guard;
 use crate::entities::guards::GuardState;
-use crate::transform_component::TransformComponent;
+use crate::navigation_system::NavigationSystem;
+use crate::noise_system::NoiseEvent;
+use crate::rng::GameRng;
 use crate::util;
-use crate::Assets;
-
-use quad_rand as qrand;
 
 pub struct GuardBasic {
     pub guard: Guard,
@@ -13,15 +13,13 @@ pub struct GuardBasic {
 
 impl GuardBasic {
     pub fn new(
-        ctx: &mut ggez::Context,
-        quad_ctx: &mut ggez::miniquad::GraphicsContext,
         position: glam::Vec2,
-        assets: &Assets,
         color: ggez::graphics::Color,
         is_tutorial: bool,
+        world_width: u32,
     ) -> Self {
         Self {
-            guard: Guard::new(ctx, quad_ctx, position, assets, color, is_tutorial),
+            guard: Guard::new(position, color, is_tutorial, world_width),
         }
     }
 
@@ -33,17 +31,43 @@ impl GuardBasic {
         &mut self,
         dt: f32,
         rect_objects: &Vec<(&ggez::graphics::Rect, isize)>,
-        player_sound: &TransformComponent,
-        guards_alerted: bool,
+        noise_events: &[NoiseEvent],
+        alert_level: AlertLevel,
+        navigation: &NavigationSystem,
+        rng: &mut GameRng,
     ) {
         if self.is_dead() {
             return;
         }
 
         match self.guard.guard_state {
-            GuardState::HeardPlayer(dir_to_player) => {
-                self.guard.do_heard_player(dir_to_player, guards_alerted)
+            GuardState::HeardPlayer(sound_position) => self
+                .guard
+                .do_heard_player(sound_position, alert_level == AlertLevel::Alert),
+            GuardState::Investigate(position) => {
+                if self
+                    .guard
+                    .do_investigate(position, dt, constants::GUARD_SPEED, navigation)
+                {
+                    self.guard.set_lookout(
+                        0.5,
+                        0.9,
+                        constants::GUARD_SEARCH_TIME.0,
+                        constants::GUARD_SEARCH_TIME.1,
+                        rng,
+                    );
+                }
             }
+            GuardState::InspectBody(body_position) => self.guard.do_inspect_body(
+                body_position,
+                dt,
+                constants::GUARD_SPEED,
+                navigation,
+                rng,
+            ),
+            GuardState::LookAroundBody(body_position, lookout_speed) => self
+                .guard
+                .do_look_around_body(body_position, lookout_speed, dt),
             GuardState::Lookout(lookout_speed) => {
                 let lookout_dir =
                     util::vec_from_angle(self.guard.transform.angle + dt * lookout_speed);
@@ -54,20 +78,26 @@ impl GuardBasic {
                 }
             }
             GuardState::Walk => {
-                if qrand::gen_range(1., 1000.) <= 5. || self.guard.is_tutorial {
-                    self.guard.set_lookout(0.5, 0.9, 3., 5.);
-                    return;
-                }
+                let walk_speed = constants::GUARD_SPEED * alert_level.speed_scale();
 
-                self.guard.do_move(rect_objects, 5., 7.);
-                self.guard.set_speed(constants::GUARD_SPEED);
+                if self.guard.patrol.is_some() {
+                    self.guard.do_patrol(dt, walk_speed, navigation);
+                } else {
+                    if rng.gen_range(1., 1000.) <= 5. || self.guard.is_tutorial {
+                        self.guard.set_lookout(0.5, 0.9, 3., 5., rng);
+                        return;
+                    }
+
+                    self.guard.do_move(rect_objects, 5., 7., rng);
+                    self.guard.set_speed(walk_speed);
+                }
             }
             GuardState::Alert => {
-                self.guard.do_move(rect_objects, 5., 7.);
+                self.guard.do_move(rect_objects, 5., 7., rng);
                 self.guard.set_speed(constants::GUARD_SPEED_MEDIUM);
             }
         };
 
-        self.guard.update(dt, player_sound);
+        self.guard.update(dt, noise_events, rng);
     }
 }
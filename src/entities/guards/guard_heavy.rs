@@ -1,12 +1,12 @@
+use crate::alert_system::AlertLevel;
 use crate::constants;
 use crate::entities::guards::Guard;
 use crate::entities::guards::GuardState;
 use crate::look_component::LookComponent;
-use crate::transform_component::TransformComponent;
+use crate::navigation_system::NavigationSystem;
+use crate::noise_system::NoiseEvent;
+use crate::rng::GameRng;
 use crate::util;
-use crate::Assets;
-
-use quad_rand as qrand;
 
 pub struct GuardHeavy {
     pub guard: Guard,
@@ -14,23 +14,19 @@ pub struct GuardHeavy {
 
 impl GuardHeavy {
     pub fn new(
-        ctx: &mut ggez::Context,
-        quad_ctx: &mut ggez::miniquad::GraphicsContext,
         position: glam::Vec2,
-        assets: &Assets,
         color: ggez::graphics::Color,
         is_tutorial: bool,
+        world_width: u32,
     ) -> Self {
-        let heavy_look_component = LookComponent::new_with_mesh(
-            ctx,
-            quad_ctx,
+        let heavy_look_component = LookComponent::new(
             glam::vec2(0., 1.),
             constants::GUARD_FOV,
             constants::GUARD_VIEW_DISTANCE_MEDIUM,
             constants::N_FOV_RAYS,
         );
 
-        let mut guard = Guard::new(ctx, quad_ctx, position, assets, color, is_tutorial);
+        let mut guard = Guard::new(position, color, is_tutorial, world_width);
         guard.add_look_component(heavy_look_component);
 
         Self { guard }
@@ -49,17 +45,48 @@ impl GuardHeavy {
         &mut self,
         dt: f32,
         rect_objects: &Vec<(&ggez::graphics::Rect, isize)>,
-        player_sound: &TransformComponent,
-        guards_alerted: bool,
+        noise_events: &[NoiseEvent],
+        alert_level: AlertLevel,
+        navigation: &NavigationSystem,
+        rng: &mut GameRng,
     ) {
         if self.guard.dead_component.is_dead {
             return;
         }
 
         match self.guard.guard_state {
-            GuardState::HeardPlayer(dir_to_player) => {
-                self.guard.do_heard_player(dir_to_player, guards_alerted)
+            GuardState::HeardPlayer(sound_position) => self
+                .guard
+                .do_heard_player(sound_position, alert_level == AlertLevel::Alert),
+            GuardState::Investigate(position) => {
+                if self.guard.do_investigate(
+                    position,
+                    dt,
+                    constants::GUARD_SPEED_MEDIUM,
+                    navigation,
+                ) {
+                    self.guard.set_lookout(
+                        0.5,
+                        0.9,
+                        constants::GUARD_SEARCH_TIME.0,
+                        constants::GUARD_SEARCH_TIME.1,
+                        rng,
+                    );
+                    self.guard.set_large_look_component();
+                } else {
+                    self.guard.set_small_look_component();
+                }
             }
+            GuardState::InspectBody(body_position) => self.guard.do_inspect_body(
+                body_position,
+                dt,
+                constants::GUARD_SPEED_MEDIUM,
+                navigation,
+                rng,
+            ),
+            GuardState::LookAroundBody(body_position, lookout_speed) => self
+                .guard
+                .do_look_around_body(body_position, lookout_speed, dt),
             GuardState::Lookout(lookout_speed) => {
                 let lookout_dir =
                     util::vec_from_angle(self.guard.transform.angle + dt * lookout_speed);
@@ -73,22 +100,38 @@ impl GuardHeavy {
                 }
             }
             GuardState::Walk => {
-                if qrand::gen_range(1., 1000.) <= 5. || self.guard.is_tutorial {
-                    self.guard.set_lookout(0.3, 0.4, 4., 6.);
-                    self.guard.set_large_look_component();
-                    return;
-                }
+                let walk_speed = constants::GUARD_SPEED_MEDIUM * alert_level.speed_scale();
+
+                if self.guard.patrol.is_some() {
+                    if self.guard.do_patrol(dt, walk_speed, navigation)
+                        || alert_level.uses_large_look()
+                    {
+                        self.guard.set_large_look_component();
+                    } else {
+                        self.guard.set_small_look_component();
+                    }
+                } else {
+                    if rng.gen_range(1., 1000.) <= 5. || self.guard.is_tutorial {
+                        self.guard.set_lookout(0.3, 0.4, 4., 6., rng);
+                        self.guard.set_large_look_component();
+                        return;
+                    }
 
-                self.guard.do_move(rect_objects, 5., 7.);
-                self.set_speed(constants::GUARD_SPEED_MEDIUM);
+                    self.guard.do_move(rect_objects, 5., 7., rng);
+                    self.set_speed(walk_speed);
+
+                    if alert_level.uses_large_look() {
+                        self.guard.set_large_look_component();
+                    }
+                }
             }
             GuardState::Alert => {
                 self.guard.set_large_look_component();
-                self.guard.do_move(rect_objects, 5., 7.);
+                self.guard.do_move(rect_objects, 5., 7., rng);
                 self.set_speed(constants::GUARD_SPEED_FAST);
             }
         };
 
-        self.guard.update(dt, player_sound);
+        self.guard.update(dt, noise_events, rng);
     }
 }
@@ -1,20 +1,19 @@
 use crate::animation_component::{AnimationComponent, AnimationState};
 use crate::collision_component::AABBCollisionComponent;
 use crate::move_component::MoveComponent;
-use crate::particle_system::ParticleSystem;
+use crate::navigation_system::NavigationSystem;
 use crate::stamina_component::StaminaComponent;
 use crate::teleport_component::TeleportComponent;
+use crate::throw_component::ThrowComponent;
 use crate::transform_component::TransformComponent;
 use crate::util;
-use crate::Assets;
-use crate::Game;
-use crate::GameState;
-use crate::SoundCollection;
-use crate::SpriteComponent;
+use crate::world::{Emitter, PlayerInput, Sound, World, WorldEvent};
 
 use crate::constants;
 use crate::entities;
 
+use serde::{Deserialize, Serialize};
+
 pub struct Player {
     pub transform: TransformComponent,
     pub animation: AnimationComponent,
@@ -22,6 +21,7 @@ pub struct Player {
     pub aabb: AABBCollisionComponent,
     pub stamina: StaminaComponent,
     pub teleport: TeleportComponent,
+    pub throw: ThrowComponent,
 
     pub stealth_intent: bool,
     pub is_stealth: bool,
@@ -29,33 +29,31 @@ pub struct Player {
     pub footstep_timer: f32,
     pub attack_range: f32,
     pub guard_to_attack_idx: Option<usize>,
+    pub dragged_body: Option<Body>,
 
     pub move_type: MoveType,
 }
 
-#[derive(PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum MoveType {
     Slow,
     Normal,
     Sprint,
+    Drag,
+}
+
+/// Dead body which can be dragged around by the player
+#[derive(Clone, Copy, PartialEq)]
+pub enum Body {
+    Target,
+    Guard(usize),
 }
 
 impl Player {
-    pub fn new(
-        ctx: &mut ggez::Context,
-        quad_ctx: &mut ggez::miniquad::GraphicsContext,
-        position: glam::Vec2,
-        assets: &Assets,
-        color: ggez::graphics::Color,
-    ) -> Self {
+    pub fn new(position: glam::Vec2, color: ggez::graphics::Color, world_width: u32) -> Self {
         Self {
-            transform: TransformComponent::new(
-                position,
-                constants::ENTITY_SIZE,
-                util::compute_grid_index(&position),
-            ),
+            transform: TransformComponent::new(position, constants::ENTITY_SIZE, world_width),
             animation: util::build_walk_animation(
-                &assets,
                 util::compute_animation_duration(constants::PLAYER_SPEED),
                 color,
             ),
@@ -67,18 +65,12 @@ impl Player {
                 constants::ENTITY_SIZE,
             )),
 
-            teleport: TeleportComponent::new(
-                SpriteComponent::new(assets.teleport.clone(), ggez::graphics::Color::WHITE)
-                    .scale(constants::SPRITE_SCALE),
-            ),
+            teleport: TeleportComponent::new(),
+            throw: ThrowComponent::new(),
             stamina: StaminaComponent::new(
-                ctx,
-                quad_ctx,
-                100.,
-                1.,
-                1.,
-                ggez::graphics::Rect::new(10., 10., 200., 20.),
-                ggez::graphics::Color::GREEN,
+                constants::STAMINA_MAX,
+                constants::STAMINA_DECREASE_RATE,
+                constants::STAMINA_INCREASE_RATE,
             ),
             stealth_intent: false,
             is_stealth: false,
@@ -86,6 +78,7 @@ impl Player {
             footstep_timer: 0.,
             attack_range: constants::PLAYER_ATTACK_RANGE,
             guard_to_attack_idx: None,
+            dragged_body: None,
 
             move_type: MoveType::Normal,
         }
@@ -110,16 +103,6 @@ impl Player {
         self.move_component.set_y_dir(dir.y);
     }
 
-    #[inline]
-    pub fn set_x_dir(&mut self, x_dir: f32) {
-        self.move_component.set_x_dir_normalized(x_dir);
-    }
-
-    #[inline]
-    pub fn set_y_dir(&mut self, y_dir: f32) {
-        self.move_component.set_y_dir_normalized(y_dir);
-    }
-
     #[inline]
     pub fn set_speed(&mut self, speed: f32) {
         self.move_component.set_speed(speed);
@@ -130,6 +113,12 @@ impl Player {
         self.move_type = move_type;
     }
 
+    #[inline]
+    pub fn drop_body(&mut self) {
+        self.dragged_body = None;
+        self.move_type = MoveType::Normal;
+    }
+
     #[inline]
     pub fn set_colliding_vec_components(&mut self, colliding_axis: (bool, bool)) {
         self.aabb.colliding_axis = colliding_axis;
@@ -141,7 +130,7 @@ impl Player {
     }
 
     #[inline]
-    fn get_move_magnitude(&self) -> f32 {
+    pub fn get_move_magnitude(&self) -> f32 {
         self.move_component.direction.length() * self.move_component.speed
     }
 
@@ -157,33 +146,60 @@ impl Player {
         self.get_sound_radius_scale() * constants::SPRITE_SIZE as f32 / 2.
     }
 
-    pub fn teleport_action(
-        &mut self,
-        ctx: &mut ggez::Context,
-        sound_collection: &mut SoundCollection,
-        particle_system: &mut ParticleSystem,
-    ) {
+    pub fn teleport_action(&mut self, events: &mut Vec<WorldEvent>) {
+        // Bodies cannot be teleported
+        if self.stamina.stamina > constants::TELEPORT_COST {
+            self.drop_body();
+        }
+
         if self.teleport.location.is_none() && self.stamina.stamina > constants::TELEPORT_COST {
             self.teleport.set_location(self.transform.clone());
             self.stamina.stamina -= constants::TELEPORT_COST;
 
-            sound_collection.play(ctx, 2).unwrap_or_default();
+            events.push(WorldEvent::Sound(Sound::TeleportInitial, None));
         } else if self.stamina.stamina > constants::TELEPORT_COST {
-            particle_system.emit(3, self.transform.position, 16);
+            events.push(WorldEvent::Particles(
+                Emitter::Teleport,
+                self.transform.position,
+                16,
+                None,
+            ));
 
             self.transform
-                .set(self.teleport.location.as_ref().unwrap().position);
+                .jump_to(self.teleport.location.as_ref().unwrap().position);
 
             self.teleport.location = None;
             self.stamina.stamina -= constants::TELEPORT_COST;
 
-            sound_collection.play(ctx, 3).unwrap_or_default();
+            events.push(WorldEvent::Sound(Sound::Teleport, None));
+        }
+    }
+
+    /// Throws a distraction towards `target`, it lands in front of the first wall on the way
+    pub fn throw_action(&mut self, target: glam::Vec2, navigation: &NavigationSystem) {
+        if self.throw.is_flying() || self.stamina.stamina < constants::THROW_COST {
+            return;
         }
+
+        let throw_vec = target - self.transform.position;
+        let throw_end =
+            self.transform.position + throw_vec.clamp_length_max(constants::THROW_DISTANCE);
+        let landing_position = navigation.find_landing_point(self.transform.position, throw_end);
+
+        self.throw.throw(self.transform.position, landing_position);
+        self.stamina.stamina -= constants::THROW_COST;
     }
 
     pub fn update(&mut self, dt: f32) {
-        self.stamina
-            .update(self.stealth_intent || self.move_type == MoveType::Sprint);
+        // Dragging overrides sneaking and sprinting
+        if self.dragged_body.is_some() {
+            self.move_type = MoveType::Drag;
+        }
+
+        self.stamina.update(
+            self.stealth_intent || self.move_type == MoveType::Sprint,
+            dt,
+        );
 
         if self.stamina.stamina <= 0. {
             self.is_stealth = false;
@@ -204,6 +220,7 @@ impl Player {
             MoveType::Slow => self.set_speed(constants::PLAYER_SPEED_SLOW),
             MoveType::Normal => self.set_speed(constants::PLAYER_SPEED),
             MoveType::Sprint => self.set_speed(constants::PLAYER_SPEED_FAST),
+            MoveType::Drag => self.set_speed(constants::PLAYER_SPEED_DRAG),
         }
 
         self.animation.set_color(ggez::graphics::Color::BLACK);
@@ -228,20 +245,42 @@ impl Player {
     }
 }
 
-pub fn system(ctx: &mut ggez::Context, game_state: &mut Game, dt: f32) {
-    let (player_pos, player_grid_idx, player_attack_range) = (
-        game_state.player.transform.position,
-        game_state.player.transform.grid_index,
-        game_state.player.attack_range,
+/// Applies the input of the current step to the player
+pub fn apply_input(world: &mut World, input: &PlayerInput) {
+    let player = &mut world.player;
+
+    player.set_dir(input.move_dir);
+    player.set_move_type(input.move_type);
+    player.set_stealth_intent(input.stealth);
+
+    if input.teleport {
+        player.teleport_action(&mut world.events);
+    }
+
+    if let Some(throw_target) = input.throw_target {
+        player.throw_action(throw_target, &world.navigation_system);
+    }
+
+    if input.attack {
+        attack_action(world);
+    }
+}
+
+pub fn system(world: &mut World, dt: f32) {
+    let (player_pos, player_grid_idx, player_attack_range, world_width) = (
+        world.player.transform.position,
+        world.player.transform.grid_index,
+        world.player.attack_range,
+        world.player.transform.world_width,
     );
 
-    let guards = game_state.get_all_guards();
+    let guards = world.get_all_guards();
 
     let closest_guard = guards
         .iter()
         .enumerate()
         .filter(|(_, guard)| {
-            let row = (constants::MAX_WORLD_X as usize / constants::GRID_CELL_SIZE) as isize;
+            let row = util::grid_row_len(world_width);
 
             let abs_diff = (guard.transform.grid_index - player_grid_idx).abs();
             let abs_diff_bottom_row = (guard.transform.grid_index - player_grid_idx + row).abs();
@@ -259,105 +298,175 @@ pub fn system(ctx: &mut ggez::Context, game_state: &mut Game, dt: f32) {
             let dist_to_player2 = (g2.transform.position - player_pos).length_squared();
 
             (dist_to_player1 as i32).cmp(&(dist_to_player2 as i32))
-        });
+        })
+        .map(|(guard_idx, _)| guard_idx);
 
-    if let Some((guard_idx, guard)) = closest_guard {
-        let pos = guard.transform.position;
-        game_state.camera.update(pos);
+    world.player.guard_to_attack_idx = closest_guard;
 
-        game_state.overlay_system.set_active_at(0, true);
-        game_state.overlay_system.set_pos_at(0, pos);
+    world.player.update(dt);
 
-        game_state.player.guard_to_attack_idx = Some(guard_idx);
-    } else {
-        game_state.overlay_system.set_active_at(0, false);
+    if let Some(landing_position) = world.player.throw.update(dt) {
+        world
+            .noise_system
+            .emit(landing_position, constants::THROW_NOISE_RADIUS);
+
+        world.events.push(WorldEvent::Particles(
+            Emitter::PlayerFootstep,
+            landing_position,
+            1,
+            Some(constants::THROW_NOISE_RADIUS / (constants::SPRITE_SIZE as f32 / 2.)),
+        ));
+        world.events.push(WorldEvent::Sound(Sound::Footstep, None));
+    }
+
+    if let Some(body) = world.player.dragged_body {
+        let player = &world.player;
+        let drag_position = player.transform.position
+            - util::vec_from_angle(player.transform.angle) * constants::BODY_DRAG_DISTANCE;
+        // The body slides along the walls when the player turns next to them
+        let body_position = world
+            .navigation_system
+            .find_free_position(player.transform.position, drag_position);
 
-        game_state.player.guard_to_attack_idx = None;
+        move_body(world, body, body_position);
     }
 
-    let player = &mut game_state.player;
-    player.update(dt);
+    let player = &mut world.player;
+    let events = &mut world.events;
 
-    let sound_collection = &mut game_state.sound_collection;
-    handle_stealth_sound(ctx, player, sound_collection);
+    handle_stealth_sound(player, events);
 
     if player.is_stealth {
         return;
     }
 
     if player.is_moving() && player.footstep_timer <= 0. {
-        game_state
-            .particle_system
-            .set_scale(1, player.get_sound_radius_scale());
-
-        game_state
-            .particle_system
-            .emit(1, player.transform.position, 1);
-
-        match player.move_type {
-            MoveType::Slow => sound_collection.set_volume_to(ctx, 8, 0.1),
-            MoveType::Normal => sound_collection.set_volume_to(ctx, 8, 0.5),
+        events.push(WorldEvent::Particles(
+            Emitter::PlayerFootstep,
+            player.transform.position,
+            1,
+            Some(player.get_sound_radius_scale()),
+        ));
+
+        let volume = match player.move_type {
+            MoveType::Slow => 0.1,
+            MoveType::Drag => 0.3,
+            MoveType::Normal => 0.5,
             MoveType::Sprint => {
-                game_state
-                    .particle_system
-                    .emit(0, player.transform.position, 10);
-
-                sound_collection.set_volume_to(ctx, 8, 0.9)
+                events.push(WorldEvent::Particles(
+                    Emitter::PlayerMove,
+                    player.transform.position,
+                    10,
+                    None,
+                ));
+
+                0.9
             }
-        }
-        .unwrap_or_default();
+        };
 
-        sound_collection.play(ctx, 8).unwrap_or_default();
+        events.push(WorldEvent::Sound(Sound::Footstep, Some(volume)));
         player.footstep_timer = 0.33;
     }
 
-    let target = &mut game_state.target;
+    let target = &mut world.target;
     if !target.is_dead() && util::check_collision(&player.transform, &target.transform) {
         target.set_dead(true);
-
-        sound_collection.play(ctx, 5).unwrap_or_default();
-
-        game_state
-            .particle_system
-            .emit(2, target.transform.position, 50);
+        world.kills += 1;
+
+        events.push(WorldEvent::Sound(Sound::TargetKilled, None));
+        events.push(WorldEvent::Particles(
+            Emitter::Kill,
+            target.transform.position,
+            50,
+            None,
+        ));
     }
 
-    let exit = &mut game_state.exit;
+    let exit = &mut world.exit;
 
     exit.player_exited =
         target.is_dead() && util::check_collision(&player.transform, &exit.transform);
     if exit.player_exited {
-        sound_collection.play(ctx, 7).unwrap_or_default();
+        events.push(WorldEvent::Sound(Sound::LevelExit, None));
     }
 
     player.footstep_timer = (player.footstep_timer - dt).max(-1.);
 }
 
-fn handle_stealth_sound(
-    ctx: &mut ggez::Context,
-    player: &mut Player,
-    sound_collection: &mut SoundCollection,
-) {
+fn handle_stealth_sound(player: &mut Player, events: &mut Vec<WorldEvent>) {
     if player.is_stealth && !player.was_stealth_prev {
-        sound_collection.play(ctx, 0).unwrap_or_default();
+        events.push(WorldEvent::Sound(Sound::Stealth, None));
         player.was_stealth_prev = true;
     }
     if !player.is_stealth && player.was_stealth_prev {
-        sound_collection.play(ctx, 1).unwrap_or_default();
+        events.push(WorldEvent::Sound(Sound::Unstealth, None));
         player.was_stealth_prev = false;
     }
 }
 
-pub fn try_attack_guard(ctx: &mut ggez::Context, game_state: &mut Game) {
-    if game_state.game_state == GameState::GameOver {
-        return;
+/// Attacks the closest guard, otherwise picks up or drops a body
+fn attack_action(world: &mut World) {
+    if world.player.dragged_body.is_some() {
+        world.player.drop_body();
+    } else if world.player.guard_to_attack_idx.is_some() {
+        try_attack_guard(world);
+    } else {
+        world.player.dragged_body = find_closest_body(world);
     }
+}
 
-    if let Some(guard_to_attack_idx) = game_state.player.guard_to_attack_idx {
-        let guard = &mut game_state.get_all_guards_mut()[guard_to_attack_idx];
+fn try_attack_guard(world: &mut World) {
+    if let Some(guard_to_attack_idx) = world.player.guard_to_attack_idx {
+        let guard = &mut world.get_all_guards_mut()[guard_to_attack_idx];
         guard.set_dead(true);
 
         let pos = guard.transform.position;
-        game_state.play_kill_effect(ctx, pos);
+        world.kills += 1;
+        world.play_kill_effect(pos);
     }
 }
+
+/// Returns the closest body in the attack range of the player
+fn find_closest_body(world: &World) -> Option<Body> {
+    let player = &world.player;
+    let dist_to_player =
+        |transform: &TransformComponent| (transform.position - player.transform.position).length();
+
+    let guard_bodies = world
+        .get_all_guards()
+        .into_iter()
+        .enumerate()
+        .filter(|(_, guard)| guard.is_dead())
+        .map(|(guard_idx, guard)| (Body::Guard(guard_idx), dist_to_player(&guard.transform)))
+        .collect::<Vec<(Body, f32)>>();
+
+    let target = &world.target;
+    let target_body = target
+        .is_dead()
+        .then(|| (Body::Target, dist_to_player(&target.transform)));
+
+    guard_bodies
+        .into_iter()
+        .chain(target_body)
+        .filter(|(_, dist)| *dist <= player.attack_range)
+        .min_by(|(_, dist1), (_, dist2)| dist1.total_cmp(dist2))
+        .map(|(body, _)| body)
+}
+
+fn move_body(world: &mut World, body: Body, position: glam::Vec2) {
+    let (transform, aabb) = match body {
+        Body::Target => (&mut world.target.transform, &mut world.target.aabb),
+        Body::Guard(guard_idx) => {
+            let guard = world
+                .get_all_guards_mut()
+                .into_iter()
+                .nth(guard_idx)
+                .unwrap();
+
+            (&mut guard.transform, &mut guard.aabb)
+        }
+    };
+
+    transform.set(position);
+    aabb.rect.move_to(position);
+}
@@ -5,12 +5,11 @@ use crate::entities;
 use crate::entities::AABBCollisionComponent;
 use crate::look_component::LookComponent;
 use crate::move_component::MoveComponent;
+use crate::path_component::PathComponent;
+use crate::rng::GameRng;
 use crate::transform_component::TransformComponent;
 use crate::util;
-use crate::Assets;
-use crate::SpriteComponent;
-
-use quad_rand as qrand;
+use crate::world::World;
 
 pub struct Target {
     pub transform: TransformComponent,
@@ -19,30 +18,23 @@ pub struct Target {
     pub move_component: MoveComponent,
     pub aabb: AABBCollisionComponent,
     pub look: LookComponent,
+    pub path: PathComponent,
 
+    pub start_dir: glam::Vec2, // Direction at the start of the move interval
     pub target_dir: glam::Vec2,
     pub max_move_interval: f32,
     pub move_interval: f32,
 }
 
 impl Target {
-    pub fn new(position: glam::Vec2, assets: &Assets, color: ggez::graphics::Color) -> Self {
+    pub fn new(position: glam::Vec2, color: ggez::graphics::Color, world_width: u32) -> Self {
         Self {
-            transform: TransformComponent::new(
-                position,
-                constants::ENTITY_SIZE,
-                util::compute_grid_index(&position),
-            ),
+            transform: TransformComponent::new(position, constants::ENTITY_SIZE, world_width),
             animation: util::build_walk_animation(
-                &assets,
                 util::compute_animation_duration(constants::TARGET_SPEED),
                 color,
             ),
-            dead_component: DeadComponent::new(
-                SpriteComponent::new(assets.dead.clone(), ggez::graphics::Color::GREEN)
-                    .scale(constants::SPRITE_SCALE),
-                false,
-            ),
+            dead_component: DeadComponent::new(ggez::graphics::Color::GREEN, false),
             move_component: MoveComponent::new(constants::TARGET_SPEED),
             aabb: AABBCollisionComponent::new(ggez::graphics::Rect::new(
                 position.x,
@@ -56,6 +48,8 @@ impl Target {
                 constants::GUARD_VIEW_DISTANCE,
                 constants::N_FOV_RAYS,
             ),
+            path: PathComponent::new(),
+            start_dir: glam::Vec2::ZERO,
             target_dir: glam::Vec2::ZERO,
             max_move_interval: 0.,
             move_interval: 0.,
@@ -84,16 +78,12 @@ impl Target {
         self.dead_component.is_dead = is_dead;
     }
 
-    /// Since target can be dead, it has an additional sprite for dead state
-    pub fn get_curr_animation_frame(&self) -> &SpriteComponent {
-        if self.dead_component.is_dead {
-            return &self.dead_component.sprite;
-        }
-
-        self.animation.get_curr_frame()
+    #[inline]
+    pub fn is_fleeing(&self) -> bool {
+        self.path.destination.is_some()
     }
 
-    pub fn calculate_move_dir(&self) -> glam::Vec2 {
+    pub fn calculate_move_dir(&self, rng: &mut GameRng) -> glam::Vec2 {
         let dx = 2. * constants::GUARD_FOV / self.look.ray_scales.len() as f32;
 
         let max_ray_scale = self
@@ -112,7 +102,7 @@ impl Target {
             if max_ray < 100 {
                 // Rotate 90 deg
                 return util::vec_from_angle(
-                    self.transform.angle + (qrand::gen_range(0., 1.)) * constants::PI / 2.,
+                    self.transform.angle + rng.gen_range(0., 1.) * constants::PI / 2.,
                 );
             }
             return util::vec_from_angle(
@@ -121,28 +111,45 @@ impl Target {
         }
 
         // Go in random direction
-        return util::vec_from_angle(qrand::gen_range(0., 1.) * constants::PI * 2.);
+        return util::vec_from_angle(rng.gen_range(0., 1.) * constants::PI * 2.);
     }
 
-    pub fn update(&mut self, dt: f32) {
+    pub fn update(&mut self, dt: f32, rng: &mut GameRng) {
         if self.dead_component.is_dead {
             return;
         }
 
-        if self.move_interval <= 0. {
+        if let Some(next_point) = self
+            .path
+            .next_point(self.transform.position, constants::PATH_POINT_RADIUS)
+        {
+            self.target_dir = (next_point - self.transform.position).normalize_or_zero();
+
+            let lerped_dir = util::vec_lerp(
+                self.move_component.direction,
+                self.target_dir,
+                (constants::TARGET_TURN_SPEED * dt).min(1.),
+            );
+            self.move_component.set_direction_normalized(lerped_dir);
+            self.move_component.set_speed(constants::TARGET_SPEED_FLEE);
+        } else if self.is_fleeing() {
+            // Reached the flee point, continue wandering
+            self.path.clear();
+            self.move_component.set_speed(constants::TARGET_SPEED);
+            self.move_interval = 0.;
+        } else if self.move_interval <= 0. {
             self.move_component
                 .set_direction_normalized(self.target_dir);
 
-            self.target_dir = self.calculate_move_dir();
+            self.start_dir = self.target_dir;
+            self.target_dir = self.calculate_move_dir(rng);
 
-            self.max_move_interval = qrand::gen_range(0.1, 0.4);
+            self.max_move_interval = rng.gen_range(0.1, 0.4);
             self.move_interval = self.max_move_interval;
         } else {
-            let lerped_dir = util::vec_lerp(
-                self.move_component.direction,
-                self.target_dir,
-                self.max_move_interval - self.move_interval,
-            );
+            // Turn towards the target direction over the whole interval
+            let progress = 1. - self.move_interval / self.max_move_interval;
+            let lerped_dir = util::vec_lerp(self.start_dir, self.target_dir, progress);
 
             self.move_component.set_direction_normalized(lerped_dir);
         }
@@ -170,3 +177,42 @@ impl Target {
         self.move_interval -= dt;
     }
 }
+
+pub fn system(world: &mut World, dt: f32) {
+    let target = &mut world.target;
+    let player = &world.player;
+
+    if !target.is_dead() && !target.is_fleeing() && !player.is_stealth {
+        let aabb_objects = world
+            .walls
+            .iter()
+            .map(|wall| &wall.aabb.rect)
+            .collect::<Vec<&ggez::graphics::Rect>>();
+
+        let flee_point = util::check_spotted(
+            &target.look,
+            &target.transform,
+            &player.transform,
+            &aabb_objects,
+        )
+        .then(|| {
+            world.navigation_system.find_flee_point(
+                target.transform.position,
+                player.transform.position,
+                constants::TARGET_FLEE_DISTANCE,
+            )
+        })
+        .flatten();
+
+        if let Some(flee_point) = flee_point {
+            let points = world
+                .navigation_system
+                .find_path(target.transform.position, flee_point)
+                .unwrap_or_else(|| vec![flee_point]);
+
+            target.path.set_path(points, flee_point);
+        }
+    }
+
+    target.update(dt, &mut world.rng);
+}
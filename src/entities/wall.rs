@@ -2,33 +2,32 @@ use crate::collision_component::AABBCollisionComponent;
 use crate::constants;
 use crate::entities;
 use crate::move_component::MoveComponent;
-use crate::sprite_component::SpriteComponent;
+use crate::tile_component::TileKind;
 use crate::transform_component::TransformComponent;
 use crate::util;
-use crate::Game;
+use crate::world::World;
 
 pub struct Wall {
     pub transform: TransformComponent,
     pub aabb: AABBCollisionComponent,
-    pub sprite: SpriteComponent,
+    pub kind: TileKind,
     pub brightness: f32,
 }
 
 impl Wall {
-    pub fn new(position: glam::Vec2, width: f32, height: f32, sprite: SpriteComponent) -> Self {
+    pub fn new(
+        position: glam::Vec2,
+        width: f32,
+        height: f32,
+        kind: TileKind,
+        world_width: u32,
+    ) -> Self {
         Self {
-            transform: TransformComponent::new(
-                position,
-                width * height,
-                util::compute_grid_index(&position),
-            ),
+            transform: TransformComponent::new(position, width * height, world_width),
             aabb: AABBCollisionComponent::new(ggez::graphics::Rect::new(
                 position.x, position.y, width, height,
             )),
-            sprite: sprite.scale(glam::vec2(
-                width / constants::SPRITE_SIZE as f32,
-                height / constants::SPRITE_SIZE as f32,
-            )),
+            kind,
             brightness: constants::GLOBAL_BRIGHTNESS,
         }
     }
@@ -63,7 +62,7 @@ fn get_colliding_vec_components_all(
     walls
         .iter()
         .filter(|wall| {
-            let row = (constants::MAX_WORLD_X as usize / constants::GRID_CELL_SIZE) as isize;
+            let row = util::grid_row_len(transform.world_width);
 
             let abs_diff = (wall.transform.grid_index - transform.grid_index).abs();
             let abs_diff_bottom_row =
@@ -82,33 +81,33 @@ fn get_colliding_vec_components_all(
         })
 }
 
-pub fn check_collision(game_state: &mut Game) {
+pub fn check_collision(world: &mut World) {
     let player_colliding_vec_components = get_colliding_vec_components_all(
-        &game_state.walls,
-        &game_state.player.transform,
-        &game_state.player.move_component,
-        &game_state.player.aabb,
+        &world.walls,
+        &world.player.transform,
+        &world.player.move_component,
+        &world.player.aabb,
     );
-    game_state
+    world
         .player
         .set_colliding_vec_components(player_colliding_vec_components);
 
     let target_colliding_vec_components = get_colliding_vec_components_all(
-        &game_state.walls,
-        &game_state.target.transform,
-        &game_state.target.move_component,
-        &game_state.target.aabb,
+        &world.walls,
+        &world.target.transform,
+        &world.target.move_component,
+        &world.target.aabb,
     );
-    game_state
+    world
         .target
         .set_colliding_vec_components(target_colliding_vec_components);
 
-    let guards_collding_axis = game_state
+    let guards_collding_axis = world
         .get_all_guards()
         .iter()
         .map(|guard| {
             get_colliding_vec_components_all(
-                &game_state.walls,
+                &world.walls,
                 &guard.transform,
                 &guard.move_component,
                 &guard.aabb,
@@ -116,7 +115,7 @@ pub fn check_collision(game_state: &mut Game) {
         })
         .collect::<Vec<(bool, bool)>>();
 
-    game_state
+    world
         .get_all_guards_mut()
         .iter_mut()
         .zip(guards_collding_axis)
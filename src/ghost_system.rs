@@ -0,0 +1,155 @@
+use crate::constants;
+use crate::util;
+use crate::world::World;
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Pose of the player at a point of the level time
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct GhostSample {
+    pub time: f32,
+    pub position: (f32, f32),
+    pub angle: f32,
+    pub frame: usize,
+}
+
+/// Trace of a whole run through a level
+#[derive(Serialize, Deserialize, Default)]
+pub struct GhostTrace {
+    pub time: f32,
+    pub samples: Vec<GhostSample>,
+}
+
+impl GhostTrace {
+    pub fn load(filename: &str) -> Option<Self> {
+        let data = std::fs::read_to_string(filename).ok()?;
+        serde_json::from_str(&data).ok()
+    }
+
+    pub fn save(&self, filename: &str) -> std::io::Result<()> {
+        std::fs::write(filename, serde_json::to_string(self)?)
+    }
+
+    /// Position, angle and walk animation frame at the level time, interpolated between the samples
+    pub fn pose_at(&self, time: f32) -> Option<(glam::Vec2, f32, usize)> {
+        let idx = self.samples.partition_point(|sample| sample.time <= time);
+        if idx == 0 || idx == self.samples.len() {
+            return None;
+        }
+
+        let (from, to) = (&self.samples[idx - 1], &self.samples[idx]);
+        let delta = (time - from.time) / (to.time - from.time);
+
+        let position = glam::Vec2::from(from.position).lerp(to.position.into(), delta);
+
+        // Turn the short way around
+        let angle_diff =
+            (to.angle - from.angle + constants::PI).rem_euclid(2. * constants::PI) - constants::PI;
+        let angle = from.angle + angle_diff * delta;
+
+        Some((position, angle, from.frame))
+    }
+
+    /// Time difference to the ghost when it was closest to the position, negative if ahead.
+    /// Only a few samples after the cursor are searched and the cursor follows the closest one,
+    /// so routes which backtrack or cross themselves do not jump to another part of the run
+    pub fn split_delta(&self, cursor: &mut usize, position: glam::Vec2, time: f32) -> Option<f32> {
+        let start = (*cursor).min(self.samples.len().checked_sub(1)?);
+        let end = (start + constants::GHOST_SPLIT_WINDOW).min(self.samples.len());
+
+        let (idx, sample) =
+            self.samples[start..end]
+                .iter()
+                .enumerate()
+                .min_by(|(_, s1), (_, s2)| {
+                    let dist1 = position.distance_squared(s1.position.into());
+                    let dist2 = position.distance_squared(s2.position.into());
+                    dist1.total_cmp(&dist2)
+                })?;
+        *cursor = start + idx;
+
+        Some(time - sample.time)
+    }
+}
+
+/// Records the current run and keeps the best run of each level, which is drawn as a ghost
+pub struct GhostSystem {
+    recording: GhostTrace,
+    best: HashMap<String, Option<GhostTrace>>, // By level key, a level is in the map once loaded
+    split_cursor: usize,
+}
+
+impl GhostSystem {
+    pub fn new() -> Self {
+        Self {
+            recording: GhostTrace::default(),
+            best: HashMap::new(),
+            split_cursor: 0,
+        }
+    }
+
+    /// Starts a new recording, the best run of the level is loaded the first time
+    pub fn start_attempt(&mut self, level_key: &str) {
+        self.recording = GhostTrace::default();
+        self.split_cursor = 0;
+
+        if !self.best.contains_key(level_key) {
+            self.best.insert(
+                level_key.to_owned(),
+                GhostTrace::load(&ghost_filename(level_key)),
+            );
+        }
+    }
+
+    pub fn record(&mut self, world: &World) {
+        if !world.tick.is_multiple_of(constants::GHOST_SAMPLE_TICKS) {
+            return;
+        }
+
+        let transform = &world.player.transform;
+        self.recording.samples.push(GhostSample {
+            time: world.curr_level_time,
+            position: transform.position.into(),
+            angle: transform.angle,
+            frame: world.player.animation.index,
+        });
+    }
+
+    /// Keeps the recorded run if it beat the best time of the level
+    pub fn finish_level(&mut self, level_key: &str, time: f32) {
+        let is_best = self.best(level_key).is_none_or(|best| time < best.time);
+
+        if is_best && !self.recording.samples.is_empty() {
+            let mut recording = std::mem::take(&mut self.recording);
+            recording.time = time;
+
+            let _ = recording.save(&ghost_filename(level_key));
+            self.best.insert(level_key.to_owned(), Some(recording));
+        }
+    }
+
+    pub fn best(&self, level_key: &str) -> Option<&GhostTrace> {
+        self.best.get(level_key).and_then(|best| best.as_ref())
+    }
+
+    /// Split against the best run of the level, it follows the best run from the start of the attempt
+    pub fn split_delta(&mut self, level_key: &str, position: glam::Vec2, time: f32) -> Option<f32> {
+        let best = self.best.get(level_key)?.as_ref()?;
+        best.split_delta(&mut self.split_cursor, position, time)
+    }
+}
+
+pub fn ghost_filename(level_key: &str) -> String {
+    util::home_filename(&format!(
+        "{}_{}.json",
+        constants::GHOST_FILENAME_PREFIX,
+        level_key
+    ))
+}
+
+impl Default for GhostSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,328 @@
+use crate::constants;
+use crate::util;
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll, Wake};
+
+/// Time of a single level or of the whole campaign, entries are only compared
+/// when both the level ID and the level content hash match
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PlayerEntry {
+    pub username: String,
+    pub time: f32,
+    #[serde(default)]
+    pub level_id: String,
+    #[serde(default)]
+    pub level_hash: String,
+    #[serde(default)]
+    pub timestamp: u64,
+    #[serde(default)]
+    pub game_version: String,
+    #[serde(default)]
+    pub kills: u32,
+    #[serde(default)]
+    pub detections: u32,
+    #[serde(default)]
+    pub replay: Option<String>, // Seed of the recorded attempt
+}
+
+impl fmt::Display for PlayerEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({}): {}", self.username, self.level_id, self.time)
+    }
+}
+
+/// Reason why a leaderboard request failed
+#[derive(Debug, Clone)]
+pub enum LeaderboardError {
+    Timeout,
+    Connection(String),
+    Status(u16),
+    InvalidResponse(String),
+    Storage(String),
+    Task(String),
+    Runtime(String),
+}
+
+impl LeaderboardError {
+    /// Whether the server refused the submitted entry itself, sending it again cannot succeed
+    pub fn is_rejection(&self) -> bool {
+        matches!(self, LeaderboardError::Status(400 | 422))
+    }
+
+    /// Whether the request failed because of the leaderboard settings, like a wrong API key or URL,
+    /// it cannot succeed until the settings are fixed
+    pub fn is_config_error(&self) -> bool {
+        match self {
+            LeaderboardError::Status(status) => {
+                (400..500).contains(status) && !self.is_rejection() && *status != 429
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for LeaderboardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LeaderboardError::Timeout => write!(f, "The server did not respond in time"),
+            LeaderboardError::Connection(err) => {
+                write!(f, "Could not connect to the server\n{}", err)
+            }
+            LeaderboardError::Status(401) => write!(f, "The server rejected the API key"),
+            LeaderboardError::Status(status) => {
+                write!(f, "The server responded with status {}", status)
+            }
+            LeaderboardError::InvalidResponse(err) => {
+                write!(f, "Invalid response from the server\n{}", err)
+            }
+            LeaderboardError::Storage(err) => {
+                write!(f, "Could not access the leaderboard file\n{}", err)
+            }
+            LeaderboardError::Task(err) => write!(f, "Request task failed\n{}", err),
+            LeaderboardError::Runtime(err) => {
+                write!(f, "Could not start the network runtime\n{}", err)
+            }
+        }
+    }
+}
+
+impl From<reqwest::Error> for LeaderboardError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            LeaderboardError::Timeout
+        } else if let Some(status) = err.status() {
+            LeaderboardError::Status(status.as_u16())
+        } else if err.is_decode() {
+            LeaderboardError::InvalidResponse(err.to_string())
+        } else {
+            LeaderboardError::Connection(err.to_string())
+        }
+    }
+}
+
+/// Pending backend request, it is spawned by the network system
+pub type LeaderboardFuture<T> = Pin<Box<dyn Future<Output = Result<T, LeaderboardError>> + Send>>;
+
+/// Runs the backend requests in the background
+pub trait Spawner {
+    fn spawn(&self, future: Pin<Box<dyn Future<Output = ()> + Send>>);
+}
+
+impl Spawner for tokio::runtime::Runtime {
+    fn spawn(&self, future: Pin<Box<dyn Future<Output = ()> + Send>>) {
+        tokio::runtime::Runtime::spawn(self, future);
+    }
+}
+
+/// Starts the tokio runtime, which the HTTP backend needs
+pub fn tokio_spawner() -> Result<Box<dyn Spawner>, LeaderboardError> {
+    match tokio::runtime::Runtime::new() {
+        Ok(runtime) => Ok(Box::new(runtime)),
+        Err(err) => Err(LeaderboardError::Runtime(err.to_string())),
+    }
+}
+
+/// Runs every request on its own thread without a runtime, it is enough for the local backend
+pub struct ThreadSpawner;
+
+impl Spawner for ThreadSpawner {
+    fn spawn(&self, future: Pin<Box<dyn Future<Output = ()> + Send>>) {
+        std::thread::spawn(move || block_on(future));
+    }
+}
+
+struct ThreadWaker(std::thread::Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Polls the future on the current thread, which sleeps until the future is woken
+pub fn block_on<T>(future: impl Future<Output = T>) -> T {
+    let waker = Arc::new(ThreadWaker(std::thread::current())).into();
+    let mut context = Context::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+        std::thread::park();
+    }
+}
+
+/// Storage of the leaderboard, the requests are futures which are run by a `Spawner`. The HTTP
+/// backend needs the tokio runtime, the local backend runs on any executor
+pub trait LeaderboardBackend {
+    /// All entries of the level, sorted by time
+    fn request_leaderboard(
+        &self,
+        level_id: &str,
+        level_hash: &str,
+    ) -> LeaderboardFuture<Vec<PlayerEntry>>;
+    fn submit_time(&self, new_entry: PlayerEntry) -> LeaderboardFuture<()>;
+}
+
+/// Leaderboard server which serves `GET <url>/leaderboard?level=<id>&hash=<hash>` and accepts
+/// `PUT <url>/put`, the API key is sent in the `api-key` header
+pub struct HttpBackend {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+}
+
+impl HttpBackend {
+    pub fn new(url: String, api_key: String) -> Result<Self, LeaderboardError> {
+        Ok(Self {
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs_f32(
+                    constants::NETWORK_TIMEOUT,
+                ))
+                .build()?,
+            url: url.trim_end_matches('/').to_owned(),
+            api_key,
+        })
+    }
+}
+
+impl LeaderboardBackend for HttpBackend {
+    fn request_leaderboard(
+        &self,
+        level_id: &str,
+        level_hash: &str,
+    ) -> LeaderboardFuture<Vec<PlayerEntry>> {
+        let request = self
+            .client
+            .get(format!("{}/leaderboard", self.url))
+            .query(&[("level", level_id), ("hash", level_hash)])
+            .header("api-key", &self.api_key);
+
+        Box::pin(async move {
+            let response = request.send().await?.error_for_status()?;
+
+            Ok(response.json::<Vec<PlayerEntry>>().await?)
+        })
+    }
+
+    fn submit_time(&self, new_entry: PlayerEntry) -> LeaderboardFuture<()> {
+        let request = self
+            .client
+            .put(format!("{}/put", self.url))
+            .header("api-key", &self.api_key)
+            .json(&new_entry);
+
+        Box::pin(async move {
+            request.send().await?.error_for_status()?;
+
+            Ok(())
+        })
+    }
+}
+
+/// Leaderboard in a local JSON file, for offline play
+pub struct LocalBackend {
+    filename: String,
+    // Submits read, modify and write the whole file, the lock keeps them from losing entries
+    file_lock: Arc<Mutex<()>>,
+}
+
+impl LocalBackend {
+    pub fn new(filename: String) -> Self {
+        Self {
+            filename,
+            file_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    fn read_entries(filename: &str) -> Result<Vec<PlayerEntry>, LeaderboardError> {
+        match std::fs::read_to_string(filename) {
+            Ok(data) => serde_json::from_str(&data)
+                .map_err(|err| LeaderboardError::Storage(err.to_string())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+            Err(err) => Err(LeaderboardError::Storage(err.to_string())),
+        }
+    }
+}
+
+/// Runs the file access on its own thread, so that the future does not block the executor and
+/// does not need a runtime
+fn run_blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, LeaderboardError> + Send + 'static,
+) -> LeaderboardFuture<T> {
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    let spawned = std::thread::Builder::new().spawn(move || {
+        let _ = sender.send(f());
+    });
+
+    Box::pin(async move {
+        spawned.map_err(|err| LeaderboardError::Task(err.to_string()))?;
+        receiver
+            .await
+            .map_err(|err| LeaderboardError::Task(err.to_string()))?
+    })
+}
+
+impl LeaderboardBackend for LocalBackend {
+    fn request_leaderboard(
+        &self,
+        level_id: &str,
+        level_hash: &str,
+    ) -> LeaderboardFuture<Vec<PlayerEntry>> {
+        let (filename, file_lock) = (self.filename.clone(), self.file_lock.clone());
+        let (level_id, level_hash) = (level_id.to_owned(), level_hash.to_owned());
+
+        run_blocking(move || {
+            let _guard = file_lock.lock().unwrap_or_else(PoisonError::into_inner);
+            let mut entries = Self::read_entries(&filename)?;
+            entries.retain(|entry| entry.level_id == level_id && entry.level_hash == level_hash);
+
+            Ok(entries)
+        })
+    }
+
+    fn submit_time(&self, new_entry: PlayerEntry) -> LeaderboardFuture<()> {
+        let (filename, file_lock) = (self.filename.clone(), self.file_lock.clone());
+
+        run_blocking(move || {
+            let _guard = file_lock.lock().unwrap_or_else(PoisonError::into_inner);
+            let mut entries = Self::read_entries(&filename)?;
+            entries.push(new_entry);
+            entries.sort_by(|e1, e2| e1.time.total_cmp(&e2.time));
+
+            let data = serde_json::to_string_pretty(&entries)
+                .map_err(|err| LeaderboardError::Storage(err.to_string()))?;
+            std::fs::write(&filename, data)
+                .map_err(|err| LeaderboardError::Storage(err.to_string()))
+        })
+    }
+}
+
+/// Picks the backend from the settings file, the HTTP backend is used when `leaderboard_url` is set.
+/// The local file is used when the HTTP client cannot be created
+pub fn from_config(filename: &str) -> Box<dyn LeaderboardBackend> {
+    if let Some(url) =
+        util::read_config_str(filename, "leaderboard_url").filter(|url| !url.is_empty())
+    {
+        let api_key = util::read_config_str(filename, "leaderboard_api_key").unwrap_or_default();
+        match HttpBackend::new(url, api_key) {
+            Ok(backend) => return Box::new(backend),
+            Err(err) => eprintln!("using the local leaderboard: {}", err),
+        }
+    }
+
+    Box::new(local_from_config(filename))
+}
+
+/// Local leaderboard file from the settings file, `leaderboard_file` overrides the default file
+pub fn local_from_config(filename: &str) -> LocalBackend {
+    LocalBackend::new(
+        util::read_config_str(filename, "leaderboard_file")
+            .unwrap_or_else(|| util::home_filename(constants::LEADERBOARD_FILENAME)),
+    )
+}
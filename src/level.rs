@@ -6,340 +6,523 @@ use crate::entities::guards::guard_scout::GuardScout;
 use crate::entities::player::Player;
 use crate::entities::target::Target;
 use crate::entities::wall::Wall;
-use crate::tile_component::TileComponent;
-use crate::util;
-use crate::SpriteComponent;
-use std::io::Read;
-
-use crate::Game;
-
-const ALL_LEVELS: &[&str] = &[
-    "levels/tutorial1.txt",
-    "levels/tutorial2.txt",
-    "levels/tutorial3.txt",
-    "levels/tutorial4.txt",
-    "levels/tutorial5.txt",
-    "levels/level1.txt",
-    "levels/level2.txt",
-    "levels/level3.txt",
-    "levels/level4.txt",
-    "levels/level5.txt",
-    "levels/level6.txt",
-    "levels/level7.txt",
-    "levels/level8.txt",
-    "levels/level9.txt",
-    "levels/level10.txt",
-    "levels/level11.txt",
-    "levels/level12.txt",
-];
-
-pub const TUTORIAL_COUNT: usize = 5;
-pub const LEVEL_COUNT: usize = ALL_LEVELS.len();
-
-pub fn load_level(
-    ctx: &mut ggez::Context,
-    quad_ctx: &mut ggez::miniquad::GraphicsContext,
-    game_state: &mut Game,
-    level_index: usize,
-    is_proceed: bool,
-) {
-    assert!(
-        level_index < LEVEL_COUNT,
-        "level index: {} out of bounds for length: {}",
-        level_index,
-        LEVEL_COUNT
-    );
+use crate::patrol_component::{PatrolComponent, PatrolMode, Waypoint};
+use crate::tile_component::{TileComponent, TileKind};
 
-    game_state.reset_state(is_proceed);
+use crate::world::World;
 
-    let assets = &game_state.assets;
+use std::fmt;
 
-    let level_filename = ALL_LEVELS[level_index];
-    let mut file = ggez::filesystem::open(ctx, level_filename).expect("could not open level file");
-    let mut level = String::new();
-    file.read_to_string(&mut level).unwrap();
+/// FNV-1a hash of the level content, so that times on a changed level are not mixed up
+pub fn content_hash(data: &[u8]) -> String {
+    let hash = data.iter().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    });
 
-    let (mut x, mut y): (i32, i32) = (0, 0);
-    let mut floor_tile = '-';
+    format!("{:016x}", hash)
+}
 
-    for char in level.chars() {
-        let position = glam::vec2(
-            (x as u32 * constants::LEVEL_BLOCK_SIZE) as f32,
-            (y as u32 * constants::LEVEL_BLOCK_SIZE) as f32,
-        );
-        let position_center = position + glam::Vec2::splat(constants::LEVEL_BLOCK_SIZE as f32 / 2.);
+#[derive(Clone)]
+pub struct LevelHeader {
+    pub version: u32,
+    pub name: String,
+    pub author: String,
+    pub par_time: Option<f32>,
+    /// Number of level blocks in x and y direction
+    pub size: (u32, u32),
+    pub tileset: String,
+}
+
+impl LevelHeader {
+    pub fn world_size(&self) -> glam::Vec2 {
+        glam::vec2(
+            (self.size.0 * constants::LEVEL_BLOCK_SIZE) as f32,
+            (self.size.1 * constants::LEVEL_BLOCK_SIZE) as f32,
+        )
+    }
 
-        if char != 'p' && char != 't' && char != 'g' && char != 's' && char != 'h' && char != 'e' {
-            floor_tile = char;
+    #[inline]
+    pub fn world_width(&self) -> u32 {
+        self.size.0 * constants::LEVEL_BLOCK_SIZE
+    }
+}
+
+impl Default for LevelHeader {
+    fn default() -> Self {
+        Self {
+            version: 1,
+            name: String::new(),
+            author: String::new(),
+            par_time: None,
+            size: constants::LEVEL_SIZE,
+            tileset: constants::DEFAULT_TILESET.to_owned(),
         }
+    }
+}
 
-        match char {
-            'p' => {
-                game_state.player = Player::new(
-                    ctx,
-                    quad_ctx,
-                    position_center,
-                    &assets,
-                    ggez::graphics::Color::BLACK,
-                );
-            }
-            't' => {
-                game_state.target =
-                    Target::new(position_center, &assets, ggez::graphics::Color::GREEN)
+#[derive(Clone)]
+pub struct LevelData {
+    pub header: LevelHeader,
+    pub rows: Vec<Vec<char>>,
+    /// Patrol routes keyed by the level block of the guard they belong to
+    pub patrols: Vec<((u32, u32), PatrolComponent)>,
+}
+
+impl LevelData {
+    pub fn patrol_at(&self, cell: (u32, u32)) -> Option<PatrolComponent> {
+        self.patrols
+            .iter()
+            .find(|(guard_cell, _)| *guard_cell == cell)
+            .map(|(_, patrol)| patrol.clone())
+    }
+}
+
+/// Problem which stops a level file from being parsed, the line starts at 1
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    fn new(line: usize, message: String) -> Self {
+        Self { line, message }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Where the parts of the level are in the file, the lines start at 1
+pub struct SourceMap {
+    /// Line of the first row of the grid
+    pub grid_line: usize,
+    /// Line of every patrol route, in the order of [`LevelData::patrols`]
+    pub patrol_lines: Vec<usize>,
+    /// Size from the header, the size of [`LevelHeader`] falls back to the grid size without it
+    pub declared_size: Option<(u32, u32)>,
+}
+
+/// Parses the contents of a level file.
+///
+/// Version 1 files are a bare character grid. Version 2 files start with a
+/// `key: value` header, which is terminated by a `---` line:
+///
+/// ```text
+/// version: 2
+/// name: Warehouse
+/// author: MOj0
+/// par_time: 25.5
+/// size: 40x25
+/// tileset: default
+/// ---
+/// xxxxxxxx...
+/// ```
+///
+/// Every header field except `version` is optional, a missing `size` is taken from the grid.
+/// A version 2 grid can be followed by another `---` line and patrol routes, see [`parse_patrol`].
+pub fn parse_level(source: &str) -> Result<LevelData, ParseError> {
+    parse_level_with_map(source).map(|(level_data, _)| level_data)
+}
+
+/// Same as [`parse_level`], it also returns where the parts of the level are in the file
+pub fn parse_level_with_map(source: &str) -> Result<(LevelData, SourceMap), ParseError> {
+    let mut lines = source
+        .lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line));
+
+    let is_v2 = source
+        .lines()
+        .next()
+        .is_some_and(|line| line.trim_start().starts_with("version:"));
+
+    let mut header = LevelHeader::default();
+    let mut declared_size = None;
+    let mut last_line = 0;
+
+    if is_v2 {
+        let mut header_terminated = false;
+        let mut version_line = 1;
+
+        for (line_number, line) in lines.by_ref() {
+            last_line = line_number;
+            let line = line.trim();
+            if line == constants::LEVEL_HEADER_END {
+                header_terminated = true;
+                break;
             }
-            'g' => game_state.guards_basic.push(GuardBasic::new(
-                ctx,
-                quad_ctx,
-                position_center,
-                &assets,
-                ggez::graphics::Color::YELLOW,
-                level_index < TUTORIAL_COUNT,
-            )),
-            's' => game_state.guards_scout.push(GuardScout::new(
-                ctx,
-                quad_ctx,
-                position_center,
-                &assets,
-                ggez::graphics::Color::CYAN,
-                level_index < TUTORIAL_COUNT,
-            )),
-            'h' => game_state.guards_heavy.push(GuardHeavy::new(
-                ctx,
-                quad_ctx,
-                position_center,
-                &assets,
-                ggez::graphics::Color::RED,
-                level_index < TUTORIAL_COUNT,
-            )),
-            'x' => game_state.walls.push(Wall::new(
-                position,
-                constants::LEVEL_BLOCK_SIZE as f32,
-                constants::LEVEL_BLOCK_SIZE as f32,
-                SpriteComponent::new(assets.wall.clone(), ggez::graphics::Color::WHITE),
-            )),
-            'b' => game_state.walls.push(Wall::new(
-                position,
-                constants::LEVEL_BLOCK_SIZE as f32,
-                constants::LEVEL_BLOCK_SIZE as f32,
-                SpriteComponent::new(assets.box1.clone(), ggez::graphics::Color::WHITE),
-            )),
-            'B' => game_state.walls.push(Wall::new(
-                position,
-                constants::LEVEL_BLOCK_SIZE as f32,
-                constants::LEVEL_BLOCK_SIZE as f32,
-                SpriteComponent::new(assets.box2.clone(), ggez::graphics::Color::WHITE),
-            )),
-            'e' => {
-                game_state.exit = Exit::new(
-                    position_center,
-                    SpriteComponent::new(assets.exit.clone(), ggez::graphics::Color::WHITE),
-                )
+            if line.is_empty() || line.starts_with('#') {
+                continue;
             }
-            '\n' => {
-                x = -1;
-                y += 1;
+
+            let error = |message: String| ParseError::new(line_number, message);
+
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| error(format!("invalid header line: '{}'", line)))?;
+            let value = value.trim();
+
+            match key.trim() {
+                "version" => {
+                    version_line = line_number;
+                    header.version = value
+                        .parse()
+                        .map_err(|_| error(format!("invalid version: '{}'", value)))?
+                }
+                "name" => header.name = value.to_owned(),
+                "author" => header.author = value.to_owned(),
+                "par_time" => {
+                    header.par_time = Some(
+                        value
+                            .parse()
+                            .map_err(|_| error(format!("invalid par time: '{}'", value)))?,
+                    )
+                }
+                "size" => declared_size = Some(parse_size(value).map_err(error)?),
+                "tileset" => header.tileset = value.to_owned(),
+                _ => (), // Unknown keys are ignored, so that older builds can read newer files
             }
-            _ => (),
         }
 
-        match floor_tile {
-            '1' => game_state.floor_tiles.push(TileComponent::new(
-                position,
-                constants::LEVEL_BLOCK_SIZE as f32,
-                constants::LEVEL_BLOCK_SIZE as f32,
-                SpriteComponent::new(assets.floor1.clone(), ggez::graphics::Color::WHITE),
-            )),
-            '2' => game_state.floor_tiles.push(TileComponent::new(
-                position,
-                constants::LEVEL_BLOCK_SIZE as f32,
-                constants::LEVEL_BLOCK_SIZE as f32,
-                SpriteComponent::new(assets.floor2.clone(), ggez::graphics::Color::WHITE),
-            )),
-            _ => (),
+        if !header_terminated {
+            return Err(ParseError::new(
+                last_line.max(1),
+                format!(
+                    "header is not terminated by '{}'",
+                    constants::LEVEL_HEADER_END
+                ),
+            ));
+        }
+        if header.version > constants::LEVEL_FORMAT_VERSION {
+            return Err(ParseError::new(
+                version_line,
+                format!("unsupported level version: {}", header.version),
+            ));
         }
-
-        x += 1;
     }
-}
 
-pub fn system(game_state: &mut Game) {
-    match game_state.level_idx {
-        0 => {
-            let overlay_active = !game_state.exit.player_exited;
-            game_state.overlay_system.set_active_at(1, overlay_active);
-            game_state.overlay_system.set_active_at(2, overlay_active);
-
-            let mut pos = game_state.player.transform.position;
-            let mut text = "This is you";
-            if game_state.target.is_dead() {
-                pos = game_state.exit.transform.position;
-                text = "Go through the exit to get to the next level"
-            } else if (game_state.player.transform.position - glam::vec2(580., 420.)).length() > 50.
-            {
-                pos = game_state.target.transform.position;
-                text = "This is your target\nEliminate the target to pass the level"
-            }
+    let grid_line = last_line + 1;
+    let mut rows = vec![];
+    for (_, line) in lines.by_ref() {
+        if is_v2 && line.trim() == constants::LEVEL_HEADER_END {
+            break;
+        }
 
-            game_state.overlay_system.set_pos_at(
-                1,
-                pos + glam::vec2(-constants::ENTITY_SIZE * 4., constants::ENTITY_SIZE * 4.),
-            );
-            game_state.overlay_system.set_rot_at(1, -constants::PI / 4.);
+        rows.push(line.chars().collect::<Vec<char>>());
+    }
 
-            game_state
-                .overlay_system
-                .set_pos_at(2, pos + glam::vec2(0., constants::ENTITY_SIZE * 3.));
+    let mut patrols = vec![];
+    let mut patrol_lines = vec![];
+    for (line_number, line) in lines.map(|(line_number, line)| (line_number, line.trim())) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
 
-            game_state.overlay_system.set_text_at(2, text);
+        let error = |message: String| ParseError::new(line_number, message);
+        let (guard_cell, patrol) = parse_patrol(line).map_err(error)?;
+
+        let glyph = rows
+            .get(guard_cell.1 as usize)
+            .and_then(|row| row.get(guard_cell.0 as usize));
+        if !matches!(glyph, Some('g' | 's' | 'h')) {
+            return Err(error(format!(
+                "patrol at {},{} does not belong to a guard",
+                guard_cell.0, guard_cell.1
+            )));
         }
-        1 => {
-            let overlay_active =
-                !game_state.target.is_dead() || game_state.player.teleport.location.is_some();
-            game_state.overlay_system.set_active_at(1, overlay_active);
-            game_state.overlay_system.set_active_at(2, overlay_active);
-
-            let teleport_key = game_state
-                .keybind_map
-                .get("teleport")
-                .unwrap_or(&util::MyKeyCode(ggez::event::KeyCode::F))
-                .to_string();
-            let sprint_key = game_state
-                .keybind_map
-                .get("sprint")
-                .unwrap_or(&util::MyKeyCode(ggez::event::KeyCode::Space))
-                .to_string();
-
-            let mut pos =
-                game_state.exit.transform.position + glam::vec2(0., constants::ENTITY_SIZE * 5.);
-
-            let mut text = format!("You also have some special abilities which drain your stamina\nOne of them is teleport\nTry pressing the {} key somewhere here to place the marker", teleport_key);
-
-            if game_state.target.is_dead() {
-                pos = game_state.player.transform.position;
-                text = format!("To teleport back\npress the {} key", teleport_key);
-            } else if game_state.player.teleport.location.is_some() {
-                pos = glam::vec2(
-                    constants::MAX_WORLD_X as f32 / 2.,
-                    constants::MAX_WORLD_Y as f32 / 2.,
-                );
-                text = format!("You can sprint by\nholding the {} key", sprint_key);
-            }
 
-            game_state.overlay_system.set_pos_at(
-                1,
-                pos + glam::vec2(-constants::ENTITY_SIZE * 4., constants::ENTITY_SIZE * 4.),
-            );
-            game_state.overlay_system.set_rot_at(1, -constants::PI / 4.);
+        patrols.push((guard_cell, patrol));
+        patrol_lines.push(line_number);
+    }
 
-            game_state
-                .overlay_system
-                .set_pos_at(2, pos + glam::vec2(0., constants::ENTITY_SIZE * 3.));
+    header.size = declared_size.unwrap_or_else(|| {
+        (
+            rows.iter().map(|row| row.len()).max().unwrap_or(0) as u32,
+            rows.len() as u32,
+        )
+    });
+
+    let level_data = LevelData {
+        header,
+        rows,
+        patrols,
+    };
+    let source_map = SourceMap {
+        grid_line,
+        patrol_lines,
+        declared_size,
+    };
+
+    Ok((level_data, source_map))
+}
 
-            game_state.overlay_system.set_text_at(2, &text);
-        }
-        2 => {
-            let overlay_active = !game_state.guards_basic[0].is_dead();
-            game_state.overlay_system.set_active_at(1, overlay_active);
-            game_state.overlay_system.set_active_at(2, overlay_active);
-
-            let sneak_key = game_state
-                .keybind_map
-                .get("sneak")
-                .unwrap_or(&util::MyKeyCode(ggez::event::KeyCode::S))
-                .to_string();
-            let attack_key = game_state
-                .keybind_map
-                .get("attack")
-                .unwrap_or(&util::MyKeyCode(ggez::event::KeyCode::A))
-                .to_string();
-
-            let mut pos =
-                game_state.player.transform.position + glam::vec2(0., constants::ENTITY_SIZE * 3.);
-            let mut text = format!(
-                "Target will usually have some guards\nYou can avoid them\nor eliminate them aswell\nBe wary\nguards can hear your footsteps");
-
-            if (game_state.player.transform.position - glam::vec2(500., 460.)).length() > 50. {
-                pos = game_state.guards_basic[0].guard.transform.position
-                    + glam::vec2(0., constants::ENTITY_SIZE * 3.);
-                text = format!( "Try to eliminate this guard\nApproach silently by holding the {} key\nEliminate with {} key", sneak_key, attack_key);
-            }
+/// Writes the level in the version 2 format, the inverse of [`parse_level`]
+pub fn level_to_string(level_data: &LevelData) -> String {
+    let header = &level_data.header;
+    let mut lines = vec![format!("version: {}", constants::LEVEL_FORMAT_VERSION)];
 
-            game_state.overlay_system.set_pos_at(
-                1,
-                pos + glam::vec2(-constants::ENTITY_SIZE * 4., constants::ENTITY_SIZE * 4.),
-            );
-            game_state.overlay_system.set_rot_at(1, -constants::PI / 4.);
+    if !header.name.is_empty() {
+        lines.push(format!("name: {}", header.name));
+    }
+    if !header.author.is_empty() {
+        lines.push(format!("author: {}", header.author));
+    }
+    if let Some(par_time) = header.par_time {
+        lines.push(format!("par_time: {}", par_time));
+    }
+    lines.push(format!("size: {}x{}", header.size.0, header.size.1));
+    lines.push(format!("tileset: {}", header.tileset));
+    lines.push(constants::LEVEL_HEADER_END.to_owned());
+
+    lines.extend(
+        level_data
+            .rows
+            .iter()
+            .map(|row| row.iter().collect::<String>()),
+    );
+
+    if !level_data.patrols.is_empty() {
+        lines.push(constants::LEVEL_HEADER_END.to_owned());
+    }
+    for (guard_cell, patrol) in &level_data.patrols {
+        let mode = match patrol.mode {
+            PatrolMode::Loop => "loop",
+            PatrolMode::PingPong => "pingpong",
+        };
+        let route = patrol
+            .waypoints
+            .iter()
+            .map(|waypoint| {
+                let mut waypoint_str = format!("{},{}", waypoint.cell.0, waypoint.cell.1);
+                if waypoint.wait_time > 0. {
+                    waypoint_str += &format!(" wait={}", waypoint.wait_time);
+                }
+                if let Some(look_angle) = waypoint.look_angle {
+                    waypoint_str += &format!(" look={}", look_angle);
+                }
+
+                waypoint_str
+            })
+            .collect::<Vec<String>>()
+            .join(" -> ");
+
+        lines.push(format!(
+            "patrol {},{} {}: {}",
+            guard_cell.0, guard_cell.1, mode, route
+        ));
+    }
 
-            game_state
-                .overlay_system
-                .set_pos_at(2, pos + glam::vec2(0., constants::ENTITY_SIZE * 3.));
+    lines.join("\n") + "\n"
+}
 
-            game_state.overlay_system.set_text_at(2, &text);
+/// Parses a patrol route line of the form
+/// `patrol <x>,<y> <loop|pingpong>: <x>,<y> [wait=<sec>] [look=<deg>] -> <x>,<y> ...`,
+/// where the first coordinate is the level block of the guard glyph
+fn parse_patrol(line: &str) -> Result<((u32, u32), PatrolComponent), String> {
+    let (declaration, route) = line
+        .strip_prefix("patrol")
+        .and_then(|rest| rest.split_once(':'))
+        .ok_or(format!("invalid patrol line: '{}'", line))?;
+
+    let mut declaration = declaration.split_whitespace();
+    let guard_cell = parse_cell(declaration.next().unwrap_or_default())?;
+    let mode = match declaration.next() {
+        Some("loop") => PatrolMode::Loop,
+        Some("pingpong") => PatrolMode::PingPong,
+        mode => {
+            return Err(format!(
+                "invalid patrol mode: '{}'",
+                mode.unwrap_or_default()
+            ))
         }
-        3 => {
-            let overlay_active = !game_state.exit.player_exited;
-            game_state.overlay_system.set_active_at(1, overlay_active);
-            game_state.overlay_system.set_active_at(2, overlay_active);
-
-            game_state.guards_basic[1].guard.dead_component.is_dead = true;
-
-            let mut pos = game_state.guards_basic[1].guard.transform.position
-                + glam::vec2(0., constants::ENTITY_SIZE * 3.);
-            let mut text = "If a guard spots a dead body\nALL guards will be immediately alerted";
-            if game_state.are_guards_alerted {
-                pos = game_state.target.transform.position
-                    + glam::vec2(0., constants::ENTITY_SIZE * 3.);
-                text = "Try to bypass the guards\neliminate the target and escape";
+    };
+
+    let waypoints = route
+        .split("->")
+        .map(|waypoint| {
+            let mut parts = waypoint.split_whitespace();
+            let cell = parse_cell(parts.next().unwrap_or_default())?;
+
+            let (mut wait_time, mut look_angle) = (0., None);
+            for part in parts {
+                match part.split_once('=') {
+                    Some(("wait", value)) => {
+                        wait_time = value
+                            .parse()
+                            .map_err(|_| format!("invalid wait time: '{}'", value))?
+                    }
+                    Some(("look", value)) => {
+                        look_angle = Some(
+                            value
+                                .parse()
+                                .map_err(|_| format!("invalid look angle: '{}'", value))?,
+                        )
+                    }
+                    _ => return Err(format!("invalid waypoint parameter: '{}'", part)),
+                }
             }
 
-            game_state.overlay_system.set_pos_at(
-                1,
-                pos + glam::vec2(-constants::ENTITY_SIZE * 4., constants::ENTITY_SIZE * 4.),
-            );
-            game_state.overlay_system.set_rot_at(1, -constants::PI / 4.);
+            Ok(Waypoint::new(cell, wait_time, look_angle))
+        })
+        .collect::<Result<Vec<Waypoint>, String>>()?;
 
-            game_state
-                .overlay_system
-                .set_pos_at(2, pos + glam::vec2(0., constants::ENTITY_SIZE * 3.));
+    Ok((guard_cell, PatrolComponent::new(waypoints, mode)))
+}
 
-            game_state.overlay_system.set_text_at(2, text);
-        }
-        4 => {
-            if game_state.exit.player_exited {
-                game_state.overlay_system.set_active_at(1, false);
-                game_state.overlay_system.set_active_at(2, false);
-
-                game_state.is_skip_tutorial = true;
-                game_state.write_config(&util::config_filename());
-            } else if game_state.target.is_dead()
-                || (game_state.player.transform.position - glam::vec2(100., 380.)).length() < 30.
-            {
-                game_state.overlay_system.set_active_at(1, false);
-                game_state.overlay_system.set_active_at(2, true);
-            } else {
-                game_state.overlay_system.set_active_at(2, false);
-            }
+fn parse_cell(value: &str) -> Result<(u32, u32), String> {
+    value
+        .split_once(',')
+        .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)))
+        .ok_or(format!(
+            "invalid level block: '{}', expected <x>,<y>",
+            value
+        ))
+}
 
-            let stealth_key = game_state
-                .keybind_map
-                .get("stealth")
-                .unwrap_or(&util::MyKeyCode(ggez::event::KeyCode::A))
-                .to_string();
+fn parse_size(value: &str) -> Result<(u32, u32), String> {
+    value
+        .split_once('x')
+        .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)))
+        .filter(|&(w, h)| w > 0 && h > 0)
+        .ok_or(format!(
+            "invalid size: '{}', expected <width>x<height>",
+            value
+        ))
+}
 
-            let mut pos = game_state.player.transform.position;
-            let mut text =
-                format!("Your second ability is stealth\nActivate it by holding the {} key\nYou cannot move while being stealth\nTry to eliminate the guard", stealth_key);
+/// Spawns the entities of the level into the world, it does not need a graphics context
+pub fn load_world(world: &mut World, level_data: LevelData, is_tutorial: bool) {
+    let world_width = level_data.header.world_width();
 
-            if game_state.target.is_dead() {
-                pos = glam::vec2(645., 80.);
-                text = format!("Remember, your score is determined\nby the amount of time needed\nfor completing each level\nso try to complete it as fast as possible");
+    for (y, row) in level_data.rows.iter().enumerate() {
+        let mut floor_tile = '-';
+
+        for (x, &char) in row.iter().enumerate() {
+            let position = glam::vec2(
+                (x as u32 * constants::LEVEL_BLOCK_SIZE) as f32,
+                (y as u32 * constants::LEVEL_BLOCK_SIZE) as f32,
+            );
+            let position_center =
+                position + glam::Vec2::splat(constants::LEVEL_BLOCK_SIZE as f32 / 2.);
+
+            if char != 'p'
+                && char != 't'
+                && char != 'g'
+                && char != 's'
+                && char != 'h'
+                && char != 'e'
+                && char != 'c'
+            {
+                floor_tile = char;
+            }
+
+            match char {
+                'p' => {
+                    world.player =
+                        Player::new(position_center, ggez::graphics::Color::BLACK, world_width);
+                }
+                't' => {
+                    world.target =
+                        Target::new(position_center, ggez::graphics::Color::GREEN, world_width)
+                }
+                'g' => {
+                    let mut guard = GuardBasic::new(
+                        position_center,
+                        ggez::graphics::Color::YELLOW,
+                        is_tutorial,
+                        world_width,
+                    );
+                    guard.guard.patrol = level_data.patrol_at((x as u32, y as u32));
+
+                    world.guards_basic.push(guard);
+                }
+                's' => {
+                    let mut guard = GuardScout::new(
+                        position_center,
+                        ggez::graphics::Color::CYAN,
+                        is_tutorial,
+                        world_width,
+                    );
+                    guard.guard.patrol = level_data.patrol_at((x as u32, y as u32));
+
+                    world.guards_scout.push(guard);
+                }
+                'h' => {
+                    let mut guard = GuardHeavy::new(
+                        position_center,
+                        ggez::graphics::Color::RED,
+                        is_tutorial,
+                        world_width,
+                    );
+                    guard.guard.patrol = level_data.patrol_at((x as u32, y as u32));
+
+                    world.guards_heavy.push(guard);
+                }
+                'x' => world.walls.push(Wall::new(
+                    position,
+                    constants::LEVEL_BLOCK_SIZE as f32,
+                    constants::LEVEL_BLOCK_SIZE as f32,
+                    TileKind::Wall,
+                    world_width,
+                )),
+                'b' => world.walls.push(Wall::new(
+                    position,
+                    constants::LEVEL_BLOCK_SIZE as f32,
+                    constants::LEVEL_BLOCK_SIZE as f32,
+                    TileKind::Box1,
+                    world_width,
+                )),
+                'B' => world.walls.push(Wall::new(
+                    position,
+                    constants::LEVEL_BLOCK_SIZE as f32,
+                    constants::LEVEL_BLOCK_SIZE as f32,
+                    TileKind::Box2,
+                    world_width,
+                )),
+                'c' => {
+                    let mut hiding_spot = TileComponent::new(
+                        position,
+                        constants::LEVEL_BLOCK_SIZE as f32,
+                        constants::LEVEL_BLOCK_SIZE as f32,
+                        TileKind::Floor1,
+                        world_width,
+                    );
+                    hiding_spot.brightness = constants::HIDING_SPOT_BRIGHTNESS;
+
+                    world.hiding_spots.push(hiding_spot);
+                }
+                'e' => world.exit = Exit::new(position_center, world_width),
+                _ => (),
             }
 
-            game_state
-                .overlay_system
-                .set_pos_at(2, pos + glam::vec2(0., constants::ENTITY_SIZE * 3.));
-            game_state.overlay_system.set_text_at(2, &text);
+            match floor_tile {
+                '1' => world.floor_tiles.push(TileComponent::new(
+                    position,
+                    constants::LEVEL_BLOCK_SIZE as f32,
+                    constants::LEVEL_BLOCK_SIZE as f32,
+                    TileKind::Floor1,
+                    world_width,
+                )),
+                '2' => world.floor_tiles.push(TileComponent::new(
+                    position,
+                    constants::LEVEL_BLOCK_SIZE as f32,
+                    constants::LEVEL_BLOCK_SIZE as f32,
+                    TileKind::Floor2,
+                    world_width,
+                )),
+                _ => (),
+            }
         }
-        _ => (),
     }
+
+    world
+        .navigation_system
+        .build(&world.walls, level_data.header.size);
+
+    world.level_header = level_data.header;
 }
@@ -0,0 +1,345 @@
+use crate::constants;
+use crate::level::{self, LevelData, LevelHeader};
+use crate::level_lint;
+use crate::patrol_component::{PatrolComponent, PatrolMode, Waypoint};
+use crate::rng::GameRng;
+
+use std::collections::{HashMap, VecDeque};
+
+/// Level block (column, row) of the grid
+type Cell = (usize, usize);
+
+/// Generated levels which do not pass the checks are thrown away and generated again
+const MAX_ATTEMPTS: u32 = 64;
+const ROOM_PLACEMENT_TRIES: u32 = 200;
+const ROOM_SIZE_MIN: (usize, usize) = (4, 4);
+const ROOM_SIZE_MAX: (usize, usize) = (9, 7);
+/// Smallest level which fits a few rooms, in level blocks
+const LEVEL_SIZE_MIN: (u32, u32) = (16, 12);
+const FLOOR_GLYPHS: &[char] = &['-', '1', '2'];
+const BOX_GLYPHS: &[char] = &['b', 'B'];
+
+/// Parameters of a generated level, the same parameters always produce the same level
+#[derive(Clone)]
+pub struct GeneratorParams {
+    pub seed: u64,
+    /// Number of level blocks in x and y direction
+    pub size: (u32, u32),
+    pub room_count: usize,
+    pub box_count: usize,
+    pub guard_count: usize,
+    /// Chance of a guard being a scout, otherwise a basic guard unless it is a heavy guard
+    pub scout_chance: f32,
+    pub heavy_chance: f32,
+    /// Chance of a guard patrolling its room instead of standing
+    pub patrol_chance: f32,
+}
+
+impl GeneratorParams {
+    /// Parameters for a difficulty from 0 (easy) to 1 (hard)
+    pub fn with_difficulty(seed: u64, difficulty: f32) -> Self {
+        let difficulty = difficulty.clamp(0., 1.);
+
+        Self {
+            seed,
+            size: constants::LEVEL_SIZE,
+            room_count: 4 + (difficulty * 3.).round() as usize,
+            box_count: 4 + (difficulty * 6.).round() as usize,
+            guard_count: 1 + (difficulty * 7.).round() as usize,
+            scout_chance: 0.4 * difficulty,
+            heavy_chance: (0.6 * (difficulty - 0.4)).max(0.),
+            patrol_chance: 0.3 + 0.5 * difficulty,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Room {
+    x: usize,
+    y: usize,
+    w: usize,
+    h: usize,
+}
+
+impl Room {
+    fn center(&self) -> Cell {
+        (self.x + self.w / 2, self.y + self.h / 2)
+    }
+
+    /// Rooms keep a wall between each other
+    fn overlaps(&self, other: &Room) -> bool {
+        self.x <= other.x + other.w
+            && other.x <= self.x + self.w
+            && self.y <= other.y + other.h
+            && other.y <= self.y + self.h
+    }
+
+    fn cells(&self) -> impl Iterator<Item = Cell> + '_ {
+        (self.y..self.y + self.h).flat_map(move |y| (self.x..self.x + self.w).map(move |x| (x, y)))
+    }
+}
+
+/// Generates a level, [`level::level_to_string`] writes it in the level file format.
+/// Every level is checked with [`level_lint`], so the target and the exit can be reached
+/// and no guard sees the player at the start
+pub fn generate_level(params: &GeneratorParams) -> Result<LevelData, String> {
+    if params.size.0 < LEVEL_SIZE_MIN.0 || params.size.1 < LEVEL_SIZE_MIN.1 {
+        return Err(format!(
+            "level size {}x{} is too small, the minimum is {}x{}",
+            params.size.0, params.size.1, LEVEL_SIZE_MIN.0, LEVEL_SIZE_MIN.1
+        ));
+    }
+
+    let mut rng = GameRng::new(params.seed);
+    for _ in 0..MAX_ATTEMPTS {
+        let Some(level) = generate_attempt(params, &mut rng) else {
+            continue;
+        };
+
+        let level_text = level::level_to_string(&level);
+        if level_lint::lint_level(&level_text, "generated", false).is_empty() {
+            return Ok(level);
+        }
+    }
+
+    Err(format!(
+        "no valid level for seed {:016x} after {} attempts",
+        params.seed, MAX_ATTEMPTS
+    ))
+}
+
+fn generate_attempt(params: &GeneratorParams, rng: &mut GameRng) -> Option<LevelData> {
+    let (width, height) = (params.size.0 as usize, params.size.1 as usize);
+    let mut rows = vec![vec!['x'; width]; height];
+
+    let rooms = place_rooms(params, rng);
+    if rooms.len() < 2 {
+        return None;
+    }
+
+    // Each room gets its own floor, the corridors keep the plain one
+    for room in &rooms {
+        let floor = FLOOR_GLYPHS[rng.gen_index(FLOOR_GLYPHS.len())];
+        room.cells().for_each(|(x, y)| rows[y][x] = floor);
+    }
+
+    // Every room is connected to the room before it, so all of them are connected
+    for pair in rooms.windows(2) {
+        let (from, to) = (pair[0].center(), pair[1].center());
+        let corner = if rng.gen_bool() {
+            (to.0, from.1)
+        } else {
+            (from.0, to.1)
+        };
+
+        for cell in line_cells(from, corner).chain(line_cells(corner, to)) {
+            if rows[cell.1][cell.0] == 'x' {
+                rows[cell.1][cell.0] = '-';
+            }
+        }
+    }
+
+    // Boxes are cover, one which cuts off a part of the level is taken away again
+    let mut walkable_count = walkable_cells(&rows).len();
+    let mut placed_boxes = 0;
+    for _ in 0..params.box_count * 4 {
+        if placed_boxes == params.box_count {
+            break;
+        }
+
+        let room = &rooms[rng.gen_index(rooms.len())];
+        let cell = room.cells().nth(rng.gen_index(room.w * room.h))?;
+        let floor = rows[cell.1][cell.0];
+        if BOX_GLYPHS.contains(&floor) {
+            continue;
+        }
+
+        rows[cell.1][cell.0] = BOX_GLYPHS[rng.gen_index(BOX_GLYPHS.len())];
+        if distances(&rows, first_walkable(&rows)?).len() == walkable_count - 1 {
+            walkable_count -= 1;
+            placed_boxes += 1;
+        } else {
+            rows[cell.1][cell.0] = floor;
+        }
+    }
+
+    // The target is as far from the player as the level allows, the exit is far from the target
+    let player_room = &rooms[rng.gen_index(rooms.len())];
+    let player = random_walkable(&rows, player_room, rng)?;
+    let from_player = distances(&rows, player);
+    let target = farthest(&from_player)?;
+    let from_target = distances(&rows, target);
+    let exit = farthest_where(&from_target, |cell| cell != player)?;
+
+    rows[player.1][player.0] = 'p';
+    rows[target.1][target.0] = 't';
+    rows[exit.1][exit.0] = 'e';
+
+    // Guards stand away from the player, outside of the room the player starts in
+    let guard_rooms = rooms
+        .iter()
+        .filter(|room| room.center() != player_room.center())
+        .collect::<Vec<&Room>>();
+    let min_distance = (params.size.0.min(params.size.1) / 2) as usize;
+
+    let mut patrols = vec![];
+    for _ in 0..params.guard_count {
+        let room = guard_rooms[rng.gen_index(guard_rooms.len())];
+        let Some(cell) = random_walkable(&rows, room, rng).filter(|cell| {
+            from_player
+                .get(cell)
+                .is_some_and(|&dist| dist >= min_distance)
+        }) else {
+            continue;
+        };
+
+        let chance = rng.gen_range(0., 1.);
+        rows[cell.1][cell.0] = if chance < params.heavy_chance {
+            'h'
+        } else if chance < params.heavy_chance + params.scout_chance {
+            's'
+        } else {
+            'g'
+        };
+
+        if rng.gen_range(0., 1.) < params.patrol_chance {
+            let mut waypoints = (0..2 + rng.gen_index(2))
+                .filter_map(|_| random_walkable(&rows, room, rng))
+                .map(|(x, y)| Waypoint::new((x as u32, y as u32), 0., None))
+                .collect::<Vec<Waypoint>>();
+            waypoints.dedup_by_key(|waypoint| waypoint.cell);
+
+            if !waypoints.is_empty() {
+                let mode = if rng.gen_bool() {
+                    PatrolMode::Loop
+                } else {
+                    PatrolMode::PingPong
+                };
+
+                patrols.push((
+                    (cell.0 as u32, cell.1 as u32),
+                    PatrolComponent::new(waypoints, mode),
+                ));
+            }
+        }
+    }
+
+    Some(LevelData {
+        header: LevelHeader {
+            version: constants::LEVEL_FORMAT_VERSION,
+            name: format!("Generated {:016x}", params.seed),
+            author: "generator".to_owned(),
+            size: params.size,
+            ..LevelHeader::default()
+        },
+        rows,
+        patrols,
+    })
+}
+
+fn place_rooms(params: &GeneratorParams, rng: &mut GameRng) -> Vec<Room> {
+    let (width, height) = (params.size.0 as usize, params.size.1 as usize);
+    let mut rooms: Vec<Room> = vec![];
+
+    for _ in 0..ROOM_PLACEMENT_TRIES {
+        if rooms.len() == params.room_count {
+            break;
+        }
+
+        let w = ROOM_SIZE_MIN.0 + rng.gen_index(ROOM_SIZE_MAX.0 - ROOM_SIZE_MIN.0 + 1);
+        let h = ROOM_SIZE_MIN.1 + rng.gen_index(ROOM_SIZE_MAX.1 - ROOM_SIZE_MIN.1 + 1);
+        if w + 2 > width || h + 2 > height {
+            continue;
+        }
+
+        let room = Room {
+            x: 1 + rng.gen_index(width - w - 1),
+            y: 1 + rng.gen_index(height - h - 1),
+            w,
+            h,
+        };
+        if rooms.iter().all(|other| !room.overlaps(other)) {
+            rooms.push(room);
+        }
+    }
+
+    // Corridors between neighbouring rooms are shorter
+    rooms.sort_by_key(|room| room.center());
+    rooms
+}
+
+/// Cells of a horizontal or vertical line, both ends included
+fn line_cells(from: Cell, to: Cell) -> impl Iterator<Item = Cell> {
+    let (x0, x1) = (from.0.min(to.0), from.0.max(to.0));
+    let (y0, y1) = (from.1.min(to.1), from.1.max(to.1));
+
+    (y0..=y1).flat_map(move |y| (x0..=x1).map(move |x| (x, y)))
+}
+
+fn is_walkable(glyph: char) -> bool {
+    glyph != 'x' && !BOX_GLYPHS.contains(&glyph)
+}
+
+fn walkable_cells(rows: &[Vec<char>]) -> Vec<Cell> {
+    rows.iter()
+        .enumerate()
+        .flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, &glyph)| is_walkable(glyph))
+                .map(move |(x, _)| (x, y))
+        })
+        .collect()
+}
+
+fn first_walkable(rows: &[Vec<char>]) -> Option<Cell> {
+    walkable_cells(rows).first().copied()
+}
+
+fn random_walkable(rows: &[Vec<char>], room: &Room, rng: &mut GameRng) -> Option<Cell> {
+    let cells = room
+        .cells()
+        .filter(|&(x, y)| FLOOR_GLYPHS.contains(&rows[y][x]))
+        .collect::<Vec<Cell>>();
+
+    (!cells.is_empty()).then(|| cells[rng.gen_index(cells.len())])
+}
+
+/// Path length in level blocks from the start to every reachable block
+fn distances(rows: &[Vec<char>], start: Cell) -> HashMap<Cell, usize> {
+    let mut distances = HashMap::from([(start, 0)]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some((x, y)) = queue.pop_front() {
+        let dist = distances[&(x, y)];
+        let neighbours = [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ];
+
+        for cell in neighbours {
+            let glyph = rows.get(cell.1).and_then(|row| row.get(cell.0));
+            if glyph.is_some_and(|&glyph| is_walkable(glyph)) && !distances.contains_key(&cell) {
+                distances.insert(cell, dist + 1);
+                queue.push_back(cell);
+            }
+        }
+    }
+
+    distances
+}
+
+fn farthest(distances: &HashMap<Cell, usize>) -> Option<Cell> {
+    farthest_where(distances, |_| true)
+}
+
+/// Ties are broken by the cell, so that the result does not depend on the order of the map
+fn farthest_where(distances: &HashMap<Cell, usize>, filter: impl Fn(Cell) -> bool) -> Option<Cell> {
+    distances
+        .iter()
+        .filter(|(&cell, _)| filter(cell))
+        .max_by_key(|(&cell, &dist)| (dist, std::cmp::Reverse(cell)))
+        .map(|(&cell, _)| cell)
+}
@@ -0,0 +1,415 @@
+//! Validation of level files, it is shared by the `level-lint` binary and the level tests.
+//!
+//! The checks only need the level source, so they run without a graphics context.
+
+use crate::constants;
+use crate::level::{self, LevelData, SourceMap};
+use crate::patrol_component::PatrolComponent;
+
+use std::collections::VecDeque;
+use std::fmt;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// Problem in a level file, the line and column start at 1
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn error(line: usize, column: usize, message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            line,
+            column,
+            message,
+        }
+    }
+
+    fn warning(line: usize, column: usize, message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            line,
+            column,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            self.line, self.column, severity, self.message
+        )
+    }
+}
+
+/// Level block (column, row) of the grid
+type Cell = (usize, usize);
+
+/// Glyphs which block movement and sight
+const SOLID_GLYPHS: &[char] = &['x', 'b', 'B'];
+const KNOWN_GLYPHS: &[char] = &[
+    'x', 'b', 'B', '-', '1', '2', 'c', 'p', 't', 'g', 's', 'h', 'e',
+];
+
+/// Parsed level with the positions of its lines in the file
+struct LevelGrid {
+    rows: Vec<Vec<char>>,
+    /// Line of the first row in the file
+    first_line: usize,
+    declared_size: Option<(u32, u32)>,
+    /// Patrol routes with their line in the file
+    patrols: Vec<(usize, PatrolComponent)>,
+}
+
+impl LevelGrid {
+    fn new(level_data: LevelData, source_map: SourceMap) -> Self {
+        let patrols = source_map
+            .patrol_lines
+            .into_iter()
+            .zip(level_data.patrols)
+            .map(|(line, (_, patrol))| (line, patrol))
+            .collect();
+
+        Self {
+            rows: level_data.rows,
+            first_line: source_map.grid_line,
+            declared_size: source_map.declared_size,
+            patrols,
+        }
+    }
+
+    fn glyph(&self, (x, y): Cell) -> Option<char> {
+        self.rows.get(y).and_then(|row| row.get(x)).copied()
+    }
+
+    fn is_walkable(&self, cell: Cell) -> bool {
+        self.glyph(cell)
+            .is_some_and(|glyph| !SOLID_GLYPHS.contains(&glyph))
+    }
+
+    fn find(&self, glyph: char) -> Vec<Cell> {
+        self.rows
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(move |(_, &c)| c == glyph)
+                    .map(move |(x, _)| (x, y))
+            })
+            .collect()
+    }
+
+    /// Position of the level block in the file
+    fn location(&self, (x, y): Cell) -> (usize, usize) {
+        (self.first_line + y, x + 1)
+    }
+
+    fn error_at(&self, cell: Cell, message: String) -> Diagnostic {
+        let (line, column) = self.location(cell);
+        Diagnostic::error(line, column, message)
+    }
+
+    fn warning_at(&self, cell: Cell, message: String) -> Diagnostic {
+        let (line, column) = self.location(cell);
+        Diagnostic::warning(line, column, message)
+    }
+
+    /// Level blocks which can be walked to from the start, moving between neighbouring blocks
+    fn reachable_from(&self, start: Cell) -> Vec<Vec<bool>> {
+        let mut visited = self
+            .rows
+            .iter()
+            .map(|row| vec![false; row.len()])
+            .collect::<Vec<Vec<bool>>>();
+
+        let mut queue = VecDeque::from([start]);
+        visited[start.1][start.0] = true;
+
+        while let Some((x, y)) = queue.pop_front() {
+            let neighbours = [
+                (x.wrapping_sub(1), y),
+                (x + 1, y),
+                (x, y.wrapping_sub(1)),
+                (x, y + 1),
+            ];
+
+            for (nx, ny) in neighbours {
+                if self.is_walkable((nx, ny)) && !visited[ny][nx] {
+                    visited[ny][nx] = true;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+
+        visited
+    }
+
+    /// Whether no solid block is on the straight line between the centers of the two blocks
+    fn has_line_of_sight(&self, from: Cell, to: Cell) -> bool {
+        let from = (from.0 as f32 + 0.5, from.1 as f32 + 0.5);
+        let to = (to.0 as f32 + 0.5, to.1 as f32 + 0.5);
+
+        let distance = ((to.0 - from.0).powi(2) + (to.1 - from.1).powi(2)).sqrt();
+        let n_steps = (distance * 4.).ceil() as usize;
+
+        (1..n_steps).all(|step| {
+            let delta = step as f32 / n_steps as f32;
+            let x = from.0 + (to.0 - from.0) * delta;
+            let y = from.1 + (to.1 - from.1) * delta;
+
+            self.is_walkable((x as usize, y as usize))
+        })
+    }
+}
+
+/// Number of basic guards a tutorial needs, its overlays point at them
+fn required_basic_guards(level_id: &str, is_tutorial: bool) -> usize {
+    match level_id {
+        "tutorial3" if is_tutorial => 1,
+        "tutorial4" if is_tutorial => 2,
+        _ => 0,
+    }
+}
+
+/// Checks the level and returns the problems in the order of the file,
+/// `level_id` is the file name without the extension.
+/// A file which cannot be parsed only reports the parse error
+pub fn lint_level(source: &str, level_id: &str, is_tutorial: bool) -> Vec<Diagnostic> {
+    let grid = match level::parse_level_with_map(source) {
+        Ok((level_data, source_map)) => LevelGrid::new(level_data, source_map),
+        Err(err) => return vec![Diagnostic::error(err.line, 1, err.message)],
+    };
+    let mut diagnostics = vec![];
+
+    if grid.rows.is_empty() {
+        diagnostics.push(Diagnostic::error(
+            grid.first_line,
+            1,
+            "level has no rows".to_owned(),
+        ));
+        return diagnostics;
+    }
+
+    check_shape(&grid, &mut diagnostics);
+    check_glyphs(&grid, &mut diagnostics);
+    let spawns = check_spawns(&grid, level_id, is_tutorial, &mut diagnostics);
+    check_enclosure(&grid, &mut diagnostics);
+    if let Some((player, target, exit)) = spawns {
+        check_reachability(&grid, player, target, exit, &mut diagnostics);
+        check_guard_view(&grid, player, &mut diagnostics);
+    }
+    check_patrols(&grid, &mut diagnostics);
+
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+    diagnostics
+}
+
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+}
+
+fn check_shape(grid: &LevelGrid, diagnostics: &mut Vec<Diagnostic>) {
+    let width = grid.rows[0].len();
+
+    for (y, row) in grid.rows.iter().enumerate().skip(1) {
+        if row.len() != width {
+            diagnostics.push(grid.error_at(
+                (row.len().min(width), y),
+                format!("row is {} blocks wide, expected {}", row.len(), width),
+            ));
+        }
+    }
+
+    if let Some((w, h)) = grid.declared_size {
+        if (w as usize, h as usize) != (width, grid.rows.len()) {
+            diagnostics.push(Diagnostic::error(
+                grid.first_line,
+                1,
+                format!(
+                    "header size {}x{} does not match the grid size {}x{}",
+                    w,
+                    h,
+                    width,
+                    grid.rows.len()
+                ),
+            ));
+        }
+    }
+}
+
+fn check_glyphs(grid: &LevelGrid, diagnostics: &mut Vec<Diagnostic>) {
+    for (y, row) in grid.rows.iter().enumerate() {
+        for (x, glyph) in row.iter().enumerate() {
+            if !KNOWN_GLYPHS.contains(glyph) {
+                diagnostics.push(grid.error_at((x, y), format!("unknown glyph '{}'", glyph)));
+            }
+        }
+    }
+}
+
+/// The player, target and exit must appear exactly once, returns their blocks if they do
+fn check_spawns(
+    grid: &LevelGrid,
+    level_id: &str,
+    is_tutorial: bool,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<(Cell, Cell, Cell)> {
+    let mut spawns = vec![];
+
+    for (glyph, name) in [('p', "player"), ('t', "target"), ('e', "exit")] {
+        let cells = grid.find(glyph);
+
+        match cells.first() {
+            Some(&cell) => spawns.push(cell),
+            None => diagnostics.push(Diagnostic::error(
+                grid.first_line,
+                1,
+                format!("level has no {} '{}'", name, glyph),
+            )),
+        }
+
+        for &cell in cells.iter().skip(1) {
+            diagnostics.push(grid.error_at(
+                cell,
+                format!("more than one {} '{}' in the level", name, glyph),
+            ));
+        }
+    }
+
+    let n_basic_guards = grid.find('g').len();
+    let n_required = required_basic_guards(level_id, is_tutorial);
+    if n_basic_guards < n_required {
+        diagnostics.push(Diagnostic::error(
+            grid.first_line,
+            1,
+            format!(
+                "{} needs at least {} basic guards 'g', it has {}",
+                level_id, n_required, n_basic_guards
+            ),
+        ));
+    }
+
+    match spawns[..] {
+        [player, target, exit] => Some((player, target, exit)),
+        _ => None,
+    }
+}
+
+/// Every walkable block must be surrounded by other blocks, otherwise the player could leave the level
+fn check_enclosure(grid: &LevelGrid, diagnostics: &mut Vec<Diagnostic>) {
+    let mut open_cells = vec![];
+
+    for (y, row) in grid.rows.iter().enumerate() {
+        for x in 0..row.len() {
+            if !grid.is_walkable((x, y)) {
+                continue;
+            }
+
+            let is_open = x == 0
+                || y == 0
+                || grid.glyph((x + 1, y)).is_none()
+                || grid.glyph((x, y - 1)).is_none()
+                || grid.glyph((x, y + 1)).is_none();
+
+            if is_open {
+                open_cells.push((x, y));
+            }
+        }
+    }
+
+    if let Some(&cell) = open_cells.first() {
+        let others = if open_cells.len() > 1 {
+            format!(" (and {} more blocks)", open_cells.len() - 1)
+        } else {
+            String::new()
+        };
+
+        diagnostics.push(grid.error_at(
+            cell,
+            format!("level is not enclosed by walls here{}", others),
+        ));
+    }
+}
+
+fn check_reachability(
+    grid: &LevelGrid,
+    player: Cell,
+    target: Cell,
+    exit: Cell,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let from_player = grid.reachable_from(player);
+    if !from_player[target.1][target.0] {
+        diagnostics.push(grid.error_at(
+            target,
+            "target cannot be reached from the player".to_owned(),
+        ));
+    }
+
+    let from_target = grid.reachable_from(target);
+    if !from_target[exit.1][exit.0] {
+        diagnostics.push(grid.error_at(exit, "exit cannot be reached from the target".to_owned()));
+    }
+}
+
+/// Guards look around when the level starts, so a guard which can see the player spawn
+/// detects the player before they can move
+fn check_guard_view(grid: &LevelGrid, player: Cell, diagnostics: &mut Vec<Diagnostic>) {
+    let guards = [
+        ('g', constants::GUARD_VIEW_DISTANCE),
+        ('s', constants::GUARD_VIEW_DISTANCE_LONG),
+        ('h', constants::GUARD_VIEW_DISTANCE_MEDIUM),
+    ];
+
+    for (glyph, view_distance) in guards {
+        for guard in grid.find(glyph) {
+            let dx = guard.0 as f32 - player.0 as f32;
+            let dy = guard.1 as f32 - player.1 as f32;
+            let distance = (dx * dx + dy * dy).sqrt() * constants::LEVEL_BLOCK_SIZE as f32;
+
+            if distance <= view_distance && grid.has_line_of_sight(guard, player) {
+                diagnostics.push(grid.warning_at(
+                    guard,
+                    format!("guard '{}' spawns with the player in view", glyph),
+                ));
+            }
+        }
+    }
+}
+
+/// Waypoints of the patrols must be walkable, the parser already checked that they belong to a guard
+fn check_patrols(grid: &LevelGrid, diagnostics: &mut Vec<Diagnostic>) {
+    for (line, patrol) in &grid.patrols {
+        for waypoint in &patrol.waypoints {
+            let (x, y) = waypoint.cell;
+
+            if !grid.is_walkable((x as usize, y as usize)) {
+                diagnostics.push(Diagnostic::error(
+                    *line,
+                    1,
+                    format!("waypoint {},{} is not on a walkable block", x, y),
+                ));
+            }
+        }
+    }
+}
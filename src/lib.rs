@@ -0,0 +1,38 @@
+//! The simulation, the level format and the records of the runs, they do not need a window or a
+//! graphics context.
+//! They are shared with the game, the tools in `src/bin` and the tests
+
+extern crate good_web_game as ggez;
+
+pub mod alert_system;
+pub mod animation_component;
+pub mod campaign;
+pub mod collision_component;
+pub mod compute_move_component;
+pub mod constants;
+pub mod dead_component;
+pub mod editor_system;
+pub mod entities;
+pub mod ghost_system;
+pub mod leaderboard_backend;
+pub mod level;
+pub mod level_generator;
+pub mod level_lint;
+pub mod load_error;
+pub mod look_component;
+pub mod move_component;
+pub mod navigation_system;
+pub mod noise_system;
+pub mod path_component;
+pub mod patrol_component;
+pub mod replay_system;
+pub mod rng;
+pub mod splits_system;
+pub mod stamina_component;
+pub mod suspicion_component;
+pub mod teleport_component;
+pub mod throw_component;
+pub mod tile_component;
+pub mod transform_component;
+pub mod util;
+pub mod world;
@@ -0,0 +1,33 @@
+use std::fmt;
+
+/// Reason why a level or an asset could not be loaded, it names the file so that modders know what broke
+#[derive(Debug)]
+pub enum LoadError {
+    MissingFile { filename: String, reason: String },
+    InvalidLevel { filename: String, reason: String },
+    InvalidCampaign { filename: String, reason: String },
+    InvalidImage { filename: String, reason: String },
+    InvalidSound { filename: String, reason: String },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::MissingFile { filename, reason } => {
+                write!(f, "Could not open {}\n{}", filename, reason)
+            }
+            LoadError::InvalidLevel { filename, reason } => {
+                write!(f, "Invalid level {}\n{}", filename, reason)
+            }
+            LoadError::InvalidCampaign { filename, reason } => {
+                write!(f, "Invalid campaign {}\n{}", filename, reason)
+            }
+            LoadError::InvalidImage { filename, reason } => {
+                write!(f, "Could not load image {}\n{}", filename, reason)
+            }
+            LoadError::InvalidSound { filename, reason } => {
+                write!(f, "Could not load sound {}\n{}", filename, reason)
+            }
+        }
+    }
+}
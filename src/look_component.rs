@@ -1,23 +1,19 @@
 use crate::constants;
 use crate::transform_component::TransformComponent;
 use crate::util;
-use crate::Game;
+use crate::world::World;
 
 pub struct LookComponent {
     pub look_at: glam::Vec2,
     pub fov: f32,
     pub view_distance: f32,
-    pub fov_mesh_composition: Vec<ggez::graphics::Mesh>,
     pub rays: Vec<glam::Vec2>,
-    pub ray_lines: Vec<ggez::graphics::Mesh>,
     pub ray_scales: Vec<f32>,
 }
 
 impl LookComponent {
     pub fn new(look_at: glam::Vec2, fov: f32, view_distance: f32, n_rays: u32) -> Self {
         let rays = Self::make_rays(look_at, fov, view_distance, n_rays);
-        let fov_mesh_composition = vec![];
-        let ray_lines = vec![];
 
         let ray_scales = rays.iter().map(|_| 1.0).collect::<Vec<f32>>();
 
@@ -25,48 +21,7 @@ impl LookComponent {
             look_at,
             fov,
             view_distance,
-            fov_mesh_composition,
             rays,
-            ray_lines,
-            ray_scales,
-        }
-    }
-
-    pub fn new_with_mesh(
-        ctx: &mut ggez::Context,
-        quad_ctx: &mut ggez::miniquad::GraphicsContext,
-        look_at: glam::Vec2,
-        fov: f32,
-        view_distance: f32,
-        n_rays: u32,
-    ) -> Self {
-        let rays = Self::make_rays(look_at, fov, view_distance, n_rays);
-        let fov_mesh_composition = Self::mesh_composition_from_rays(ctx, quad_ctx, &rays);
-
-        // Build a line mesh for each ray
-        let ray_lines = rays
-            .iter()
-            .map(|ray| {
-                ggez::graphics::Mesh::new_line(
-                    ctx,
-                    quad_ctx,
-                    &[glam::vec2(0., 0.), glam::vec2(ray.x, ray.y)],
-                    2.0,
-                    ggez::graphics::Color::WHITE,
-                )
-                .unwrap()
-            })
-            .collect::<Vec<ggez::graphics::Mesh>>();
-
-        let ray_scales = rays.iter().map(|_| 1.0).collect::<Vec<f32>>();
-
-        Self {
-            look_at,
-            fov,
-            view_distance,
-            fov_mesh_composition,
-            rays,
-            ray_lines,
             ray_scales,
         }
     }
@@ -93,28 +48,6 @@ impl LookComponent {
             .collect()
     }
 
-    fn mesh_composition_from_rays(
-        ctx: &mut ggez::Context,
-        quad_ctx: &mut ggez::miniquad::GraphicsContext,
-        rays: &Vec<glam::Vec2>,
-    ) -> Vec<ggez::graphics::Mesh> {
-        return rays
-            .windows(2)
-            .map(|fov_rays| {
-                let fov_points = vec![glam::vec2(0., 0.), fov_rays[0], fov_rays[1]];
-
-                ggez::graphics::Mesh::new_polygon(
-                    ctx,
-                    quad_ctx,
-                    ggez::graphics::DrawMode::fill(),
-                    &fov_points,
-                    ggez::graphics::Color::from_rgba(255, 255, 255, 125),
-                )
-                .unwrap()
-            })
-            .collect::<Vec<ggez::graphics::Mesh>>();
-    }
-
     pub fn update(
         &mut self,
         source: &TransformComponent,
@@ -184,8 +117,8 @@ impl LookComponent {
     }
 }
 
-pub fn system(game_state: &mut Game) {
-    let transform_look_basic = game_state
+pub fn system(world: &mut World) {
+    let transform_look_basic = world
         .guards_basic
         .iter_mut()
         .filter(|guard| !guard.is_dead())
@@ -196,7 +129,7 @@ pub fn system(game_state: &mut Game) {
             )
         });
 
-    let transform_look_scout = game_state
+    let transform_look_scout = world
         .guards_scout
         .iter_mut()
         .filter(|guard| !guard.is_dead())
@@ -207,7 +140,7 @@ pub fn system(game_state: &mut Game) {
             )
         });
 
-    let transform_look_heavy = game_state
+    let transform_look_heavy = world
         .guards_heavy
         .iter_mut()
         .filter(|guard| !guard.is_dead())
@@ -222,13 +155,13 @@ pub fn system(game_state: &mut Game) {
         .chain(transform_look_scout)
         .chain(transform_look_heavy);
 
-    let aabb_objects = game_state
+    let aabb_objects = world
         .walls
         .iter()
         .map(|wall| (&wall.aabb.rect, wall.transform.grid_index))
         .collect::<Vec<(&ggez::graphics::Rect, isize)>>();
 
-    let mut brightness_vec = vec![constants::GLOBAL_BRIGHTNESS; game_state.walls.len()];
+    let mut brightness_vec = vec![constants::GLOBAL_BRIGHTNESS; world.walls.len()];
     for (transform, look) in transform_look_components {
         let wall_idx_and_ray_scales = look.update(transform, &aabb_objects);
 
@@ -237,12 +170,12 @@ pub fn system(game_state: &mut Game) {
         }
     }
 
-    game_state
+    world
         .target
         .look
-        .update(&game_state.target.transform, &aabb_objects);
+        .update(&world.target.transform, &aabb_objects);
 
     for (wall_idx, brightness) in brightness_vec.iter().enumerate() {
-        game_state.walls[wall_idx].set_brightness(*brightness);
+        world.walls[wall_idx].set_brightness(*brightness);
     }
 }
@@ -1,43 +1,49 @@
 #![windows_subsystem = "windows"]
 
-mod animation_component;
 mod assets;
 mod camera_component;
-mod collision_component;
-mod compute_move_component;
-mod constants;
-mod dead_component;
-mod entities;
-mod level;
-mod look_component;
+mod mesh_cache;
 mod mouse_input_handler;
-mod move_component;
 mod network_system;
 mod overlay_system;
 mod particle_system;
 mod sound_collection;
 mod sprite_component;
-mod stamina_component;
-mod teleport_component;
-mod tile_component;
-mod transform_component;
-mod util;
+mod tutorial_system;
 
 use std::collections::HashMap;
 
+use crate::animation_component::AnimationComponent;
 use crate::assets::Assets;
+use crate::campaign::Campaign;
+use crate::dead_component::DeadComponent;
+use crate::editor_system::{EditorSystem, EditorTool};
+use crate::ghost_system::GhostSystem;
+use crate::leaderboard_backend::PlayerEntry;
+use crate::load_error::LoadError;
+use crate::mesh_cache::MeshCache;
 use crate::mouse_input_handler::MouseInputHandler;
-use crate::network_system::NetworkSystem;
+use crate::network_system::{LeaderboardState, NetworkSystem};
+use crate::replay_system::{Replay, ReplaySystem};
+use crate::rng::GameRng;
 use crate::sound_collection::SoundCollection;
-use crate::sprite_component::SpriteComponent;
+use crate::splits_system::{SpeedrunSystem, Splits};
 use crate::transform_component::TransformComponent;
+use crate::world::{PlayerInput, World, WorldEvent};
 
 extern crate good_web_game as ggez;
 
+use ultimate_ninja::{
+    alert_system, animation_component, campaign, collision_component, constants, dead_component,
+    editor_system, entities, ghost_system, leaderboard_backend, level, level_lint, load_error,
+    patrol_component, replay_system, rng, splits_system, tile_component, transform_component, util,
+    world,
+};
+
 use ggez::event::{KeyCode, KeyMods};
 use ggez::input::MouseButton;
 use ggez::miniquad;
-use ggez::{audio, graphics, Context, GameResult};
+use ggez::{graphics, Context, GameResult};
 
 #[derive(PartialEq)]
 pub enum GameState {
@@ -52,26 +58,46 @@ pub enum GameState {
     LevelAnimation,
     GameOver,
     EndScreen,
+    LoadError(String),
+    Editor,
+}
+
+/// Stats of the last finished run of a level, they are submitted with the level time
+#[derive(Clone, Copy, Default)]
+pub struct LevelStats {
+    kills: u32,
+    detections: u32,
+    seed: u64,
+}
+
+/// Times and stats of the levels of a campaign, kept while another campaign is played
+pub struct CampaignProgress {
+    level_times: Vec<f32>,
+    level_stats: Vec<LevelStats>,
+}
+
+impl CampaignProgress {
+    fn new(campaign: &Campaign) -> Self {
+        Self {
+            level_times: vec![0.; campaign.level_count()],
+            level_stats: vec![LevelStats::default(); campaign.level_count()],
+        }
+    }
 }
 
 pub struct Game {
     game_state: GameState,
     assets: assets::Assets,
     camera: camera_component::CameraComponent,
-    player: entities::player::Player,
-    target: entities::target::Target,
-    guards_basic: Vec<entities::guards::guard_basic::GuardBasic>,
-    guards_scout: Vec<entities::guards::guard_scout::GuardScout>,
-    guards_heavy: Vec<entities::guards::guard_heavy::GuardHeavy>,
-    walls: Vec<entities::wall::Wall>,
-    floor_tiles: Vec<tile_component::TileComponent>,
-    exit: entities::exit::Exit,
+    world: World,
+    input: PlayerInput,
+    tick_accumulator: f32, // Frame time which has not been simulated yet
+    mesh_cache: MeshCache,
     sound_collection: SoundCollection,
     mouse_input_handler: MouseInputHandler,
     particle_system: particle_system::ParticleSystem,
     overlay_system: overlay_system::OverlaySystem,
     level_idx: usize,
-    are_guards_alerted: bool,
     debug_draw: bool,
     is_touch_joystick_activated: bool,
     menu_rectangle: graphics::MeshBatch,
@@ -80,25 +106,34 @@ pub struct Game {
     keybind_input: Option<util::MyKeyCode>,
     grid_mesh: graphics::MeshBatch,
     n_objects: usize,
-    curr_level_time: f32,
-    level_times: [f32; level::LEVEL_COUNT],
-    leaderboard_str: Option<String>,
+    campaigns: Vec<Campaign>,
+    campaign_idx: usize,
+    progress: Vec<CampaignProgress>, // One per campaign
+    leaderboard_level: usize,        // 0 is the campaign, then the levels without the tutorial
+    leaderboard_page: usize,
     network_system: NetworkSystem,
-    tokio_runtime: tokio::runtime::Runtime,
+    replay_system: ReplaySystem,
+    ghost_system: GhostSystem,
+    speedrun_system: SpeedrunSystem,
+    editor_system: EditorSystem,
+    editor_cursor: graphics::Mesh,
     player_name: String,
+    username: String, // Name of the last submit, to find the player on the leaderboard
     is_skip_tutorial: bool,
+    is_speedrun: bool,
 }
 
 impl Game {
-    pub fn new(ctx: &mut Context, quad_ctx: &mut miniquad::GraphicsContext) -> Self {
-        let (is_muted, are_particles_activated, is_skip_tutorial, keybind_map) =
+    pub fn new(
+        ctx: &mut Context,
+        quad_ctx: &mut miniquad::GraphicsContext,
+    ) -> Result<Self, LoadError> {
+        let (is_muted, are_particles_activated, is_skip_tutorial, is_speedrun, keybind_map) =
             util::read_config(&util::config_filename());
 
-        let tokio_runtime = tokio::runtime::Runtime::new().unwrap();
-
         let game_state = GameState::Menu;
 
-        let assets = Assets::load(ctx, quad_ctx);
+        let assets = Assets::load(ctx, quad_ctx)?;
 
         let camera = camera_component::CameraComponent::new(
             glam::Vec2::ZERO,
@@ -107,38 +142,26 @@ impl Game {
                 (constants::HEIGHT / 2) as f32,
             ),
             constants::CAMERA_DEFAULT_LERP_DELTA,
+            glam::vec2(constants::MAX_WORLD_X as f32, constants::MAX_WORLD_Y as f32),
         );
 
-        let player = entities::player::Player::new(
-            ctx,
-            quad_ctx,
-            glam::Vec2::ZERO,
-            &assets,
-            ggez::graphics::Color::BLACK,
-        );
-
-        let target =
-            entities::target::Target::new(glam::Vec2::ZERO, &assets, ggez::graphics::Color::GREEN);
-
-        let exit = entities::exit::Exit::new(
-            glam::Vec2::ZERO,
-            SpriteComponent::new(assets.exit.clone(), ggez::graphics::Color::WHITE),
-        );
+        let mesh_cache = MeshCache::new(ctx, quad_ctx);
 
+        // In the order of `world::Sound`
         let mut sounds = [
-            audio::Source::new(ctx, "sounds/stealth.ogg").unwrap(),
-            audio::Source::new(ctx, "sounds/unstealth.ogg").unwrap(),
-            audio::Source::new(ctx, "sounds/teleport_initial.ogg").unwrap(),
-            audio::Source::new(ctx, "sounds/teleport.ogg").unwrap(),
-            audio::Source::new(ctx, "sounds/player_dead.ogg").unwrap(),
-            audio::Source::new(ctx, "sounds/target_killed.ogg").unwrap(),
-            audio::Source::new(ctx, "sounds/dead_target_detected.ogg").unwrap(),
-            audio::Source::new(ctx, "sounds/level_exit.ogg").unwrap(),
-            audio::Source::new(ctx, "sounds/footstep.ogg").unwrap(),
+            sound_collection::load_sound(ctx, "sounds/stealth.ogg")?,
+            sound_collection::load_sound(ctx, "sounds/unstealth.ogg")?,
+            sound_collection::load_sound(ctx, "sounds/teleport_initial.ogg")?,
+            sound_collection::load_sound(ctx, "sounds/teleport.ogg")?,
+            sound_collection::load_sound(ctx, "sounds/player_dead.ogg")?,
+            sound_collection::load_sound(ctx, "sounds/target_killed.ogg")?,
+            sound_collection::load_sound(ctx, "sounds/dead_target_detected.ogg")?,
+            sound_collection::load_sound(ctx, "sounds/level_exit.ogg")?,
+            sound_collection::load_sound(ctx, "sounds/footstep.ogg")?,
         ];
         sounds
             .iter_mut()
-            .for_each(|sound| sound.set_volume(ctx, 0.5).unwrap());
+            .for_each(|sound| sound.set_volume(ctx, 0.5).unwrap_or_default());
 
         let sound_collection = SoundCollection {
             sounds,
@@ -202,6 +225,7 @@ impl Game {
             particle_image.clone(),
         );
 
+        // In the order of `world::Emitter`
         particle_system.add_emitter(player_move_particle_emitter);
         particle_system.add_emitter(player_footstep_particle_emitter);
         particle_system.add_emitter(target_killed_particle_emitter);
@@ -278,10 +302,7 @@ impl Game {
             quad_ctx,
             &[
                 glam::vec2(0., 0.),
-                glam::vec2(
-                    constants::MAX_WORLD_X.max(constants::MAX_WORLD_Y) as f32,
-                    0.,
-                ),
+                glam::vec2(constants::GRID_LINE_LENGTH, 0.),
             ],
             2.,
             graphics::Color::new(0.85, 0.85, 0.85, 0.75),
@@ -290,26 +311,106 @@ impl Game {
 
         let grid_mesh = graphics::MeshBatch::new(grid_line).unwrap();
 
-        let network_system = NetworkSystem::new();
+        let editor_cursor = graphics::Mesh::new_rectangle(
+            ctx,
+            quad_ctx,
+            graphics::DrawMode::stroke(2.),
+            graphics::Rect::new(
+                0.,
+                0.,
+                constants::LEVEL_BLOCK_SIZE as f32,
+                constants::LEVEL_BLOCK_SIZE as f32,
+            ),
+            constants::EDITOR_CURSOR_COLOR,
+        )
+        .unwrap();
+
+        // Without the runtime only the local leaderboard can be used
+        let network_system = match leaderboard_backend::tokio_spawner() {
+            Ok(spawner) => NetworkSystem::new(
+                leaderboard_backend::from_config(&util::config_filename()),
+                spawner,
+            ),
+            Err(err) => {
+                eprintln!("using the local leaderboard: {}", err);
+                NetworkSystem::new(
+                    Box::new(leaderboard_backend::local_from_config(
+                        &util::config_filename(),
+                    )),
+                    Box::new(leaderboard_backend::ThreadSpawner),
+                )
+            }
+        };
+        let campaigns = campaign::load_campaigns(ctx)?;
+        let progress = campaigns.iter().map(CampaignProgress::new).collect();
+        let username =
+            util::read_config_str(&util::config_filename(), "username").unwrap_or_default();
+
+        // A replay file is passed with `--replay <filename>`
+        let mut replay_system = ReplaySystem::new();
+        let args = std::env::args().collect::<Vec<String>>();
+        if let Some(filename) = args
+            .windows(2)
+            .find(|args| args[0] == "--replay")
+            .map(|args| &args[1])
+        {
+            match Replay::load(filename) {
+                Some(replay) => replay_system.queue_playback(replay),
+                None => eprintln!("could not load replay: {}", filename),
+            }
+        }
+
+        // Splits of another timer are imported with `--import-splits <filename>`
+        let speedrun_system = match args
+            .windows(2)
+            .find(|args| args[0] == "--import-splits")
+            .map(|args| &args[1])
+        {
+            Some(filename) => {
+                let segment_count = campaigns[0].level_count() - campaigns[0].tutorial_count();
+                match Splits::load(filename, segment_count) {
+                    Ok(splits) => {
+                        // The import replaces the saved splits right away, not only after a run
+                        let splits_filename = splits_system::splits_filename(&campaigns[0].id);
+                        if let Err(err) = splits.save(&splits_filename) {
+                            eprintln!("could not save splits {}: {}", splits_filename, err);
+                        }
+
+                        SpeedrunSystem::new(splits, &campaigns[0])
+                    }
+                    Err(err) => {
+                        eprintln!("could not import splits {}: {}", filename, err);
+                        SpeedrunSystem::load(&campaigns[0])
+                    }
+                }
+            }
+            None => SpeedrunSystem::load(&campaigns[0]),
+        };
 
-        Game {
+        // A level file is opened in the editor with `--edit <filename>`
+        let edit_filename = args
+            .windows(2)
+            .find(|args| args[0] == "--edit")
+            .map(|args| args[1].clone());
+        let editor_system = EditorSystem::open(
+            &edit_filename
+                .clone()
+                .unwrap_or_else(editor_system::default_filename),
+        );
+
+        let mut game = Game {
             game_state,
             assets,
             camera,
-            player,
-            target,
-            guards_basic: vec![],
-            guards_scout: vec![],
-            guards_heavy: vec![],
-            walls: vec![],
-            floor_tiles: vec![],
-            exit,
+            world: World::new(),
+            input: PlayerInput::new(),
+            tick_accumulator: 0.,
+            mesh_cache,
             sound_collection,
             mouse_input_handler,
             particle_system,
             overlay_system,
             level_idx,
-            are_guards_alerted: false,
             debug_draw: false,
             is_touch_joystick_activated: false,
             menu_rectangle,
@@ -318,14 +419,28 @@ impl Game {
             keybind_input: None,
             grid_mesh,
             n_objects: 0,
-            curr_level_time: 0.,
-            level_times: [0.; level::LEVEL_COUNT],
-            leaderboard_str: None,
+            campaigns,
+            campaign_idx: 0,
+            progress,
+            leaderboard_level: 0,
+            leaderboard_page: 0,
             network_system,
-            tokio_runtime,
-            player_name: String::new(),
+            replay_system,
+            ghost_system: GhostSystem::new(),
+            speedrun_system,
+            editor_system,
+            editor_cursor,
+            player_name: username.clone(),
+            username,
             is_skip_tutorial,
+            is_speedrun,
+        };
+
+        if edit_filename.is_some() {
+            game.open_editor(ctx, quad_ctx);
         }
+
+        Ok(game)
     }
 
     pub fn reset_state(&mut self, is_proceed: bool) {
@@ -333,77 +448,209 @@ impl Game {
             self.game_state = GameState::Game;
         }
 
-        self.player.is_stealth = false;
-        self.player.stealth_intent = false;
+        let seed = self.replay_system.start_attempt(
+            &self.campaigns[self.campaign_idx].id,
+            self.level_idx,
+            GameRng::time_seed(),
+        );
+        self.world.reset(is_proceed, seed);
+        self.ghost_system.start_attempt(&self.level_key());
+        self.input.clear_actions();
+        self.tick_accumulator = 0.;
+
+        self.particle_system.reset();
+    }
+
+    fn campaign(&self) -> &Campaign {
+        &self.campaigns[self.campaign_idx]
+    }
+
+    fn level_key(&self) -> String {
+        self.campaign().level_key(self.level_idx)
+    }
 
-        self.target.set_dead(false);
-        self.are_guards_alerted = false;
+    /// Switches to the campaign, its progress is kept from the last time it was played
+    fn select_campaign(&mut self, campaign_idx: usize) {
+        if campaign_idx == self.campaign_idx {
+            return;
+        }
 
-        self.exit.player_exited = false;
+        self.speedrun_system.reset_run();
+        self.campaign_idx = campaign_idx;
+        self.level_idx = 0;
+        self.speedrun_system = SpeedrunSystem::load(self.campaign());
 
-        self.guards_basic.clear();
-        self.guards_scout.clear();
-        self.guards_heavy.clear();
+        self.leaderboard_level = 0;
+        self.network_system.reset_leaderboard();
+    }
 
-        self.walls.clear();
+    /// Shows the editor with the world built from the edited level
+    fn open_editor(&mut self, ctx: &mut Context, quad_ctx: &mut miniquad::Context) {
+        self.speedrun_system.reset_run();
+        self.editor_system.is_playtest = false;
+        self.game_state = GameState::Editor;
 
-        self.floor_tiles.clear();
+        // Tutorial hints of the last played level
+        self.overlay_system.set_active_at(1, false);
+        self.overlay_system.set_active_at(2, false);
 
-        self.particle_system.reset();
+        self.camera
+            .set_lerp_delta(constants::CAMERA_DEFAULT_LERP_DELTA);
+        self.refresh_editor(ctx, quad_ctx, 0);
+    }
 
-        if is_proceed {
-            self.curr_level_time = 0.;
+    /// Rebuilds the world from the edited level
+    fn refresh_editor(&mut self, ctx: &mut Context, quad_ctx: &mut miniquad::Context, seed: u64) {
+        let level_data = self.editor_system.level.clone();
+
+        if self.assets.tileset.name != level_data.header.tileset {
+            match assets::Tileset::load(ctx, quad_ctx, &level_data.header.tileset) {
+                Ok(tileset) => self.assets.tileset = tileset,
+                Err(err) => self.editor_system.status = err.to_string().replace('\n', ": "),
+            }
         }
+
+        self.camera.set_world_size(level_data.header.world_size());
+        self.world.reset(true, seed);
+        level::load_world(&mut self.world, level_data, false);
+        self.mesh_cache.prepare(ctx, quad_ctx, &self.world);
     }
 
-    // Collect all common guard objects into one vector
-    pub fn get_all_guards(&self) -> Vec<&entities::guards::Guard> {
-        let guards_b = self
-            .guards_basic
-            .iter()
-            .map(|guard_basic| &guard_basic.guard);
+    /// Plays the edited level as it is, without recording a replay or a ghost
+    fn start_playtest(&mut self, ctx: &mut Context, quad_ctx: &mut miniquad::Context) {
+        if let Some(diagnostic) = self.editor_system.first_error() {
+            self.editor_system.status = format!("Cannot playtest, {}", diagnostic);
+            return;
+        }
 
-        let guards_s = self
-            .guards_scout
-            .iter()
-            .map(|guard_sniper| &guard_sniper.guard);
+        self.refresh_editor(ctx, quad_ctx, GameRng::time_seed());
+        self.input.clear_actions();
+        self.tick_accumulator = 0.;
+        self.particle_system.reset();
 
-        let guards_h = self
-            .guards_heavy
-            .iter()
-            .map(|guard_heavy| &guard_heavy.guard);
+        self.editor_system.is_playtest = true;
+        self.editor_system.status = "Playtest, press Escape to stop".to_owned();
+        self.game_state = GameState::Game;
+    }
 
-        guards_b
-            .chain(guards_s)
-            .chain(guards_h)
-            .collect::<Vec<&entities::guards::Guard>>()
+    fn stop_playtest(
+        &mut self,
+        ctx: &mut Context,
+        quad_ctx: &mut miniquad::Context,
+        status: String,
+    ) {
+        self.open_editor(ctx, quad_ctx);
+        self.editor_system.status = status;
     }
 
-    pub fn get_all_guards_mut(&mut self) -> Vec<&mut entities::guards::Guard> {
-        let guards_b = self
-            .guards_basic
-            .iter_mut()
-            .map(|guard_basic| &mut guard_basic.guard);
+    /// Fraction of the next tick which has already passed, used to interpolate between ticks
+    pub fn tick_alpha(&self) -> f32 {
+        self.tick_accumulator / constants::FIXED_DT
+    }
 
-        let guards_s = self
-            .guards_scout
-            .iter_mut()
-            .map(|guard_sniper| &mut guard_sniper.guard);
+    /// Plays back the sounds and particles of a world step
+    fn handle_world_events(&mut self, ctx: &mut Context, events: Vec<WorldEvent>) {
+        for event in events {
+            match event {
+                WorldEvent::Sound(sound, volume) => {
+                    let sound_idx = sound as usize;
+                    if let Some(volume) = volume {
+                        self.sound_collection
+                            .set_volume_to(ctx, sound_idx, volume)
+                            .unwrap_or_default();
+                    }
 
-        let guards_h = self
-            .guards_heavy
-            .iter_mut()
-            .map(|guard_heavy| &mut guard_heavy.guard);
+                    self.sound_collection
+                        .play(ctx, sound_idx)
+                        .unwrap_or_default();
+                }
+                WorldEvent::Particles(emitter, position, n_particles, scale) => {
+                    let emitter_idx = emitter as usize;
+                    if let Some(scale) = scale {
+                        self.particle_system.set_scale(emitter_idx, scale);
+                    }
 
-        guards_b
-            .chain(guards_s)
-            .chain(guards_h)
-            .collect::<Vec<&mut entities::guards::Guard>>()
+                    self.particle_system
+                        .emit(emitter_idx, position, n_particles);
+                }
+            }
+        }
     }
 
-    pub fn play_kill_effect(&mut self, ctx: &mut Context, pos: glam::Vec2) {
-        self.sound_collection.play(ctx, 5).unwrap_or_default();
-        self.particle_system.emit(2, pos, 50);
+    /// Reads, checks and spawns the level of the current campaign
+    fn try_load_level(
+        &mut self,
+        ctx: &mut Context,
+        quad_ctx: &mut miniquad::Context,
+        level_index: usize,
+        is_proceed: bool,
+    ) -> Result<(), LoadError> {
+        let campaign = &self.campaigns[self.campaign_idx];
+        assert!(
+            level_index < campaign.level_count(),
+            "level index: {} out of bounds for length: {}",
+            level_index,
+            campaign.level_count()
+        );
+
+        let level = campaign.read_level(ctx, level_index)?;
+        let campaign_level = &campaign.levels[level_index];
+        let (level_filename, level_id, is_tutorial) = (
+            campaign_level.source.filename(),
+            campaign_level.id.clone(),
+            campaign_level.is_tutorial,
+        );
+
+        let invalid_level = |reason: String| LoadError::InvalidLevel {
+            filename: level_filename.clone(),
+            reason,
+        };
+
+        // The first error is enough to find the broken spot, `level-lint` lists all of them
+        if let Some(diagnostic) = level_lint::lint_level(&level, &level_id, is_tutorial)
+            .into_iter()
+            .find(|diagnostic| diagnostic.severity == level_lint::Severity::Error)
+        {
+            return Err(invalid_level(diagnostic.to_string()));
+        }
+
+        let level_data =
+            level::parse_level(&level).map_err(|err| invalid_level(err.to_string()))?;
+
+        if self.assets.tileset.name != level_data.header.tileset {
+            self.assets.tileset = assets::Tileset::load(ctx, quad_ctx, &level_data.header.tileset)?;
+        }
+
+        // The replay and ghost attempts only start once the level is known to load
+        self.reset_state(is_proceed);
+
+        self.camera.set_world_size(level_data.header.world_size());
+
+        level::load_world(&mut self.world, level_data, is_tutorial);
+        if is_tutorial {
+            tutorial_system::prepare_world(&mut self.world, &level_id);
+        }
+
+        self.mesh_cache.prepare(ctx, quad_ctx, &self.world);
+
+        Ok(())
+    }
+
+    /// Loads the current level, a broken level file shows the error screen instead of the level
+    fn load_level(
+        &mut self,
+        ctx: &mut Context,
+        quad_ctx: &mut miniquad::Context,
+        is_proceed: bool,
+    ) -> bool {
+        match self.try_load_level(ctx, quad_ctx, self.level_idx, is_proceed) {
+            Ok(()) => true,
+            Err(err) => {
+                eprintln!("{}", err);
+                self.game_state = GameState::LoadError(err.to_string());
+                false
+            }
+        }
     }
 
     fn next_level(
@@ -412,44 +659,444 @@ impl Game {
         quad_ctx: &mut miniquad::Context,
         is_proceeed: bool,
     ) {
-        self.level_times[self.level_idx] = self.curr_level_time;
+        let progress = &mut self.progress[self.campaign_idx];
+        progress.level_times[self.level_idx] = self.world.curr_level_time;
+        progress.level_stats[self.level_idx].kills = self.world.kills;
+        progress.level_stats[self.level_idx].seed = self.world.seed;
+        self.ghost_system
+            .finish_level(&self.level_key(), self.world.curr_level_time);
+        if self.is_speedrun {
+            self.speedrun_system
+                .split(self.level_idx, self.world.curr_level_time);
+        }
 
-        if self.level_idx == level::LEVEL_COUNT - 1 {
+        if self.level_idx == self.campaign().level_count() - 1 {
             self.reset_state(is_proceeed);
             self.level_idx = 0;
             self.game_state = GameState::EndScreen;
         } else {
             self.level_idx += 1;
-            level::load_level(ctx, quad_ctx, self, self.level_idx, is_proceeed);
+            self.progress[self.campaign_idx].level_stats[self.level_idx] = LevelStats::default();
+            if !self.load_level(ctx, quad_ctx, is_proceeed) {
+                return;
+            }
 
-            self.n_objects =
-                1 + self.guards_basic.len() + self.guards_scout.len() + self.walls.len();
+            self.n_objects = 1
+                + self.world.guards_basic.len()
+                + self.world.guards_scout.len()
+                + self.world.walls.len();
             self.game_state = GameState::LevelAnimation;
-            self.curr_level_time = constants::LEVEL_ANIMATION_TIME;
+            self.world.curr_level_time = constants::LEVEL_ANIMATION_TIME;
         }
     }
 
     fn do_level_animation(&mut self, dt: f32) {
-        if self.curr_level_time <= 0.
-            && (self.player.is_moving() || self.level_idx < level::TUTORIAL_COUNT)
+        if self.world.curr_level_time <= 0.
+            && (self.input.move_dir != glam::Vec2::ZERO
+                || self.campaign().levels[self.level_idx].is_tutorial)
         {
             self.camera
                 .set_lerp_delta(constants::CAMERA_DEFAULT_LERP_DELTA);
 
-            self.curr_level_time = 0.;
+            self.world.curr_level_time = 0.;
             self.game_state = GameState::Game;
 
             return;
         }
 
         self.camera.set_lerp_delta(0.05);
-        if self.curr_level_time >= constants::LEVEL_ANIMATION_TIME / 3. {
-            self.camera.update(self.target.transform.position);
+        if self.world.curr_level_time >= constants::LEVEL_ANIMATION_TIME / 3. {
+            self.camera.update(self.world.target.transform.position);
+        } else {
+            self.camera.update(self.world.player.transform.position);
+        }
+
+        self.world.curr_level_time = (self.world.curr_level_time - dt).max(0.);
+    }
+
+    /// Level ID and content hash of the level selected on the leaderboard screen
+    fn leaderboard_key(&self) -> (String, String) {
+        let campaign = self.campaign();
+
+        if self.leaderboard_level == 0 {
+            (campaign.id.clone(), campaign.hash())
         } else {
-            self.camera.update(self.player.transform.position);
+            let level_idx = campaign.tutorial_count() + self.leaderboard_level - 1;
+            (
+                campaign.level_key(level_idx),
+                campaign.levels[level_idx].hash.clone(),
+            )
+        }
+    }
+
+    fn request_leaderboard(&mut self) {
+        let (level_id, level_hash) = self.leaderboard_key();
+        self.network_system
+            .do_request_leaderboard(&level_id, &level_hash);
+
+        self.leaderboard_page = 0;
+    }
+
+    /// Entries of every level of the finished run and of the whole campaign
+    fn level_entries(&self) -> Vec<PlayerEntry> {
+        let make_entry =
+            |level_id: &str, level_hash: String, time: f32, stats: LevelStats| PlayerEntry {
+                username: self.username.clone(),
+                time,
+                level_id: level_id.to_owned(),
+                level_hash,
+                timestamp: util::unix_time(),
+                game_version: env!("CARGO_PKG_VERSION").to_owned(),
+                kills: stats.kills,
+                detections: stats.detections,
+                replay: (stats.seed != 0).then(|| format!("{:016x}", stats.seed)),
+            };
+
+        let campaign = self.campaign();
+        let progress = &self.progress[self.campaign_idx];
+
+        let mut entries = (campaign.tutorial_count()..campaign.level_count())
+            .map(|lvl_idx| {
+                make_entry(
+                    &campaign.level_key(lvl_idx),
+                    campaign.levels[lvl_idx].hash.clone(),
+                    progress.level_times[lvl_idx],
+                    progress.level_stats[lvl_idx],
+                )
+            })
+            .collect::<Vec<PlayerEntry>>();
+
+        let campaign_stats = progress.level_stats[campaign.tutorial_count()..]
+            .iter()
+            .fold(LevelStats::default(), |acc, stats| LevelStats {
+                kills: acc.kills + stats.kills,
+                detections: acc.detections + stats.detections,
+                seed: 0,
+            });
+        entries.push(make_entry(
+            &campaign.id,
+            campaign.hash(),
+            entries.iter().map(|entry| entry.time).sum(),
+            campaign_stats,
+        ));
+
+        entries
+    }
+
+    /// Level selection, pagination and jump to the player on the leaderboard screen
+    fn leaderboard_key_down(&mut self, keycode: KeyCode) {
+        let level_count = 1 + self.campaign().level_count() - self.campaign().tutorial_count();
+        let page_count = match self.network_system.leaderboard() {
+            LeaderboardState::Ready(entries) => {
+                entries.len().div_ceil(constants::LEADERBOARD_SIZE).max(1)
+            }
+            _ => 1,
+        };
+
+        match keycode {
+            KeyCode::Left => {
+                self.leaderboard_level = (self.leaderboard_level + level_count - 1) % level_count;
+                self.request_leaderboard();
+            }
+            KeyCode::Right => {
+                self.leaderboard_level = (self.leaderboard_level + 1) % level_count;
+                self.request_leaderboard();
+            }
+            KeyCode::Up => self.leaderboard_page = self.leaderboard_page.saturating_sub(1),
+            KeyCode::Down => {
+                self.leaderboard_page = (self.leaderboard_page + 1).min(page_count - 1)
+            }
+            KeyCode::J => {
+                if let LeaderboardState::Ready(entries) = self.network_system.leaderboard() {
+                    if let Some(rank) = entries
+                        .iter()
+                        .position(|entry| entry.username == self.username)
+                    {
+                        self.leaderboard_page = rank / constants::LEADERBOARD_SIZE;
+                    }
+                }
+            }
+            _ => (),
         }
+    }
 
-        self.curr_level_time = (self.curr_level_time - dt).max(0.);
+    fn editor_key_down(
+        &mut self,
+        ctx: &mut Context,
+        quad_ctx: &mut miniquad::Context,
+        keycode: KeyCode,
+    ) {
+        let editor = &mut self.editor_system;
+
+        match keycode {
+            KeyCode::Escape => {
+                editor.pan_dir = glam::Vec2::ZERO;
+                editor.painting = None;
+                self.game_state = GameState::Menu;
+                self.level_idx = 0;
+            }
+            KeyCode::Tab => {
+                editor.tool = match editor.tool {
+                    EditorTool::Paint => EditorTool::Patrol,
+                    EditorTool::Patrol => EditorTool::Paint,
+                };
+            }
+            KeyCode::F2 => editor.save(),
+            KeyCode::F5 => self.start_playtest(ctx, quad_ctx),
+            KeyCode::F3 if editor.generate(GameRng::time_seed()) => {
+                self.refresh_editor(ctx, quad_ctx, 0);
+            }
+            KeyCode::Backspace if editor.tool == EditorTool::Patrol => {
+                editor.remove_waypoint();
+            }
+            KeyCode::Left => editor.pan_dir.x = -1.,
+            KeyCode::Right => editor.pan_dir.x = 1.,
+            KeyCode::Up => editor.pan_dir.y = -1.,
+            KeyCode::Down => editor.pan_dir.y = 1.,
+            _ => (),
+        }
+    }
+
+    /// Glyphs select the brush, in the patrol tool the keys edit the route of the selected guard
+    fn editor_text_input(&mut self, character: char) {
+        let editor = &mut self.editor_system;
+
+        match editor.tool {
+            EditorTool::Paint if editor_system::PALETTE.contains(&character) => {
+                editor.glyph = character;
+            }
+            EditorTool::Patrol => match character {
+                'm' => {
+                    editor.toggle_patrol_mode();
+                }
+                'w' => {
+                    editor.cycle_wait_time();
+                }
+                'l' => {
+                    editor.cycle_look_angle();
+                }
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+
+    fn editor_mouse_down(
+        &mut self,
+        ctx: &mut Context,
+        quad_ctx: &mut miniquad::Context,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        let Some(cell) = self.editor_cell(quad_ctx, x, y) else {
+            return;
+        };
+
+        let is_changed = match (self.editor_system.tool, button) {
+            (EditorTool::Paint, MouseButton::Left | MouseButton::Right) => {
+                let glyph = if button == MouseButton::Left {
+                    self.editor_system.glyph
+                } else {
+                    '-'
+                };
+                self.editor_system.painting = Some(glyph);
+                self.editor_system.paint(cell, glyph)
+            }
+            (EditorTool::Patrol, MouseButton::Left) => self.editor_system.patrol_click(cell),
+            (EditorTool::Patrol, MouseButton::Right) => self.editor_system.remove_waypoint(),
+            _ => false,
+        };
+
+        if is_changed {
+            self.refresh_editor(ctx, quad_ctx, 0);
+        }
+    }
+
+    /// Level block under the mouse
+    fn editor_cell(&self, quad_ctx: &miniquad::Context, x: f32, y: f32) -> Option<(u32, u32)> {
+        let screen_size = quad_ctx.screen_size();
+        let screen_pos = glam::vec2(
+            x * constants::WIDTH as f32 / screen_size.0,
+            y * constants::HEIGHT as f32 / screen_size.1,
+        );
+
+        self.editor_system
+            .cell_at(self.camera.global_position(screen_pos))
+    }
+
+    fn draw_editor(
+        &mut self,
+        ctx: &mut Context,
+        quad_ctx: &mut miniquad::Context,
+    ) -> Result<(), ggez::GameError> {
+        use graphics::DrawParam;
+
+        self.draw_game(ctx, quad_ctx)?;
+
+        let block_size = constants::LEVEL_BLOCK_SIZE as f32;
+        let cell_position =
+            |cell: (u32, u32)| glam::vec2(cell.0 as f32 * block_size, cell.1 as f32 * block_size);
+        let editor = &self.editor_system;
+
+        // Block grid
+        let world_size = editor.level.header.world_size();
+        self.grid_mesh.clear();
+        for x in (0..=world_size.x as u32).step_by(constants::LEVEL_BLOCK_SIZE as usize) {
+            self.grid_mesh.add(
+                DrawParam::default()
+                    .dest(self.camera.world_position(glam::vec2(x as f32, 0.)))
+                    .rotation(constants::PI / 2.)
+                    .scale(glam::vec2(world_size.y / constants::GRID_LINE_LENGTH, 0.5)),
+            );
+        }
+        for y in (0..=world_size.y as u32).step_by(constants::LEVEL_BLOCK_SIZE as usize) {
+            self.grid_mesh.add(
+                DrawParam::default()
+                    .dest(self.camera.world_position(glam::vec2(0., y as f32)))
+                    .scale(glam::vec2(world_size.x / constants::GRID_LINE_LENGTH, 0.5)),
+            );
+        }
+        self.grid_mesh.draw(ctx, quad_ctx, DrawParam::default())?;
+
+        // Patrol routes, from the guard through the waypoints
+        let mut routes = graphics::MeshBuilder::new();
+        let mut has_routes = false;
+        for (guard_cell, patrol) in &editor.level.patrols {
+            let mut points = vec![cell_position(*guard_cell)];
+            points.extend(
+                patrol
+                    .waypoints
+                    .iter()
+                    .map(|waypoint| cell_position(waypoint.cell)),
+            );
+            if patrol.mode == patrol_component::PatrolMode::Loop {
+                points.push(cell_position(patrol.waypoints[0].cell));
+            }
+
+            let points = points
+                .into_iter()
+                .map(|point| self.camera.world_position(point) + block_size / 2.)
+                .collect::<Vec<glam::Vec2>>();
+            if points.len() > 1 {
+                let width = if editor.selected_guard == Some(*guard_cell) {
+                    4.
+                } else {
+                    2.
+                };
+                routes.line(&points, width, constants::EDITOR_ROUTE_COLOR)?;
+                has_routes = true;
+            }
+
+            for (idx, waypoint) in patrol.waypoints.iter().enumerate() {
+                let mut label = (idx + 1).to_string();
+                if waypoint.wait_time > 0. {
+                    label += &format!(" wait={}", waypoint.wait_time);
+                }
+                if let Some(look_angle) = waypoint.look_angle {
+                    label += &format!(" look={}", look_angle);
+                }
+
+                graphics::queue_text(
+                    ctx,
+                    &util::make_text(label, 16.),
+                    self.camera.world_position(cell_position(waypoint.cell)) + 4.,
+                    None,
+                );
+            }
+        }
+        if has_routes {
+            let routes = routes.build(ctx, quad_ctx)?;
+            graphics::draw(ctx, quad_ctx, &routes, DrawParam::default())?;
+        }
+
+        if let Some(guard_cell) = editor.selected_guard {
+            graphics::draw(
+                ctx,
+                quad_ctx,
+                &self.editor_cursor,
+                DrawParam::default()
+                    .dest(self.camera.world_position(cell_position(guard_cell)))
+                    .color(constants::EDITOR_ROUTE_COLOR),
+            )?;
+        }
+        if let Some(cursor) = editor.cursor {
+            graphics::draw(
+                ctx,
+                quad_ctx,
+                &self.editor_cursor,
+                DrawParam::default().dest(self.camera.world_position(cell_position(cursor))),
+            )?;
+        }
+
+        let tool_str = match editor.tool {
+            EditorTool::Paint => editor_system::PALETTE
+                .iter()
+                .map(|&glyph| {
+                    if glyph == editor.glyph {
+                        format!("[{}]", glyph)
+                    } else {
+                        glyph.to_string()
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join(" "),
+            EditorTool::Patrol => {
+                let mode = editor
+                    .selected_guard
+                    .and_then(|guard_cell| editor.patrol(guard_cell))
+                    .map_or("", |patrol| match patrol.mode {
+                        patrol_component::PatrolMode::Loop => " (loop)",
+                        patrol_component::PatrolMode::PingPong => " (pingpong)",
+                    });
+                format!(
+                    "Patrol{}: click guard, click blocks, M mode, W wait, L look",
+                    mode
+                )
+            }
+        };
+
+        let lint_str = match editor.first_error() {
+            Some(diagnostic) => diagnostic.to_string(),
+            None => "No errors".to_owned(),
+        };
+
+        graphics::queue_text(
+            ctx,
+            &util::make_text(
+                format!(
+                    "{}{}\n{}",
+                    editor.filename,
+                    if editor.is_modified { " *" } else { "" },
+                    editor.status
+                ),
+                20.,
+            ),
+            glam::vec2(8., 8.),
+            None,
+        );
+        graphics::queue_text(
+            ctx,
+            &util::make_text(
+                format!(
+                    "{}\n{}\nTab: tool  F5: playtest  F3: generate  F2: save  Esc: menu",
+                    lint_str, tool_str
+                ),
+                20.,
+            ),
+            glam::vec2(8., constants::HEIGHT as f32 * 0.86),
+            None,
+        );
+
+        graphics::draw_queued_text(
+            ctx,
+            quad_ctx,
+            DrawParam::default(),
+            None,
+            graphics::default_filter(ctx),
+        )?;
+
+        Ok(())
     }
 
     fn draw_menu(
@@ -467,6 +1114,26 @@ impl Game {
             )),
         )?;
 
+        graphics::draw(
+            ctx,
+            quad_ctx,
+            &util::make_text("E: Level editor".into(), 20.),
+            graphics::DrawParam::default().dest(glam::vec2(8., 8.)),
+        )?;
+
+        // Level packs of the user are selected with the arrow keys
+        if self.campaigns.len() > 1 {
+            graphics::draw(
+                ctx,
+                quad_ctx,
+                &util::make_text(format!("< {} >", self.campaign().name), 28.),
+                graphics::DrawParam::default().dest(glam::vec2(
+                    constants::WIDTH as f32 * 0.05,
+                    constants::HEIGHT as f32 * 0.2,
+                )),
+            )?;
+        }
+
         self.menu_rectangle.clear();
 
         self.menu_rectangle
@@ -630,6 +1297,35 @@ impl Game {
             ),
         )?;
 
+        graphics::draw(
+            ctx,
+            quad_ctx,
+            &self.menu_square,
+            graphics::DrawParam::default().dest(constants::BTN_BOTTOM_LEFT_POS4),
+        )?;
+        if self.is_speedrun {
+            graphics::draw(
+                ctx,
+                quad_ctx,
+                &self.assets.checkmark,
+                graphics::DrawParam::default()
+                    .dest(constants::BTN_BOTTOM_LEFT_POS4 + glam::vec2(5., 5.))
+                    .scale(glam::Vec2::splat(constants::WIDTH as f32 / 800.)),
+            )?;
+        }
+        graphics::draw(
+            ctx,
+            quad_ctx,
+            &util::make_text("Speedrun".into(), 36.),
+            graphics::DrawParam::default().dest(
+                constants::BTN_BOTTOM_LEFT_POS4
+                    + glam::vec2(
+                        constants::WIDTH as f32 * 0.075,
+                        constants::HEIGHT as f32 * 0.0166,
+                    ),
+            ),
+        )?;
+
         Ok(())
     }
 
@@ -702,6 +1398,59 @@ When you complete your mission, a pathway to the next level will appear"
         Ok(())
     }
 
+    fn draw_load_error(
+        &mut self,
+        ctx: &mut Context,
+        quad_ctx: &mut miniquad::Context,
+        message: String,
+    ) -> Result<(), ggez::GameError> {
+        graphics::draw(
+            ctx,
+            quad_ctx,
+            &util::make_text("Loading failed".into(), 42.),
+            graphics::DrawParam::default().dest(glam::vec2(
+                constants::WIDTH as f32 * 0.3,
+                constants::HEIGHT as f32 * 0.0833,
+            )),
+        )?;
+
+        self.menu_rectangle.clear();
+        self.menu_rectangle
+            .add(graphics::DrawParam::default().dest(constants::BTN_BACK_POS));
+        self.menu_rectangle
+            .draw(ctx, quad_ctx, graphics::DrawParam::default())?;
+
+        graphics::draw(
+            ctx,
+            quad_ctx,
+            &util::make_text("Menu".into(), 36.),
+            graphics::DrawParam::default().dest(
+                constants::BTN_BACK_POS
+                    + glam::vec2(
+                        constants::WIDTH as f32 * 0.1,
+                        constants::HEIGHT as f32 * 0.033,
+                    ),
+            ),
+        )?;
+
+        let mut text = util::make_text(message, 20.);
+        text.set_bounds(
+            glam::vec2(constants::WIDTH as f32 * 0.85, constants::HEIGHT as f32),
+            graphics::Align::Left,
+        );
+        graphics::draw(
+            ctx,
+            quad_ctx,
+            &text,
+            graphics::DrawParam::default().dest(glam::vec2(
+                constants::WIDTH as f32 * 0.0375,
+                constants::HEIGHT as f32 * 0.4,
+            )),
+        )?;
+
+        Ok(())
+    }
+
     fn draw_keybindings(
         &mut self,
         ctx: &mut Context,
@@ -855,20 +1604,96 @@ When you complete your mission, a pathway to the next level will appear"
             ),
         )?;
 
-        if self.leaderboard_str.is_none()
-            && self.network_system.submit_finished()
-            && self.network_system.leaderboard_ready()
-        {
-            let mut network = NetworkSystem::new();
-            std::mem::swap(&mut network, &mut self.network_system);
+        let level_name = if self.leaderboard_level == 0 {
+            self.campaign().name.clone()
+        } else {
+            format!("Level {}", self.leaderboard_level)
+        };
 
-            self.leaderboard_str = Some(network.get_response());
+        graphics::draw(
+            ctx,
+            quad_ctx,
+            &util::make_text(format!("< {} >", level_name), 32.),
+            graphics::DrawParam::default().dest(glam::vec2(
+                constants::WIDTH as f32 * 0.4,
+                constants::HEIGHT as f32 * 0.19,
+            )),
+        )?;
+
+        graphics::draw(
+            ctx,
+            quad_ctx,
+            &util::make_text(
+                "Left/Right: level   Up/Down: page   J: jump to me".into(),
+                16.,
+            ),
+            graphics::DrawParam::default().dest(glam::vec2(
+                constants::WIDTH as f32 * 0.4,
+                constants::HEIGHT as f32 * 0.27,
+            )),
+        )?;
+
+        let pending_submits = self.network_system.pending_submits();
+        let submit_status = match (pending_submits, self.network_system.last_submit_error()) {
+            (0, None) => None,
+            (0, Some(err)) => Some(format!("A time was rejected:\n{}", err)),
+            (_, Some(err)) if err.is_config_error() => Some(format!(
+                "{} times waiting to be submitted, check the leaderboard settings:\n{}",
+                pending_submits, err
+            )),
+            (_, Some(err)) => Some(format!(
+                "{} times waiting to be submitted, retrying:\n{}",
+                pending_submits, err
+            )),
+            (_, None) => Some(format!("{} times waiting to be submitted", pending_submits)),
+        };
+        if let Some(status) = submit_status {
+            graphics::draw(
+                ctx,
+                quad_ctx,
+                &util::make_text(status, 16.),
+                graphics::DrawParam::default().dest(glam::vec2(
+                    constants::WIDTH as f32 * 0.4,
+                    constants::HEIGHT as f32 * 0.31,
+                )),
+            )?;
         }
 
-        let leaderboard_str = self
-            .leaderboard_str
-            .clone()
-            .unwrap_or("Requesting leaderboard...".to_owned());
+        let leaderboard_str = match self.network_system.leaderboard() {
+            LeaderboardState::Idle | LeaderboardState::InFlight => {
+                "Requesting leaderboard...".to_owned()
+            }
+            LeaderboardState::Failed(err) => format!("Could not load the leaderboard\n\n{}", err),
+            LeaderboardState::Ready(entries) if entries.is_empty() => "No times yet".to_owned(),
+            LeaderboardState::Ready(entries) => {
+                let page_count = entries.len().div_ceil(constants::LEADERBOARD_SIZE);
+                let page = self.leaderboard_page.min(page_count - 1);
+
+                let rows = entries
+                    .iter()
+                    .enumerate()
+                    .skip(page * constants::LEADERBOARD_SIZE)
+                    .take(constants::LEADERBOARD_SIZE)
+                    .map(|(rank, entry)| {
+                        format!(
+                            "{}{}. {}: {:.3}   kills: {}   detections: {}\n",
+                            if entry.username == self.username {
+                                "> "
+                            } else {
+                                ""
+                            },
+                            rank + 1,
+                            entry.username,
+                            entry.time,
+                            entry.kills,
+                            entry.detections
+                        )
+                    })
+                    .collect::<String>();
+
+                format!("{}\nPage {}/{}", rows, page + 1, page_count)
+            }
+        };
 
         graphics::draw(
             ctx,
@@ -880,7 +1705,7 @@ When you complete your mission, a pathway to the next level will appear"
             )),
         )?;
 
-        self.curr_level_time += ggez::timer::delta(ctx).as_secs_f32();
+        self.world.curr_level_time += ggez::timer::delta(ctx).as_secs_f32();
 
         Ok(())
     }
@@ -921,12 +1746,15 @@ When you complete your mission, a pathway to the next level will appear"
                 .dest(constants::BTN_BOTTOM_RIGHT_POS + glam::vec2(65., 20.)),
         )?;
 
-        let level_times_str = (level::TUTORIAL_COUNT..level::LEVEL_COUNT)
+        let tutorial_count = self.campaign().tutorial_count();
+        let level_times = &self.progress[self.campaign_idx].level_times;
+
+        let level_times_str = (tutorial_count..self.campaign().level_count())
             .map(|lvl_idx| {
                 format!(
                     "Level {}: {}\n",
-                    lvl_idx + 1 - level::TUTORIAL_COUNT,
-                    self.level_times[lvl_idx]
+                    lvl_idx + 1 - tutorial_count,
+                    level_times[lvl_idx]
                 )
             })
             .reduce(|acc, itm| acc + &itm)
@@ -939,11 +1767,7 @@ When you complete your mission, a pathway to the next level will appear"
             graphics::DrawParam::default().dest(glam::vec2(250., 20.)),
         )?;
 
-        let total_time = self
-            .level_times
-            .iter()
-            .skip(level::TUTORIAL_COUNT)
-            .sum::<f32>();
+        let total_time = level_times.iter().skip(tutorial_count).sum::<f32>();
         graphics::draw(
             ctx,
             quad_ctx,
@@ -967,6 +1791,39 @@ When you complete your mission, a pathway to the next level will appear"
         Ok(())
     }
 
+    /// Draws the current walk animation frame, or the body if the character is dead
+    fn draw_character(
+        &self,
+        ctx: &mut Context,
+        quad_ctx: &mut miniquad::Context,
+        transform: &TransformComponent,
+        animation: &AnimationComponent,
+        dead_component: &DeadComponent,
+    ) -> GameResult {
+        let (image, color) = if dead_component.is_dead {
+            (&self.assets.dead, dead_component.color)
+        } else {
+            (
+                &self.assets.walk_animation[animation.index],
+                animation.color,
+            )
+        };
+
+        sprite_component::render_image(
+            ctx,
+            quad_ctx,
+            image,
+            graphics::DrawParam::default()
+                .dest(
+                    self.camera
+                        .world_position(transform.interpolate(self.tick_alpha())),
+                )
+                .rotation(-transform.angle)
+                .scale(constants::SPRITE_SCALE)
+                .color(color),
+        )
+    }
+
     fn draw_game(
         &mut self,
         ctx: &mut Context,
@@ -974,76 +1831,119 @@ When you complete your mission, a pathway to the next level will appear"
     ) -> Result<(), ggez::GameError> {
         use graphics::DrawParam;
         let mut n_objects_drawn = 0;
+        let is_editor_level =
+            self.game_state == GameState::Editor || self.editor_system.is_playtest;
+
+        let world = &self.world;
+        let alpha = self.tick_alpha();
+        let tint = world.alert_system.alert_level.tint();
 
-        self.floor_tiles
+        world
+            .floor_tiles
             .iter()
-            .map(|floor_tile| {
-                sprite_component::render(
+            .chain(world.hiding_spots.iter())
+            .map(|tile| {
+                graphics::draw(
                     ctx,
                     quad_ctx,
-                    &floor_tile.sprite,
+                    self.assets.tileset.image(tile.kind),
                     DrawParam::default()
-                        .dest(self.camera.world_position(floor_tile.transform.position))
-                        .scale(floor_tile.sprite.scale)
+                        .dest(self.camera.world_position(tile.transform.position))
+                        .scale(tile.size / constants::SPRITE_SIZE as f32)
                         .color(graphics::Color::new(
-                            floor_tile.brightness,
-                            floor_tile.brightness
-                                * self.are_guards_alerted.then(|| 0.5).unwrap_or(1.),
-                            floor_tile.brightness
-                                * self.are_guards_alerted.then(|| 0.5).unwrap_or(1.),
+                            tile.brightness,
+                            tile.brightness * tint,
+                            tile.brightness * tint,
                             1.,
                         )),
                 )
             })
             .count();
 
-        if self.player.teleport.location.is_some() {
-            sprite_component::render_sprite(
+        if let Some(teleport_location) = world.player.teleport.location.as_ref() {
+            sprite_component::render_image(
                 ctx,
                 quad_ctx,
-                &self.player.teleport.sprite,
-                DrawParam::default().dest(
-                    self.camera
-                        .world_position(self.player.teleport.location.as_ref().unwrap().position),
-                ),
+                &self.assets.teleport,
+                DrawParam::default()
+                    .dest(self.camera.world_position(teleport_location.position))
+                    .scale(constants::SPRITE_SCALE),
             )?;
         }
 
-        sprite_component::render_sprite(
+        // Draw the best run of the level as a ghost
+        if let Some((ghost_pos, ghost_angle, ghost_frame)) = self
+            .ghost_system
+            .best(&self.level_key())
+            .filter(|_| self.game_state != GameState::LevelAnimation && !is_editor_level)
+            .and_then(|ghost| ghost.pose_at(world.curr_level_time))
+        {
+            // A frame out of range comes from a corrupt ghost file
+            if let Some(ghost_image) = self.assets.walk_animation.get(ghost_frame) {
+                sprite_component::render_image(
+                    ctx,
+                    quad_ctx,
+                    ghost_image,
+                    DrawParam::default()
+                        .dest(self.camera.world_position(ghost_pos))
+                        .rotation(-ghost_angle)
+                        .scale(constants::SPRITE_SCALE)
+                        .color(constants::GHOST_COLOR),
+                )?;
+            }
+        }
+
+        let player = &world.player;
+        sprite_component::render_image(
             ctx,
             quad_ctx,
-            &self.player.animation.get_curr_frame(),
+            &self.assets.walk_animation[player.animation.index],
             DrawParam::default()
-                .dest(self.camera.world_position(self.player.transform.position))
-                .rotation(-self.player.transform.angle),
+                .dest(
+                    self.camera
+                        .world_position(player.transform.interpolate(alpha)),
+                )
+                .rotation(-player.transform.angle)
+                .scale(constants::SPRITE_SCALE)
+                .color(player.animation.color),
         )?;
 
-        if self.camera.contains(&self.target.aabb) {
-            sprite_component::render_sprite(
+        if let Some((projectile_pos, projectile_height)) = player.throw.get_position() {
+            sprite_component::render_mesh(
                 ctx,
                 quad_ctx,
-                &self.target.get_curr_animation_frame(),
-                DrawParam::default()
-                    .dest(self.camera.world_position(self.target.transform.position))
-                    .rotation(-self.target.transform.angle),
+                &self.mesh_cache.projectile,
+                DrawParam::default().dest(
+                    self.camera.world_position(projectile_pos) - glam::vec2(0., projectile_height),
+                ),
+            )?;
+        }
+
+        let target = &world.target;
+        if self.camera.contains(&target.aabb) {
+            self.draw_character(
+                ctx,
+                quad_ctx,
+                &target.transform,
+                &target.animation,
+                &target.dead_component,
             )?;
             n_objects_drawn += 1;
         }
 
-        let all_guards = self.get_all_guards();
+        let all_guards = world.get_all_guards();
 
         // Draw guards
         n_objects_drawn += all_guards
             .iter()
             .filter(|guard| self.camera.contains(&guard.aabb))
             .map(|guard| {
-                sprite_component::render_sprite(
+                self.draw_character(
                     ctx,
                     quad_ctx,
-                    &guard.get_curr_animation_frame(),
-                    DrawParam::default()
-                        .dest(self.camera.world_position(guard.transform.position))
-                        .rotation(-guard.transform.angle),
+                    &guard.transform,
+                    &guard.animation,
+                    &guard.dead_component,
                 )
             })
             .count();
@@ -1054,22 +1954,24 @@ When you complete your mission, a pathway to the next level will appear"
                 .iter()
                 .filter(|guard| !guard.is_dead())
                 .map(|guard| {
-                    guard.look_components[guard.look_idx]
-                        .fov_mesh_composition
+                    let look = &guard.look_components[guard.look_idx];
+
+                    self.mesh_cache
+                        .fov_meshes(&look.rays)
                         .iter()
-                        .zip(&guard.look_components[guard.look_idx].ray_scales)
+                        .zip(&look.ray_scales)
                         .flat_map(|(fov_section, scale)| {
                             sprite_component::render_mesh(
                                 ctx,
                                 quad_ctx,
                                 fov_section,
                                 DrawParam::default()
-                                    .dest(self.camera.world_position(guard.transform.position))
+                                    .dest(
+                                        self.camera
+                                            .world_position(guard.transform.interpolate(alpha)),
+                                    )
                                     .rotation(
-                                        -constants::PI / 2.
-                                            - util::get_vec_angle(
-                                                guard.look_components[guard.look_idx].look_at,
-                                            ),
+                                        -constants::PI / 2. - util::get_vec_angle(look.look_at),
                                     )
                                     .scale(glam::Vec2::splat(*scale))
                                     .color(guard.look_color),
@@ -1078,6 +1980,30 @@ When you complete your mission, a pathway to the next level will appear"
                         .count()
                 })
                 .count();
+
+            // Draw suspicion meters above guards
+            all_guards
+                .iter()
+                .filter(|guard| !guard.is_dead() && guard.suspicion.suspicion > 0.)
+                .map(|guard| {
+                    sprite_component::render_mesh(
+                        ctx,
+                        quad_ctx,
+                        &self.mesh_cache.suspicion,
+                        DrawParam::default()
+                            .dest(
+                                self.camera
+                                    .world_position(guard.transform.interpolate(alpha))
+                                    - glam::vec2(
+                                        constants::SUSPICION_BAR_SIZE.x / 2.,
+                                        constants::SUSPICION_BAR_OFFSET,
+                                    ),
+                            )
+                            .scale(glam::vec2(guard.suspicion.suspicion, 1.))
+                            .color(guard.look_color),
+                    )
+                })
+                .count();
         }
 
         if self.debug_draw {
@@ -1085,26 +2011,26 @@ When you complete your mission, a pathway to the next level will appear"
             all_guards
                 .iter()
                 .map(|guard| {
-                    guard.look_components[guard.look_idx]
-                        .ray_lines
+                    let look = &guard.look_components[guard.look_idx];
+
+                    self.mesh_cache
+                        .ray_lines(&look.rays)
                         .iter()
-                        .enumerate()
-                        .map(|(ray_idx, ray_line)| {
+                        .zip(&look.ray_scales)
+                        .map(|(ray_line, scale)| {
                             sprite_component::render_mesh(
                                 ctx,
                                 quad_ctx,
                                 ray_line,
                                 DrawParam::default()
-                                    .dest(self.camera.world_position(guard.transform.position))
+                                    .dest(
+                                        self.camera
+                                            .world_position(guard.transform.interpolate(alpha)),
+                                    )
                                     .rotation(
-                                        -constants::PI / 2.
-                                            - util::get_vec_angle(
-                                                guard.look_components[guard.look_idx].look_at,
-                                            ),
+                                        -constants::PI / 2. - util::get_vec_angle(look.look_at),
                                     )
-                                    .scale(glam::Vec2::splat(
-                                        guard.look_components[guard.look_idx].ray_scales[ray_idx],
-                                    )),
+                                    .scale(glam::Vec2::splat(*scale)),
                             )
                         })
                         .count();
@@ -1115,9 +2041,8 @@ When you complete your mission, a pathway to the next level will appear"
             all_guards
                 .iter()
                 .map(|guard| {
-                    guard
-                        .compute_move_component
-                        .ray_lines
+                    self.mesh_cache
+                        .ray_lines(&guard.compute_move_component.rays)
                         .iter()
                         .map(|ray_line| {
                             sprite_component::render_mesh(
@@ -1125,7 +2050,10 @@ When you complete your mission, a pathway to the next level will appear"
                                 quad_ctx,
                                 ray_line,
                                 DrawParam::default()
-                                    .dest(self.camera.world_position(guard.transform.position))
+                                    .dest(
+                                        self.camera
+                                            .world_position(guard.transform.interpolate(alpha)),
+                                    )
                                     .rotation(
                                         -constants::PI / 2.
                                             - util::get_vec_angle(
@@ -1139,22 +2067,25 @@ When you complete your mission, a pathway to the next level will appear"
                 .count();
         }
 
-        n_objects_drawn += self
+        n_objects_drawn += world
             .walls
             .iter()
             .filter(|wall| self.camera.contains(&wall.aabb))
             .map(|wall| {
-                sprite_component::render(
+                graphics::draw(
                     ctx,
                     quad_ctx,
-                    &wall.sprite,
+                    self.assets.tileset.image(wall.kind),
                     DrawParam::default()
                         .dest(self.camera.world_position(wall.transform.position))
-                        .scale(wall.sprite.scale)
+                        .scale(
+                            glam::vec2(wall.aabb.rect.w, wall.aabb.rect.h)
+                                / constants::SPRITE_SIZE as f32,
+                        )
                         .color(graphics::Color::new(
                             wall.brightness,
-                            wall.brightness * self.are_guards_alerted.then(|| 0.5).unwrap_or(1.),
-                            wall.brightness * self.are_guards_alerted.then(|| 0.5).unwrap_or(1.),
+                            wall.brightness * tint,
+                            wall.brightness * tint,
                             1.,
                         )),
                 )
@@ -1164,8 +2095,8 @@ When you complete your mission, a pathway to the next level will appear"
         sprite_component::render_mesh(
             ctx,
             quad_ctx,
-            &self.player.stamina.stamina_mesh,
-            DrawParam::default().scale(glam::vec2(self.player.stamina.get_percentage(), 1.)),
+            &self.mesh_cache.stamina,
+            DrawParam::default().scale(glam::vec2(player.stamina.get_percentage(), 1.)),
         )
         .unwrap();
 
@@ -1187,14 +2118,15 @@ When you complete your mission, a pathway to the next level will appear"
             .unwrap();
         }
 
-        if self.target.is_dead() {
-            sprite_component::render_sprite(
+        if target.is_dead() {
+            sprite_component::render_image(
                 ctx,
                 quad_ctx,
-                &self.exit.sprite,
+                &self.assets.exit,
                 DrawParam::default()
-                    .dest(self.camera.world_position(self.exit.transform.position))
-                    .rotation(-self.exit.scale_rotation_counter),
+                    .dest(self.camera.world_position(world.exit.transform.position))
+                    .rotation(-world.exit.scale_rotation_counter)
+                    .scale(glam::Vec2::splat(world.exit.scale)),
             )?;
         }
 
@@ -1316,18 +2248,84 @@ When you complete your mission, a pathway to the next level will appear"
             );
         }
 
-        if self.level_idx >= level::TUTORIAL_COUNT {
+        // The chapter and the level are announced while the camera pans over the level
+        if self.game_state == GameState::LevelAnimation {
+            let level = &self.campaign().levels[self.level_idx];
+            let level_name = if self.world.level_header.name.is_empty() {
+                &level.id
+            } else {
+                &self.world.level_header.name
+            };
+
+            graphics::queue_text(
+                ctx,
+                &util::make_text(format!("{}: {}", level.chapter, level_name), 32.),
+                glam::vec2(
+                    constants::WIDTH as f32 * 0.05,
+                    constants::HEIGHT as f32 * 0.05,
+                ),
+                None,
+            );
+        }
+
+        if self.editor_system.is_playtest
+            || (!is_editor_level && !self.campaign().levels[self.level_idx].is_tutorial)
+        {
+            let level_key = self.level_key();
+            let split_delta = if is_editor_level {
+                None
+            } else {
+                self.ghost_system.split_delta(
+                    &level_key,
+                    self.world.player.transform.position,
+                    self.world.curr_level_time,
+                )
+            }
+            .map_or(String::new(), |delta| format!(" ({:+.1})", delta));
+
+            let (level_time_str, mut text_x) = match self.world.level_header.par_time {
+                Some(par_time) => (
+                    format!(
+                        "{:.1}{} / {:.1}",
+                        self.world.curr_level_time, split_delta, par_time
+                    ),
+                    0.8,
+                ),
+                None => (
+                    format!("{:.1}{}", self.world.curr_level_time, split_delta),
+                    0.9,
+                ),
+            };
+            if !split_delta.is_empty() {
+                text_x -= 0.1;
+            }
+
             graphics::queue_text(
                 ctx,
-                &util::make_text(format!("{:.1}", self.curr_level_time), 24.),
+                &util::make_text(level_time_str, 24.),
                 glam::vec2(
-                    constants::WIDTH as f32 * 0.9,
+                    constants::WIDTH as f32 * text_x,
                     constants::HEIGHT as f32 * 0.95,
                 ),
                 None,
             );
         }
 
+        if self.is_speedrun && self.game_state != GameState::LevelAnimation && !is_editor_level {
+            if let Some((run_time, delta)) =
+                self.speedrun_system.comparison(self.world.curr_level_time)
+            {
+                let delta_str = delta.map_or(String::new(), |delta| format!(" ({:+.1})", delta));
+
+                graphics::queue_text(
+                    ctx,
+                    &util::make_text(format!("Run {:.1}{}", run_time, delta_str), 24.),
+                    glam::vec2(constants::WIDTH as f32 * 0.7, 8.),
+                    None,
+                );
+            }
+        }
+
         if self.debug_draw {
             graphics::queue_text(
                 ctx,
@@ -1338,7 +2336,10 @@ When you complete your mission, a pathway to the next level will appear"
             graphics::queue_text(
                 ctx,
                 &util::make_text(
-                    format!("player grid index: {}", self.player.transform.grid_index),
+                    format!(
+                        "player grid index: {}",
+                        self.world.player.transform.grid_index
+                    ),
                     24.,
                 ),
                 glam::vec2(8., 80.),
@@ -1346,17 +2347,21 @@ When you complete your mission, a pathway to the next level will appear"
             );
 
             // Draw grid
+            let world_size = self.world.level_header.world_size();
             self.grid_mesh.clear();
-            for x in (0..=constants::MAX_WORLD_X).step_by(constants::GRID_CELL_SIZE) {
+            for x in (0..=world_size.x as u32).step_by(constants::GRID_CELL_SIZE) {
                 self.grid_mesh.add(
                     DrawParam::default()
                         .dest(self.camera.world_position(glam::vec2(x as f32, 0.)))
-                        .rotation(constants::PI / 2.),
+                        .rotation(constants::PI / 2.)
+                        .scale(glam::vec2(world_size.y / constants::GRID_LINE_LENGTH, 1.)),
                 );
             }
-            for y in (0..=constants::MAX_WORLD_Y).step_by(constants::GRID_CELL_SIZE) {
+            for y in (0..=world_size.y as u32).step_by(constants::GRID_CELL_SIZE) {
                 self.grid_mesh.add(
-                    DrawParam::default().dest(self.camera.world_position(glam::vec2(0., y as f32))),
+                    DrawParam::default()
+                        .dest(self.camera.world_position(glam::vec2(0., y as f32)))
+                        .scale(glam::vec2(world_size.x / constants::GRID_LINE_LENGTH, 1.)),
                 );
             }
 
@@ -1381,6 +2386,8 @@ When you complete your mission, a pathway to the next level will appear"
             "mute": !self.sound_collection.is_on,
             "particles": self.particle_system.is_activated,
             "skip_tutorial": self.is_skip_tutorial,
+            "speedrun": self.is_speedrun,
+            "username": self.username,
             "up": self.keybind_map.get("up").unwrap_or(&util::MyKeyCode(ggez::event::KeyCode::Up)).0 as u8,
             "down": self.keybind_map.get("down").unwrap_or(&util::MyKeyCode(ggez::event::KeyCode::Down)).0 as u8,
             "left": self.keybind_map.get("left").unwrap_or(&util::MyKeyCode(ggez::event::KeyCode::Left)).0 as u8,
@@ -1393,7 +2400,17 @@ When you complete your mission, a pathway to the next level will appear"
             "restart": self.keybind_map.get("restart").unwrap_or(&util::MyKeyCode(ggez::event::KeyCode::R)).0 as u8,
         });
 
-        std::fs::write(filename, serde_json::to_string_pretty(&json).unwrap()).unwrap();
+        // Keep the settings which are only edited in the file, like the leaderboard server
+        let data = std::fs::read_to_string(filename).unwrap_or_default();
+        let mut config = match serde_json::from_str(&data) {
+            Ok(serde_json::Value::Object(config)) => config,
+            _ => serde_json::Map::new(),
+        };
+        if let serde_json::Value::Object(settings) = json {
+            config.extend(settings);
+        }
+
+        std::fs::write(filename, serde_json::to_string_pretty(&config).unwrap()).unwrap();
     }
 }
 
@@ -1403,8 +2420,15 @@ impl ggez::event::EventHandler<ggez::GameError> for Game {
         ctx: &mut Context,
         quad_ctx: &mut miniquad::Context,
     ) -> Result<(), ggez::GameError> {
-        // NOTE: Since this spawns a thread, it is okay to block here
-        self.tokio_runtime.block_on(self.network_system.tick());
+        self.network_system.update();
+
+        if self.game_state == GameState::Editor {
+            self.editor_system
+                .update(ggez::timer::delta(ctx).as_secs_f32());
+            self.camera.update(self.editor_system.camera_center);
+
+            return Ok(());
+        }
 
         // Handle keybind change
         if let GameState::KeybindInput(key) = &self.game_state {
@@ -1421,6 +2445,37 @@ impl ggez::event::EventHandler<ggez::GameError> for Game {
             return Ok(());
         }
 
+        // Start the queued replay, it skips the menu and the level animation
+        if let Some((campaign_id, level_idx)) = self
+            .replay_system
+            .pending_level()
+            .map(|(campaign_id, level_idx)| (campaign_id.to_owned(), level_idx))
+        {
+            if self.game_state == GameState::Menu {
+                match self
+                    .campaigns
+                    .iter()
+                    .position(|campaign| campaign.id == campaign_id)
+                    .filter(|&campaign_idx| level_idx < self.campaigns[campaign_idx].level_count())
+                {
+                    Some(campaign_idx) => {
+                        self.select_campaign(campaign_idx);
+                        self.level_idx = level_idx;
+                        if self.load_level(ctx, quad_ctx, true) {
+                            self.game_state = GameState::Game;
+                        }
+                    }
+                    None => {
+                        eprintln!(
+                            "replay level {} of campaign '{}' not found",
+                            level_idx, campaign_id
+                        );
+                        self.replay_system.cancel_playback();
+                    }
+                }
+            }
+        }
+
         if self.game_state == GameState::Menu
             || self.game_state == GameState::Info
             || self.game_state == GameState::Keybindings
@@ -1429,6 +2484,7 @@ impl ggez::event::EventHandler<ggez::GameError> for Game {
             || self.game_state == GameState::Pause
             || self.game_state == GameState::EndScreen
             || self.game_state == GameState::SubmitTime
+            || matches!(self.game_state, GameState::LoadError(_))
         {
             return Ok(());
         }
@@ -1443,19 +2499,75 @@ impl ggez::event::EventHandler<ggez::GameError> for Game {
 
         mouse_input_handler::system(self, ggez::timer::time_since_start(ctx).as_secs_f32());
 
-        entities::wall::check_collision(self);
+        // Step the world at a fixed rate, independent of the frame rate
+        self.tick_accumulator = (self.tick_accumulator + dt).min(constants::MAX_FRAME_TIME);
+        while self.tick_accumulator >= constants::FIXED_DT {
+            self.tick_accumulator -= constants::FIXED_DT;
+
+            let input = if self.editor_system.is_playtest {
+                self.input.clone()
+            } else {
+                self.replay_system.tick_input(self.world.tick, &self.input)
+            };
+            let events = self.world.step(&input, constants::FIXED_DT);
+            if !self.editor_system.is_playtest {
+                self.ghost_system.record(&self.world);
+            }
+            self.input.clear_actions();
+            self.handle_world_events(ctx, events);
+
+            if self.world.is_player_detected || self.world.exit.player_exited {
+                self.replay_system.finish_attempt();
+                break;
+            }
+            if self.replay_system.is_playback_finished(self.world.tick) {
+                break;
+            }
+        }
+
+        if let Some(guard_idx) = self.world.player.guard_to_attack_idx {
+            let pos = self.world.get_all_guards()[guard_idx]
+                .transform
+                .interpolate(self.tick_alpha());
+            self.camera.update(pos);
 
-        entities::player::system(ctx, self, dt);
+            self.overlay_system.set_active_at(0, true);
+            self.overlay_system.set_pos_at(0, pos);
+        } else {
+            self.overlay_system.set_active_at(0, false);
+        }
+
+        if self.editor_system.is_playtest
+            && (self.world.is_player_detected || self.world.exit.player_exited)
+        {
+            let status = if self.world.exit.player_exited {
+                format!("Finished in {:.1}s", self.world.curr_level_time)
+            } else {
+                format!("Detected after {:.1}s", self.world.curr_level_time)
+            };
+            self.stop_playtest(ctx, quad_ctx, status);
 
-        self.target.update(dt);
+            return Ok(());
+        }
 
-        entities::guards::system(ctx, self, dt);
+        if self.world.is_player_detected {
+            self.game_state = GameState::GameOver;
+            self.progress[self.campaign_idx].level_stats[self.level_idx].detections += 1;
+        }
 
-        look_component::system(self);
+        if self.replay_system.is_playback_finished(self.world.tick) {
+            self.replay_system.stop_playback();
 
-        entities::exit::system(self, dt);
+            if self.game_state == GameState::Game {
+                self.game_state = GameState::Menu;
+                self.level_idx = 0;
+                return Ok(());
+            }
+        }
 
-        level::system(self);
+        if !self.editor_system.is_playtest {
+            tutorial_system::system(self);
+        }
 
         particle_system::system(self, dt);
 
@@ -1463,9 +2575,7 @@ impl ggez::event::EventHandler<ggez::GameError> for Game {
 
         camera_component::system(self);
 
-        self.curr_level_time += dt;
-
-        if self.exit.player_exited {
+        if self.world.exit.player_exited {
             self.next_level(ctx, quad_ctx, true);
         }
 
@@ -1503,6 +2613,14 @@ impl ggez::event::EventHandler<ggez::GameError> for Game {
             return self.draw_end_screen(ctx, quad_ctx);
         }
 
+        if let GameState::LoadError(message) = &self.game_state {
+            return self.draw_load_error(ctx, quad_ctx, message.clone());
+        }
+
+        if self.game_state == GameState::Editor {
+            return self.draw_editor(ctx, quad_ctx);
+        }
+
         self.draw_game(ctx, quad_ctx)
     }
 
@@ -1527,6 +2645,48 @@ impl ggez::event::EventHandler<ggez::GameError> for Game {
             return;
         }
 
+        if self.game_state == GameState::Leaderboard {
+            self.leaderboard_key_down(keycode);
+            return;
+        }
+
+        if self.game_state == GameState::Editor {
+            self.editor_key_down(ctx, quad_ctx, keycode);
+            return;
+        }
+
+        if self.game_state == GameState::Menu {
+            let campaign_count = self.campaigns.len();
+            match keycode {
+                KeyCode::Left => {
+                    self.select_campaign((self.campaign_idx + campaign_count - 1) % campaign_count)
+                }
+                KeyCode::Right => self.select_campaign((self.campaign_idx + 1) % campaign_count),
+                KeyCode::E => {
+                    self.open_editor(ctx, quad_ctx);
+                    return;
+                }
+                _ => (),
+            }
+        }
+
+        if self.editor_system.is_playtest
+            && (self.game_state == GameState::Game || self.game_state == GameState::Pause)
+            && keycode == KeyCode::Escape
+        {
+            self.stop_playtest(ctx, quad_ctx, "Playtest stopped".to_owned());
+            return;
+        }
+
+        if let GameState::LoadError(_) = &self.game_state {
+            if keycode == KeyCode::Escape {
+                self.game_state = GameState::Menu;
+                self.level_idx = 0;
+            }
+
+            return;
+        }
+
         if (self.game_state == GameState::Game || self.game_state == GameState::Pause)
             && keycode == KeyCode::Escape
         {
@@ -1542,28 +2702,21 @@ impl ggez::event::EventHandler<ggez::GameError> for Game {
         for (key, value) in &self.keybind_map {
             if keycode == value.0 {
                 match key.as_str() {
-                    "up" => self.player.set_y_dir(-1.),
-                    "down" => self.player.set_y_dir(1.),
-                    "left" => self.player.set_x_dir(-1.),
-                    "right" => self.player.set_x_dir(1.),
-                    "stealth" => self.player.set_stealth_intent(true),
-                    "teleport" => {
-                        if self.game_state == GameState::GameOver {
-                            return;
-                        }
-
-                        self.player.teleport_action(
-                            ctx,
-                            &mut self.sound_collection,
-                            &mut self.particle_system,
-                        );
+                    "up" => self.input.set_y_dir(-1.),
+                    "down" => self.input.set_y_dir(1.),
+                    "left" => self.input.set_x_dir(-1.),
+                    "right" => self.input.set_x_dir(1.),
+                    "stealth" => self.input.stealth = true,
+                    "teleport" => self.input.teleport = self.game_state == GameState::Game,
+                    "sneak" => self.input.move_type = entities::player::MoveType::Slow,
+                    "sprint" => self.input.move_type = entities::player::MoveType::Sprint,
+                    "attack" => self.input.attack = self.game_state == GameState::Game,
+                    "restart" if self.editor_system.is_playtest => {
+                        self.start_playtest(ctx, quad_ctx);
+                    }
+                    "restart" => {
+                        self.load_level(ctx, quad_ctx, false);
                     }
-                    "sneak" => self.player.set_move_type(entities::player::MoveType::Slow),
-                    "sprint" => self
-                        .player
-                        .set_move_type(entities::player::MoveType::Sprint),
-                    "attack" => entities::player::try_attack_guard(ctx, self),
-                    "restart" => level::load_level(ctx, quad_ctx, self, self.level_idx, false),
                     _ => (),
                 }
 
@@ -1583,42 +2736,52 @@ impl ggez::event::EventHandler<ggez::GameError> for Game {
         keycode: KeyCode,
         _keymods: KeyMods,
     ) {
+        if self.game_state == GameState::Editor {
+            match keycode {
+                KeyCode::Left | KeyCode::Right => self.editor_system.pan_dir.x = 0.,
+                KeyCode::Up | KeyCode::Down => self.editor_system.pan_dir.y = 0.,
+                _ => (),
+            }
+
+            return;
+        }
+
         if (keycode
             == self
                 .keybind_map
                 .get("up")
                 .unwrap_or(&util::MyKeyCode(KeyCode::Up))
                 .0)
-            && self.player.move_component.direction.y < 0.
+            && self.input.move_dir.y < 0.
         {
-            self.player.set_y_dir(0.);
+            self.input.set_y_dir(0.);
         } else if (keycode
             == self
                 .keybind_map
                 .get("down")
                 .unwrap_or(&util::MyKeyCode(KeyCode::Down))
                 .0)
-            && self.player.move_component.direction.y > 0.
+            && self.input.move_dir.y > 0.
         {
-            self.player.set_y_dir(0.);
+            self.input.set_y_dir(0.);
         } else if (keycode
             == self
                 .keybind_map
                 .get("left")
                 .unwrap_or(&util::MyKeyCode(KeyCode::Left))
                 .0)
-            && self.player.move_component.direction.x < 0.
+            && self.input.move_dir.x < 0.
         {
-            self.player.set_x_dir(0.);
+            self.input.set_x_dir(0.);
         } else if (keycode
             == self
                 .keybind_map
                 .get("right")
                 .unwrap_or(&util::MyKeyCode(KeyCode::Right))
                 .0)
-            && self.player.move_component.direction.x > 0.
+            && self.input.move_dir.x > 0.
         {
-            self.player.set_x_dir(0.);
+            self.input.set_x_dir(0.);
         } else if keycode
             == self
                 .keybind_map
@@ -1626,7 +2789,7 @@ impl ggez::event::EventHandler<ggez::GameError> for Game {
                 .unwrap_or(&util::MyKeyCode(KeyCode::D))
                 .0
         {
-            self.player.set_stealth_intent(false);
+            self.input.stealth = false;
         } else if keycode
             == self
                 .keybind_map
@@ -1640,8 +2803,7 @@ impl ggez::event::EventHandler<ggez::GameError> for Game {
                     .unwrap_or(&util::MyKeyCode(KeyCode::S))
                     .0
         {
-            self.player
-                .set_move_type(entities::player::MoveType::Normal);
+            self.input.move_type = entities::player::MoveType::Normal;
         }
     }
 
@@ -1651,6 +2813,11 @@ impl ggez::event::EventHandler<ggez::GameError> for Game {
         _quad_ctx: &mut miniquad::Context,
         character: char,
     ) {
+        if self.game_state == GameState::Editor {
+            self.editor_text_input(character);
+            return;
+        }
+
         if self.game_state == GameState::EndScreen && self.player_name.len() < 28 {
             self.player_name.push(character);
         }
@@ -1664,6 +2831,11 @@ impl ggez::event::EventHandler<ggez::GameError> for Game {
         x: f32,
         y: f32,
     ) {
+        if self.game_state == GameState::Editor {
+            self.editor_mouse_down(ctx, quad_ctx, button, x, y);
+            return;
+        }
+
         match button {
             MouseButton::Left => {
                 let curr_t = ggez::timer::time_since_start(ctx).as_secs_f32();
@@ -1699,6 +2871,15 @@ impl ggez::event::EventHandler<ggez::GameError> for Game {
                     ) {
                         self.is_skip_tutorial = !self.is_skip_tutorial;
 
+                        setting_changed = true;
+                    } else if util::rect_contains_point(
+                        screen_size,
+                        constants::BTN_DIM_SQUARE,
+                        constants::BTN_BOTTOM_LEFT_POS4,
+                        glam::vec2(x, y),
+                    ) {
+                        self.is_speedrun = !self.is_speedrun;
+
                         setting_changed = true;
                     }
 
@@ -1714,6 +2895,7 @@ impl ggez::event::EventHandler<ggez::GameError> for Game {
                     || self.game_state == GameState::GameOver
                     || self.game_state == GameState::EndScreen
                     || self.game_state == GameState::Pause
+                    || matches!(self.game_state, GameState::LoadError(_))
                 {
                     if let Some(new_game_state) = self.mouse_input_handler.handle_menu_pressed(
                         &self.game_state,
@@ -1728,19 +2910,23 @@ impl ggez::event::EventHandler<ggez::GameError> for Game {
                             if self.game_state == GameState::Menu {
                                 self.level_idx = 0;
                                 if self.is_skip_tutorial {
-                                    self.level_idx = level::TUTORIAL_COUNT;
+                                    self.level_idx = self.campaign().tutorial_count();
                                 }
+                                self.progress[self.campaign_idx] =
+                                    CampaignProgress::new(self.campaign());
                             }
 
-                            level::load_level(ctx, quad_ctx, self, self.level_idx, is_proceed);
+                            if !self.load_level(ctx, quad_ctx, is_proceed) {
+                                return;
+                            }
                             self.n_objects = 1
-                                + self.guards_basic.len()
-                                + self.guards_scout.len()
-                                + self.walls.len();
+                                + self.world.guards_basic.len()
+                                + self.world.guards_scout.len()
+                                + self.world.walls.len();
 
                             if is_proceed {
                                 self.game_state = GameState::LevelAnimation;
-                                self.curr_level_time = constants::LEVEL_ANIMATION_TIME;
+                                self.world.curr_level_time = constants::LEVEL_ANIMATION_TIME;
                             } else {
                                 self.game_state = GameState::Game
                             }
@@ -1752,35 +2938,30 @@ impl ggez::event::EventHandler<ggez::GameError> for Game {
                             }
 
                             self.game_state = new_game_state;
-                            self.curr_level_time = 0.;
+                            self.world.curr_level_time = 0.;
 
                             if self.game_state == GameState::Menu {
                                 self.level_idx = 0;
+                                self.speedrun_system.reset_run();
                             }
                         }
 
                         if self.game_state == GameState::Leaderboard
-                            && !self.network_system.request_in_progress()
-                            && self.leaderboard_str.is_none()
+                            && matches!(self.network_system.leaderboard(), LeaderboardState::Idle)
                         {
-                            self.network_system.do_request_leaderboard();
+                            self.request_leaderboard();
                         }
 
                         if self.game_state == GameState::SubmitTime {
-                            self.leaderboard_str = None;
-
-                            let total_time = self
-                                .level_times
-                                .iter()
-                                .skip(level::TUTORIAL_COUNT)
-                                .sum::<f32>();
-                            self.network_system.do_submit_time_and_reqeust_leaderboard(
-                                self.player_name.trim().to_owned(),
-                                total_time,
-                            );
-
-                            self.player_name = String::new();
+                            self.username = self.player_name.trim().to_owned();
+                            self.write_config(&util::config_filename());
+
+                            self.network_system.do_submit_times(self.level_entries());
+
+                            self.player_name = self.username.clone();
+                            self.leaderboard_level = 0;
                             self.game_state = GameState::Leaderboard;
+                            self.request_leaderboard();
                         }
                     }
 
@@ -1824,6 +3005,19 @@ impl ggez::event::EventHandler<ggez::GameError> for Game {
                 // NOTE: there is no PlayerAction to handle here
                 self.mouse_input_handler.handle_game_pressed(true, curr_t);
             }
+            MouseButton::Right => {
+                if self.game_state != GameState::Game {
+                    return;
+                }
+
+                let screen_size = quad_ctx.screen_size();
+                let screen_pos = glam::vec2(
+                    x * constants::WIDTH as f32 / screen_size.0,
+                    y * constants::HEIGHT as f32 / screen_size.1,
+                );
+
+                self.input.throw_target = Some(self.camera.global_position(screen_pos));
+            }
             _ => (),
         }
     }
@@ -1836,23 +3030,24 @@ impl ggez::event::EventHandler<ggez::GameError> for Game {
         _x: f32,
         _y: f32,
     ) {
+        if self.game_state == GameState::Editor {
+            self.editor_system.painting = None;
+            return;
+        }
+
         match button {
             MouseButton::Left => {
                 let curr_t = ggez::timer::time_since_start(ctx).as_secs_f32();
 
                 match self.mouse_input_handler.handle_game_pressed(false, curr_t) {
                     Some(mouse_input_handler::PlayerAction::Teleport) => {
-                        self.player.teleport_action(
-                            ctx,
-                            &mut self.sound_collection,
-                            &mut self.particle_system,
-                        )
+                        self.input.teleport = self.game_state == GameState::Game
                     }
                     Some(mouse_input_handler::PlayerAction::Stealth(is_stealth)) => {
-                        self.player.set_stealth_intent(is_stealth)
+                        self.input.stealth = is_stealth
                     }
                     Some(mouse_input_handler::PlayerAction::StopMoving) => {
-                        self.player.set_dir(glam::Vec2::ZERO)
+                        self.input.move_dir = glam::Vec2::ZERO
                     }
                     None => (),
                 }
@@ -1863,24 +3058,99 @@ impl ggez::event::EventHandler<ggez::GameError> for Game {
 
     fn mouse_motion_event(
         &mut self,
-        _ctx: &mut Context,
-        _quad_ctx: &mut miniquad::Context,
+        ctx: &mut Context,
+        quad_ctx: &mut miniquad::Context,
         x: f32,
         y: f32,
         _dx: f32,
         _dy: f32,
     ) {
+        if self.game_state == GameState::Editor {
+            self.editor_system.cursor = self.editor_cell(quad_ctx, x, y);
+
+            if let (Some(cell), Some(glyph)) =
+                (self.editor_system.cursor, self.editor_system.painting)
+            {
+                if self.editor_system.paint(cell, glyph) {
+                    self.refresh_editor(ctx, quad_ctx, 0);
+                }
+            }
+
+            return;
+        }
+
         let direction = self
             .mouse_input_handler
-            .get_move_direction(_quad_ctx.screen_size(), glam::vec2(x, y));
+            .get_move_direction(quad_ctx.screen_size(), glam::vec2(x, y));
 
         if let Some(dir) = direction {
-            self.player.set_dir(dir);
+            self.input.move_dir = dir;
         }
 
         self.is_touch_joystick_activated = direction.is_some();
         if direction.is_some() {
-            self.player.set_stealth_intent(false);
+            self.input.stealth = false;
+        }
+    }
+}
+
+/// Shown instead of the game when the assets it needs at startup could not be loaded
+struct LoadErrorScreen {
+    message: String,
+}
+
+impl LoadErrorScreen {
+    fn new(err: LoadError) -> Self {
+        Self {
+            message: format!("{}\n\nPress Escape to quit", err),
+        }
+    }
+}
+
+impl ggez::event::EventHandler<ggez::GameError> for LoadErrorScreen {
+    fn update(
+        &mut self,
+        _ctx: &mut Context,
+        _quad_ctx: &mut miniquad::Context,
+    ) -> Result<(), ggez::GameError> {
+        Ok(())
+    }
+
+    fn draw(
+        &mut self,
+        ctx: &mut Context,
+        quad_ctx: &mut miniquad::Context,
+    ) -> Result<(), ggez::GameError> {
+        graphics::clear(ctx, quad_ctx, constants::BG_COLOR);
+
+        let mut text = util::make_text(self.message.clone(), 20.);
+        text.set_bounds(
+            glam::vec2(constants::WIDTH as f32 * 0.9, constants::HEIGHT as f32),
+            graphics::Align::Left,
+        );
+        graphics::draw(
+            ctx,
+            quad_ctx,
+            &text,
+            graphics::DrawParam::default().dest(glam::vec2(
+                constants::WIDTH as f32 * 0.05,
+                constants::HEIGHT as f32 * 0.1,
+            )),
+        )?;
+
+        Ok(())
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        _quad_ctx: &mut miniquad::Context,
+        keycode: KeyCode,
+        _keymods: KeyMods,
+        _repeat: bool,
+    ) {
+        if keycode == KeyCode::Escape {
+            ggez::event::quit(ctx);
         }
     }
 }
@@ -1894,7 +3164,13 @@ fn main() -> GameResult {
         .high_dpi(true)
         .cache(Some(include_bytes!("resources.tar")));
 
-    ggez::start(conf, |mut context, mut quad_ctx| {
-        Box::new(Game::new(&mut context, &mut quad_ctx))
+    ggez::start(conf, |context, quad_ctx| {
+        match Game::new(context, quad_ctx) {
+            Ok(game) => Box::new(game),
+            Err(err) => {
+                eprintln!("{}", err);
+                Box::new(LoadErrorScreen::new(err))
+            }
+        }
     })
 }
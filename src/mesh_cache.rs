@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use crate::constants;
+use crate::world::World;
+
+use ggez::graphics;
+
+/// Rays are identified by the bits of their coordinates, guards with the same look share the meshes
+type RaysKey = Vec<(u32, u32)>;
+
+/// Meshes for drawing the world, which the gameplay components only describe
+pub struct MeshCache {
+    pub stamina: graphics::Mesh,
+    pub suspicion: graphics::Mesh,
+    pub projectile: graphics::Mesh,
+    fov_meshes: HashMap<RaysKey, Vec<graphics::Mesh>>,
+    ray_lines: HashMap<RaysKey, Vec<graphics::Mesh>>,
+}
+
+impl MeshCache {
+    pub fn new(ctx: &mut ggez::Context, quad_ctx: &mut ggez::miniquad::GraphicsContext) -> Self {
+        let stamina = graphics::Mesh::new_rectangle(
+            ctx,
+            quad_ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(10., 10., 200., 20.),
+            graphics::Color::GREEN,
+        )
+        .unwrap();
+
+        let suspicion = graphics::Mesh::new_rectangle(
+            ctx,
+            quad_ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(
+                0.,
+                0.,
+                constants::SUSPICION_BAR_SIZE.x,
+                constants::SUSPICION_BAR_SIZE.y,
+            ),
+            graphics::Color::WHITE,
+        )
+        .unwrap();
+
+        let projectile = graphics::Mesh::new_circle(
+            ctx,
+            quad_ctx,
+            graphics::DrawMode::fill(),
+            glam::Vec2::ZERO,
+            constants::THROW_PROJECTILE_SIZE,
+            0.5,
+            graphics::Color::new(0.6, 0.6, 0.6, 1.),
+        )
+        .unwrap();
+
+        Self {
+            stamina,
+            suspicion,
+            projectile,
+            fov_meshes: HashMap::new(),
+            ray_lines: HashMap::new(),
+        }
+    }
+
+    /// Builds the meshes for the look and move rays of all guards in the world, which are not cached yet
+    pub fn prepare(
+        &mut self,
+        ctx: &mut ggez::Context,
+        quad_ctx: &mut ggez::miniquad::GraphicsContext,
+        world: &World,
+    ) {
+        for guard in world.get_all_guards() {
+            for look in guard.look_components.iter() {
+                self.fov_meshes
+                    .entry(rays_key(&look.rays))
+                    .or_insert_with(|| fov_meshes_from_rays(ctx, quad_ctx, &look.rays));
+                self.ray_lines
+                    .entry(rays_key(&look.rays))
+                    .or_insert_with(|| ray_lines_from_rays(ctx, quad_ctx, &look.rays));
+            }
+
+            let move_rays = &guard.compute_move_component.rays;
+            self.ray_lines
+                .entry(rays_key(move_rays))
+                .or_insert_with(|| ray_lines_from_rays(ctx, quad_ctx, move_rays));
+        }
+    }
+
+    /// Returns the triangles between the neighbouring rays
+    pub fn fov_meshes(&self, rays: &[glam::Vec2]) -> &[graphics::Mesh] {
+        self.fov_meshes
+            .get(&rays_key(rays))
+            .map_or(&[], |meshes| meshes.as_slice())
+    }
+
+    /// Returns a line for each ray
+    pub fn ray_lines(&self, rays: &[glam::Vec2]) -> &[graphics::Mesh] {
+        self.ray_lines
+            .get(&rays_key(rays))
+            .map_or(&[], |meshes| meshes.as_slice())
+    }
+}
+
+fn rays_key(rays: &[glam::Vec2]) -> RaysKey {
+    rays.iter()
+        .map(|ray| (ray.x.to_bits(), ray.y.to_bits()))
+        .collect()
+}
+
+fn fov_meshes_from_rays(
+    ctx: &mut ggez::Context,
+    quad_ctx: &mut ggez::miniquad::GraphicsContext,
+    rays: &[glam::Vec2],
+) -> Vec<graphics::Mesh> {
+    rays.windows(2)
+        .map(|fov_rays| {
+            let fov_points = vec![glam::vec2(0., 0.), fov_rays[0], fov_rays[1]];
+
+            graphics::Mesh::new_polygon(
+                ctx,
+                quad_ctx,
+                graphics::DrawMode::fill(),
+                &fov_points,
+                graphics::Color::from_rgba(255, 255, 255, 125),
+            )
+            .unwrap()
+        })
+        .collect::<Vec<graphics::Mesh>>()
+}
+
+fn ray_lines_from_rays(
+    ctx: &mut ggez::Context,
+    quad_ctx: &mut ggez::miniquad::GraphicsContext,
+    rays: &[glam::Vec2],
+) -> Vec<graphics::Mesh> {
+    rays.iter()
+        .map(|ray| {
+            graphics::Mesh::new_line(
+                ctx,
+                quad_ctx,
+                &[glam::vec2(0., 0.), glam::vec2(ray.x, ray.y)],
+                2.0,
+                graphics::Color::WHITE,
+            )
+            .unwrap()
+        })
+        .collect::<Vec<graphics::Mesh>>()
+}
@@ -110,7 +110,8 @@ impl MouseInputHandler {
 
         if (*game_state == GameState::Info
             || *game_state == GameState::Keybindings
-            || *game_state == GameState::Leaderboard)
+            || *game_state == GameState::Leaderboard
+            || matches!(game_state, GameState::LoadError(_)))
             && util::rect_contains_point(
                 screen_size,
                 constants::BTN_DIM_RECT,
@@ -242,6 +243,6 @@ pub fn system(game_state: &mut Game, curr_time: f32) {
     if let Some(PlayerAction::Stealth(is_stealth)) =
         game_state.mouse_input_handler.get_player_action(curr_time)
     {
-        game_state.player.set_stealth_intent(is_stealth);
+        game_state.input.stealth = is_stealth;
     }
 }
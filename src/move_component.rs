@@ -36,13 +36,4 @@ impl MoveComponent {
     pub fn set_y_dir(&mut self, y_dir: f32) {
         self.set_direction(glam::vec2(self.direction.x, y_dir));
     }
-
-    #[inline]
-    pub fn set_x_dir_normalized(&mut self, x_dir: f32) {
-        self.set_direction_normalized(glam::vec2(x_dir, self.direction.y));
-    }
-    #[inline]
-    pub fn set_y_dir_normalized(&mut self, y_dir: f32) {
-        self.set_direction_normalized(glam::vec2(self.direction.x, y_dir));
-    }
 }
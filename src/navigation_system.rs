@@ -0,0 +1,350 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
+
+use crate::constants;
+use crate::entities::wall::Wall;
+
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+
+/// Walkable grid of the level (one cell per level block), answers path queries with A*
+pub struct NavigationSystem {
+    width: usize,
+    height: usize,
+    blocked: Vec<bool>,
+}
+
+#[derive(PartialEq, Eq)]
+struct OpenNode {
+    f_cost: u32,
+    cell_idx: usize,
+}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, so that BinaryHeap pops the node with the lowest cost first
+        other
+            .f_cost
+            .cmp(&self.f_cost)
+            .then_with(|| other.cell_idx.cmp(&self.cell_idx))
+    }
+}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl NavigationSystem {
+    pub fn new() -> Self {
+        Self {
+            width: 0,
+            height: 0,
+            blocked: vec![],
+        }
+    }
+
+    /// Rebuilds the walkable grid, `size` is the level size in blocks
+    pub fn build(&mut self, walls: &[Wall], size: (u32, u32)) {
+        self.width = size.0 as usize;
+        self.height = size.1 as usize;
+        self.blocked = vec![false; self.width * self.height];
+
+        for wall in walls.iter() {
+            let rect = &wall.aabb.rect;
+            let (x0, y0) = Self::cell_of(glam::vec2(rect.left(), rect.top()));
+            let (x1, y1) = Self::cell_of(glam::vec2(rect.right() - 1., rect.bottom() - 1.));
+
+            for y in y0.max(0)..=y1.min(self.height as i32 - 1) {
+                for x in x0.max(0)..=x1.min(self.width as i32 - 1) {
+                    self.blocked[y as usize * self.width + x as usize] = true;
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn cell_of(position: glam::Vec2) -> (i32, i32) {
+        (
+            (position.x / constants::LEVEL_BLOCK_SIZE as f32).floor() as i32,
+            (position.y / constants::LEVEL_BLOCK_SIZE as f32).floor() as i32,
+        )
+    }
+
+    #[inline]
+    fn cell_center(cell_idx: usize, width: usize) -> glam::Vec2 {
+        let block_size = constants::LEVEL_BLOCK_SIZE as f32;
+
+        glam::vec2(
+            (cell_idx % width) as f32 * block_size,
+            (cell_idx / width) as f32 * block_size,
+        ) + glam::Vec2::splat(block_size / 2.)
+    }
+
+    pub fn is_walkable_cell(&self, x: i32, y: i32) -> bool {
+        x >= 0
+            && y >= 0
+            && (x as usize) < self.width
+            && (y as usize) < self.height
+            && !self.blocked[y as usize * self.width + x as usize]
+    }
+
+    #[inline]
+    pub fn is_walkable(&self, position: glam::Vec2) -> bool {
+        let (x, y) = Self::cell_of(position);
+        self.is_walkable_cell(x, y)
+    }
+
+    /// Checks whether an entity can walk in a straight line from `from` to `to`
+    pub fn has_line_of_walk(&self, from: glam::Vec2, to: glam::Vec2) -> bool {
+        let step = constants::LEVEL_BLOCK_SIZE as f32 / 4.;
+        let n_steps = ((to - from).length() / step).ceil().max(1.) as u32;
+        let clearance = constants::ENTITY_SIZE;
+
+        (0..=n_steps).all(|i| {
+            let point = from.lerp(to, i as f32 / n_steps as f32);
+
+            [
+                glam::vec2(-clearance, -clearance),
+                glam::vec2(clearance, -clearance),
+                glam::vec2(-clearance, clearance),
+                glam::vec2(clearance, clearance),
+            ]
+            .iter()
+            .all(|offset| self.is_walkable(point + *offset))
+        })
+    }
+
+    /// Returns the furthest position on the line from `from` to `to` at which the collision box of an
+    /// entity, which starts at its position, does not overlap a wall
+    pub fn find_free_position(&self, from: glam::Vec2, to: glam::Vec2) -> glam::Vec2 {
+        let step = constants::ENTITY_SIZE / 4.;
+        let n_steps = ((to - from).length() / step).ceil().max(1.) as u32;
+        let size = constants::ENTITY_SIZE - 0.01;
+
+        (1..=n_steps)
+            .map(|i| from.lerp(to, i as f32 / n_steps as f32))
+            .take_while(|position| {
+                [
+                    glam::vec2(0., 0.),
+                    glam::vec2(size, 0.),
+                    glam::vec2(0., size),
+                    glam::vec2(size, size),
+                ]
+                .iter()
+                .all(|offset| self.is_walkable(*position + *offset))
+            })
+            .last()
+            .unwrap_or(from)
+    }
+
+    /// Returns the furthest point on the line from `from` to `to` before it hits a wall
+    pub fn find_landing_point(&self, from: glam::Vec2, to: glam::Vec2) -> glam::Vec2 {
+        let step = constants::LEVEL_BLOCK_SIZE as f32 / 4.;
+        let n_steps = ((to - from).length() / step).ceil().max(1.) as u32;
+
+        (1..=n_steps)
+            .map(|i| from.lerp(to, i as f32 / n_steps as f32))
+            .take_while(|point| self.is_walkable(*point))
+            .last()
+            .unwrap_or(from)
+    }
+
+    /// Returns the points to walk through to get from `from` to `to`, the last point is `to`.
+    /// Returns `None` if `to` is not reachable.
+    pub fn find_path(&self, from: glam::Vec2, to: glam::Vec2) -> Option<Vec<glam::Vec2>> {
+        let (start_x, start_y) = Self::cell_of(from);
+        let (goal_x, goal_y) = Self::cell_of(to);
+
+        if !self.is_walkable_cell(goal_x, goal_y) {
+            return None;
+        }
+        if !self.is_walkable_cell(start_x, start_y) || self.has_line_of_walk(from, to) {
+            return Some(vec![to]);
+        }
+
+        let cell_path = self.find_cell_path((start_x, start_y), (goal_x, goal_y))?;
+
+        Some(self.smooth_path(from, to, &cell_path))
+    }
+
+    /// Returns the center of the cell within `max_steps` cells of `from`
+    /// which is the furthest away from `threat`
+    pub fn find_flee_point(
+        &self,
+        from: glam::Vec2,
+        threat: glam::Vec2,
+        max_steps: u32,
+    ) -> Option<glam::Vec2> {
+        let (start_x, start_y) = Self::cell_of(from);
+        if !self.is_walkable_cell(start_x, start_y) {
+            return None;
+        }
+
+        let start_idx = start_y as usize * self.width + start_x as usize;
+        let mut steps = vec![u32::MAX; self.blocked.len()];
+        let mut queue = VecDeque::from([start_idx]);
+        steps[start_idx] = 0;
+
+        let mut best = (
+            start_idx,
+            (Self::cell_center(start_idx, self.width) - threat).length(),
+        );
+
+        while let Some(cell_idx) = queue.pop_front() {
+            if steps[cell_idx] >= max_steps {
+                continue;
+            }
+
+            let (x, y) = (
+                (cell_idx % self.width) as i32,
+                (cell_idx / self.width) as i32,
+            );
+            for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                let (nx, ny) = (x + dx, y + dy);
+                if !self.is_walkable_cell(nx, ny) {
+                    continue;
+                }
+
+                let neighbour_idx = ny as usize * self.width + nx as usize;
+                if steps[neighbour_idx] != u32::MAX {
+                    continue;
+                }
+
+                steps[neighbour_idx] = steps[cell_idx] + 1;
+                queue.push_back(neighbour_idx);
+
+                let dist_to_threat =
+                    (Self::cell_center(neighbour_idx, self.width) - threat).length();
+                if dist_to_threat > best.1 {
+                    best = (neighbour_idx, dist_to_threat);
+                }
+            }
+        }
+
+        (best.0 != start_idx).then(|| Self::cell_center(best.0, self.width))
+    }
+
+    fn find_cell_path(&self, start: (i32, i32), goal: (i32, i32)) -> Option<Vec<usize>> {
+        let start_idx = start.1 as usize * self.width + start.0 as usize;
+        let goal_idx = goal.1 as usize * self.width + goal.0 as usize;
+
+        let heuristic = |x: i32, y: i32| {
+            let (dx, dy) = ((x - goal.0).unsigned_abs(), (y - goal.1).unsigned_abs());
+            STRAIGHT_COST * dx.max(dy) + (DIAGONAL_COST - STRAIGHT_COST) * dx.min(dy)
+        };
+
+        let mut g_costs = vec![u32::MAX; self.blocked.len()];
+        let mut came_from = vec![usize::MAX; self.blocked.len()];
+        let mut open = BinaryHeap::new();
+
+        g_costs[start_idx] = 0;
+        open.push(OpenNode {
+            f_cost: heuristic(start.0, start.1),
+            cell_idx: start_idx,
+        });
+
+        while let Some(OpenNode { f_cost, cell_idx }) = open.pop() {
+            if cell_idx == goal_idx {
+                let mut path = vec![goal_idx];
+                let mut curr_idx = goal_idx;
+                while came_from[curr_idx] != usize::MAX {
+                    curr_idx = came_from[curr_idx];
+                    path.push(curr_idx);
+                }
+                path.reverse();
+
+                return Some(path);
+            }
+
+            let (x, y) = (
+                (cell_idx % self.width) as i32,
+                (cell_idx / self.width) as i32,
+            );
+            let g_cost = g_costs[cell_idx];
+            if f_cost > g_cost + heuristic(x, y) {
+                continue; // Stale entry, a cheaper one was already expanded
+            }
+
+            for (dx, dy) in [
+                (1, 0),
+                (-1, 0),
+                (0, 1),
+                (0, -1),
+                (1, 1),
+                (1, -1),
+                (-1, 1),
+                (-1, -1),
+            ] {
+                let (nx, ny) = (x + dx, y + dy);
+                if !self.is_walkable_cell(nx, ny) {
+                    continue;
+                }
+
+                let is_diagonal = dx != 0 && dy != 0;
+                // Do not cut corners of walls
+                if is_diagonal
+                    && (!self.is_walkable_cell(x + dx, y) || !self.is_walkable_cell(x, y + dy))
+                {
+                    continue;
+                }
+
+                let neighbour_idx = ny as usize * self.width + nx as usize;
+                let new_g_cost = g_cost
+                    + if is_diagonal {
+                        DIAGONAL_COST
+                    } else {
+                        STRAIGHT_COST
+                    };
+
+                if new_g_cost < g_costs[neighbour_idx] {
+                    g_costs[neighbour_idx] = new_g_cost;
+                    came_from[neighbour_idx] = cell_idx;
+                    open.push(OpenNode {
+                        f_cost: new_g_cost + heuristic(nx, ny),
+                        cell_idx: neighbour_idx,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Skips every cell center that can be bypassed by walking in a straight line
+    fn smooth_path(
+        &self,
+        from: glam::Vec2,
+        to: glam::Vec2,
+        cell_path: &[usize],
+    ) -> Vec<glam::Vec2> {
+        let mut points = cell_path
+            .iter()
+            .map(|cell_idx| Self::cell_center(*cell_idx, self.width))
+            .collect::<Vec<glam::Vec2>>();
+
+        // Start and goal cells are replaced with the exact positions
+        points.remove(0);
+        points.pop();
+        points.push(to);
+
+        let mut smoothed = vec![];
+        let mut anchor = from;
+        for i in 0..points.len() - 1 {
+            if !self.has_line_of_walk(anchor, points[i + 1]) {
+                smoothed.push(points[i]);
+                anchor = points[i];
+            }
+        }
+        smoothed.push(to);
+
+        smoothed
+    }
+}
+
+impl Default for NavigationSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,151 +1,249 @@
-use serde::{Deserialize, Serialize};
-use std::fmt;
+use crate::constants;
+use crate::leaderboard_backend::{LeaderboardBackend, LeaderboardError, PlayerEntry, Spawner};
+use crate::util;
+
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// State of the leaderboard of the selected level
+pub enum LeaderboardState {
+    Idle,
+    InFlight,
+    Ready(Vec<PlayerEntry>),
+    Failed(LeaderboardError),
+}
+
+/// Result of a spawned request, tagged with the ID of the request
+enum NetworkMessage {
+    Leaderboard(u64, Result<Vec<PlayerEntry>, LeaderboardError>),
+    Submit(u64, Result<(), LeaderboardError>),
+}
+
+/// Submission which has not reached the leaderboard yet
+struct PendingSubmit {
+    id: u64,
+    entry: PlayerEntry,
+    attempts: u32,
+    next_attempt: Instant,
+    is_in_flight: bool,
+    is_parked: bool, // Refused because of the settings, it is sent again after a restart
+}
 
+/// Spawns the backend requests, their results are sent back through a channel
+/// which is drained once per frame, so the game never waits on the network
 pub struct NetworkSystem {
-    do_request_leaderboard: bool,
-    submit_player_entry: Option<(String, f32)>,
-    leaderboard_join_handle: Option<tokio::task::JoinHandle<Result<String, reqwest::Error>>>,
-    submit_join_handle: Option<tokio::task::JoinHandle<()>>,
+    backend: Box<dyn LeaderboardBackend>,
+    spawner: Box<dyn Spawner>,
+    sender: mpsc::Sender<NetworkMessage>,
+    receiver: mpsc::Receiver<NetworkMessage>,
+    leaderboard: LeaderboardState,
+    request_level: Option<(String, String)>,
+    request_id: u64,
+    outbox: Vec<PendingSubmit>,
+    outbox_filename: String,
+    next_submit_id: u64,
+    last_submit_error: Option<LeaderboardError>,
 }
 
 impl NetworkSystem {
-    pub fn new() -> Self {
-        NetworkSystem {
-            do_request_leaderboard: false,
-            submit_player_entry: None,
-            leaderboard_join_handle: None,
-            submit_join_handle: None,
-        }
+    /// Submissions which were not sent before the game was closed are loaded from the outbox file
+    pub fn new(backend: Box<dyn LeaderboardBackend>, spawner: Box<dyn Spawner>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        let outbox_filename = util::home_filename(constants::OUTBOX_FILENAME);
+        let entries: Vec<PlayerEntry> = std::fs::read_to_string(&outbox_filename)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+
+        let mut network_system = NetworkSystem {
+            backend,
+            spawner,
+            sender,
+            receiver,
+            leaderboard: LeaderboardState::Idle,
+            request_level: None,
+            request_id: 0,
+            outbox: Vec::new(),
+            outbox_filename,
+            next_submit_id: 0,
+            last_submit_error: None,
+        };
+        network_system.push_outbox(entries);
+
+        network_system
     }
 
-    pub fn do_request_leaderboard(&mut self) {
-        self.do_request_leaderboard = true;
+    /// Requests the leaderboard of the level, it is requested after the submits in flight are done
+    pub fn do_request_leaderboard(&mut self, level_id: &str, level_hash: &str) {
+        self.request_level = Some((level_id.to_owned(), level_hash.to_owned()));
+        self.request_id += 1;
+        self.leaderboard = LeaderboardState::InFlight;
     }
 
-    pub fn do_submit_time_and_reqeust_leaderboard(&mut self, username: String, time: f32) {
-        self.submit_player_entry = Some((username, time));
+    /// Adds the entries to the outbox, they are kept until the backend accepts them
+    pub fn do_submit_times(&mut self, entries: Vec<PlayerEntry>) {
+        self.push_outbox(entries);
+        self.save_outbox();
     }
 
-    pub fn request_in_progress(&self) -> bool {
-        self.do_request_leaderboard
+    /// Drops the leaderboard of another campaign, a request in flight is ignored when it arrives
+    pub fn reset_leaderboard(&mut self) {
+        self.request_level = None;
+        self.request_id += 1;
+        self.leaderboard = LeaderboardState::Idle;
     }
 
-    pub async fn tick(&mut self) {
-        if self.do_request_leaderboard {
-            self.leaderboard_join_handle = Some(self.request_leaderboard().await);
-
-            self.do_request_leaderboard = false;
-        }
-
-        if let Some((username, time)) = &self.submit_player_entry {
-            let new_player_entry = PlayerEntry {
-                username: username.to_string(),
-                time: *time,
-            };
+    pub fn leaderboard(&self) -> &LeaderboardState {
+        &self.leaderboard
+    }
 
-            self.submit_join_handle = Some(self.submit_time(new_player_entry).await);
-            self.submit_player_entry = None;
-        }
+    /// Number of submissions which are waiting for a retry or are in flight
+    pub fn pending_submits(&self) -> usize {
+        self.outbox.len()
+    }
 
-        if let Some(submit_handle) = &self.submit_join_handle {
-            if submit_handle.is_finished() {
-                self.leaderboard_join_handle = Some(self.request_leaderboard().await);
+    pub fn last_submit_error(&self) -> Option<&LeaderboardError> {
+        self.last_submit_error.as_ref()
+    }
 
-                self.do_request_leaderboard = false;
-                self.submit_join_handle = None;
+    /// Handles the finished requests and spawns the due ones, it never blocks
+    pub fn update(&mut self) {
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                NetworkMessage::Leaderboard(request_id, result) => {
+                    // A newer request replaced this one
+                    if request_id != self.request_id {
+                        continue;
+                    }
+
+                    self.leaderboard = match result {
+                        Ok(mut entries) => {
+                            entries.sort_by(|e1, e2| e1.time.total_cmp(&e2.time));
+                            LeaderboardState::Ready(entries)
+                        }
+                        Err(err) => LeaderboardState::Failed(err),
+                    };
+                }
+                NetworkMessage::Submit(submit_id, result) => self.handle_submit(submit_id, result),
             }
         }
-    }
-
-    async fn request_leaderboard(&self) -> tokio::task::JoinHandle<Result<String, reqwest::Error>> {
-        let api_key = "bzkOp6qOAmopVFZhty69SSyB7OqTRDu1IqTs7TlLuBDeja7cDPGSaB0gL6c1IpBK";
-        let client = reqwest::Client::new();
-
-        tokio::spawn(async move {
-            let response = client
-                .get("https://data.mongodb-api.com/app/data-mjiob/endpoint/leaderboard")
-                .header("api-key", api_key)
-                .send()
-                .await;
-
-            match response {
-                Ok(res) => {
-                    let response_str = res.text().await.unwrap();
 
-                    let leaderboard: Vec<PlayerEntry> =
-                        serde_json::from_str(&response_str).unwrap();
+        // One submit at a time, the backend may not handle concurrent writes
+        let now = Instant::now();
+        let is_submit_in_flight = self.outbox.iter().any(|pending| pending.is_in_flight);
+        let next_submit = self
+            .outbox
+            .iter_mut()
+            .find(|pending| !pending.is_parked && pending.next_attempt <= now);
+        if let (false, Some(pending)) = (is_submit_in_flight, next_submit) {
+            pending.is_in_flight = true;
+            let (submit_id, future) = (pending.id, self.backend.submit_time(pending.entry.clone()));
+
+            let sender = self.sender.clone();
+            self.spawner.spawn(Box::pin(async move {
+                let _ = sender.send(NetworkMessage::Submit(submit_id, future.await));
+            }));
+        }
 
-                    let leaderboard_str: String = leaderboard
-                        .iter()
-                        .map(|entry| entry.to_string() + "\n\n")
-                        .collect();
+        let is_submit_in_flight = self.outbox.iter().any(|pending| pending.is_in_flight);
+        if !is_submit_in_flight {
+            if let Some((level_id, level_hash)) = self.request_level.take() {
+                let request_id = self.request_id;
+                let future = self.backend.request_leaderboard(&level_id, &level_hash);
 
-                    Ok(leaderboard_str)
-                }
-                Err(err) => Err(err),
+                let sender = self.sender.clone();
+                self.spawner.spawn(Box::pin(async move {
+                    let _ = sender.send(NetworkMessage::Leaderboard(request_id, future.await));
+                }));
             }
-        })
+        }
     }
 
-    async fn submit_time(&self, new_entry: PlayerEntry) -> tokio::task::JoinHandle<()> {
-        let api_key = "bzkOp6qOAmopVFZhty69SSyB7OqTRDu1IqTs7TlLuBDeja7cDPGSaB0gL6c1IpBK";
-        let client = reqwest::Client::new();
-
-        tokio::spawn(async move {
-            let response = client
-                .put("https://data.mongodb-api.com/app/data-mjiob/endpoint/put")
-                .header("api-key", api_key)
-                .json(&new_entry)
-                .send()
-                .await;
-
-            match response {
-                Ok(_) => println!("Submit successful!"),
-                Err(err) => println!("Error when posting submit: {:?}", err),
+    fn handle_submit(&mut self, submit_id: u64, result: Result<(), LeaderboardError>) {
+        let Some(idx) = self
+            .outbox
+            .iter()
+            .position(|pending| pending.id == submit_id)
+        else {
+            return;
+        };
+
+        match result {
+            Ok(()) => {
+                println!("Submit successful!");
+                self.last_submit_error = None;
+                self.outbox.remove(idx);
+                self.save_outbox();
+            }
+            Err(err) if err.is_rejection() => {
+                println!("Submit rejected: {}", err);
+                self.last_submit_error = Some(err);
+                self.outbox.remove(idx);
+                self.save_outbox();
+            }
+            Err(err) if err.is_config_error() => {
+                // The outbox is kept, it is sent once the game restarts with fixed settings
+                println!("Submit refused, check the leaderboard settings: {}", err);
+                let pending = &mut self.outbox[idx];
+                pending.is_in_flight = false;
+                pending.is_parked = true;
+                self.last_submit_error = Some(err);
+            }
+            Err(err) => {
+                let pending = &mut self.outbox[idx];
+                pending.is_in_flight = false;
+                pending.attempts += 1;
+
+                let delay = (constants::SUBMIT_RETRY_DELAY
+                    * 2f32.powi(pending.attempts as i32 - 1))
+                .min(constants::SUBMIT_MAX_RETRY_DELAY);
+                pending.next_attempt = Instant::now() + Duration::from_secs_f32(delay);
+
+                println!("Error when posting submit, retrying in {}s: {}", delay, err);
+                self.last_submit_error = Some(err);
             }
-        })
-    }
-
-    pub fn leaderboard_ready(&self) -> bool {
-        if let Some(j_handle) = &self.leaderboard_join_handle {
-            return j_handle.is_finished();
         }
-
-        false
     }
 
-    pub fn submit_finished(&self) -> bool {
-        if let Some(j_handle) = &self.submit_join_handle {
-            return j_handle.is_finished();
+    fn push_outbox(&mut self, entries: Vec<PlayerEntry>) {
+        for entry in entries {
+            self.outbox.push(PendingSubmit {
+                id: self.next_submit_id,
+                entry,
+                attempts: 0,
+                next_attempt: Instant::now(),
+                is_in_flight: false,
+                is_parked: false,
+            });
+            self.next_submit_id += 1;
         }
-
-        true
     }
 
-    #[tokio::main]
-    pub async fn get_response(self) -> String {
-        if let Some(j_handle) = self.leaderboard_join_handle {
-            return match j_handle.await {
-                Ok(res) => match res {
-                    Ok(str) => str,
-                    Err(err) => format!("Error when getting leaderboard\n\n{}", err),
-                },
-                Err(err) => format!("Join error: {:?}", err),
-            };
+    fn save_outbox(&self) {
+        let entries = self
+            .outbox
+            .iter()
+            .map(|pending| &pending.entry)
+            .collect::<Vec<&PlayerEntry>>();
+
+        let result = if entries.is_empty() {
+            match std::fs::remove_file(&self.outbox_filename) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+                _ => Ok(()),
+            }
+        } else {
+            std::fs::write(
+                &self.outbox_filename,
+                serde_json::to_string_pretty(&entries).unwrap(),
+            )
+        };
+
+        if let Err(err) = result {
+            println!(
+                "could not save the outbox {}: {}",
+                self.outbox_filename, err
+            );
         }
-
-        "Warning: thread has not completed yet...".to_owned()
-    }
-}
-
-#[derive(Serialize, Deserialize)]
-struct PlayerEntry {
-    username: String,
-    time: f32,
-}
-
-impl fmt::Display for PlayerEntry {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}: {}", self.username, self.time)
     }
 }
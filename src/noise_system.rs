@@ -0,0 +1,48 @@
+/// Sound which guards within the radius can hear
+pub struct NoiseEvent {
+    pub position: glam::Vec2,
+    pub radius: f32,
+}
+
+impl NoiseEvent {
+    #[inline]
+    pub fn is_heard_at(&self, position: glam::Vec2, size: f32) -> bool {
+        (self.position - position).length_squared() <= (self.radius + size).powi(2)
+    }
+}
+
+/// Collects noise events during a frame, the guards consume them
+pub struct NoiseSystem {
+    pub noise_events: Vec<NoiseEvent>,
+}
+
+impl NoiseSystem {
+    pub fn new() -> Self {
+        Self {
+            noise_events: vec![],
+        }
+    }
+
+    #[inline]
+    pub fn emit(&mut self, position: glam::Vec2, radius: f32) {
+        if radius > 0. {
+            self.noise_events.push(NoiseEvent { position, radius });
+        }
+    }
+
+    #[inline]
+    pub fn take_events(&mut self) -> Vec<NoiseEvent> {
+        std::mem::take(&mut self.noise_events)
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.noise_events.clear();
+    }
+}
+
+impl Default for NoiseSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
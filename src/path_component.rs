@@ -0,0 +1,48 @@
+pub struct PathComponent {
+    pub points: Vec<glam::Vec2>,
+    pub point_idx: usize,
+    pub destination: Option<glam::Vec2>,
+}
+
+impl PathComponent {
+    pub fn new() -> Self {
+        Self {
+            points: vec![],
+            point_idx: 0,
+            destination: None,
+        }
+    }
+
+    #[inline]
+    pub fn set_path(&mut self, points: Vec<glam::Vec2>, destination: glam::Vec2) {
+        self.points = points;
+        self.point_idx = 0;
+        self.destination = Some(destination);
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.point_idx = 0;
+        self.destination = None;
+    }
+
+    /// Skips the points which are within `reach_radius` and returns the next point to walk to
+    pub fn next_point(&mut self, position: glam::Vec2, reach_radius: f32) -> Option<glam::Vec2> {
+        while let Some(point) = self.points.get(self.point_idx) {
+            if (*point - position).length_squared() > reach_radius.powi(2) {
+                return Some(*point);
+            }
+
+            self.point_idx += 1;
+        }
+
+        None
+    }
+}
+
+impl Default for PathComponent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
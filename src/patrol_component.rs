@@ -0,0 +1,117 @@
+use crate::constants;
+use crate::util;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum PatrolMode {
+    /// After the last waypoint, continue with the first one
+    Loop,
+    /// After the last waypoint, walk the route backwards
+    PingPong,
+}
+
+#[derive(Clone)]
+pub struct Waypoint {
+    /// Level block (column, row) of the waypoint
+    pub cell: (u32, u32),
+    pub wait_time: f32,
+    /// Angle in degrees, 0 is right and 90 is up
+    pub look_angle: Option<f32>,
+}
+
+impl Waypoint {
+    pub fn new(cell: (u32, u32), wait_time: f32, look_angle: Option<f32>) -> Self {
+        Self {
+            cell,
+            wait_time,
+            look_angle,
+        }
+    }
+
+    /// Center of the waypoint's level block
+    pub fn position(&self) -> glam::Vec2 {
+        glam::vec2(
+            (self.cell.0 * constants::LEVEL_BLOCK_SIZE) as f32,
+            (self.cell.1 * constants::LEVEL_BLOCK_SIZE) as f32,
+        ) + glam::Vec2::splat(constants::LEVEL_BLOCK_SIZE as f32 / 2.)
+    }
+
+    pub fn look_dir(&self) -> Option<glam::Vec2> {
+        self.look_angle
+            .map(|angle| util::vec_from_angle(angle.to_radians()))
+    }
+}
+
+#[derive(Clone)]
+pub struct PatrolComponent {
+    pub waypoints: Vec<Waypoint>,
+    pub mode: PatrolMode,
+    pub waypoint_idx: usize,
+    is_reversed: bool,
+    /// Remaining wait time while the guard stands at the current waypoint
+    pub wait_timer: Option<f32>,
+}
+
+impl PatrolComponent {
+    pub fn new(waypoints: Vec<Waypoint>, mode: PatrolMode) -> Self {
+        assert!(!waypoints.is_empty(), "patrol route has no waypoints");
+
+        Self {
+            waypoints,
+            mode,
+            waypoint_idx: 0,
+            is_reversed: false,
+            wait_timer: None,
+        }
+    }
+
+    #[inline]
+    pub fn current_waypoint(&self) -> &Waypoint {
+        &self.waypoints[self.waypoint_idx]
+    }
+
+    #[inline]
+    pub fn is_waiting(&self) -> bool {
+        self.wait_timer.is_some()
+    }
+
+    #[inline]
+    pub fn start_waiting(&mut self) {
+        self.wait_timer = Some(self.current_waypoint().wait_time);
+    }
+
+    /// Counts down the wait timer and moves on to the next waypoint once it runs out
+    pub fn update_wait(&mut self, dt: f32) {
+        if let Some(wait_timer) = self.wait_timer {
+            if wait_timer <= 0. {
+                self.wait_timer = None;
+                self.advance();
+            } else {
+                self.wait_timer = Some(wait_timer - dt);
+            }
+        }
+    }
+
+    pub fn advance(&mut self) {
+        let n_waypoints = self.waypoints.len();
+        if n_waypoints == 1 {
+            return;
+        }
+
+        match self.mode {
+            PatrolMode::Loop => self.waypoint_idx = (self.waypoint_idx + 1) % n_waypoints,
+            PatrolMode::PingPong => {
+                if self.is_reversed && self.waypoint_idx == 0 {
+                    self.is_reversed = false;
+                } else if !self.is_reversed && self.waypoint_idx == n_waypoints - 1 {
+                    self.is_reversed = true;
+                }
+
+                if self.is_reversed {
+                    self.waypoint_idx -= 1;
+                } else {
+                    self.waypoint_idx += 1;
+                }
+            }
+        }
+    }
+}
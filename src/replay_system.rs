@@ -0,0 +1,217 @@
+use crate::constants;
+use crate::entities::player::MoveType;
+use crate::util;
+use crate::world::PlayerInput;
+
+use serde::{Deserialize, Serialize};
+
+/// Input of a tick, only stored when it differs from the input of the previous tick
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct InputRecord {
+    pub tick: u32,
+    pub move_dir: (f32, f32),
+    pub move_type: MoveType,
+    pub stealth: bool,
+    pub teleport: bool,
+    pub attack: bool,
+    pub throw_target: Option<(f32, f32)>,
+}
+
+impl InputRecord {
+    fn new(tick: u32, input: &PlayerInput) -> Self {
+        Self {
+            tick,
+            move_dir: input.move_dir.into(),
+            move_type: input.move_type,
+            stealth: input.stealth,
+            teleport: input.teleport,
+            attack: input.attack,
+            throw_target: input.throw_target.map(|target| target.into()),
+        }
+    }
+
+    fn same_input(&self, other: &InputRecord) -> bool {
+        Self {
+            tick: 0,
+            ..self.clone()
+        } == Self {
+            tick: 0,
+            ..other.clone()
+        }
+    }
+
+    fn to_input(&self) -> PlayerInput {
+        PlayerInput {
+            move_dir: self.move_dir.into(),
+            move_type: self.move_type,
+            stealth: self.stealth,
+            teleport: self.teleport,
+            attack: self.attack,
+            throw_target: self.throw_target.map(|target| target.into()),
+        }
+    }
+}
+
+/// Inputs of a single level attempt, the seed and the inputs reproduce the attempt exactly
+#[derive(Serialize, Deserialize)]
+pub struct Replay {
+    pub campaign_id: String,
+    pub level_index: usize,
+    pub seed: u64,
+    pub ticks: u32,
+    pub inputs: Vec<InputRecord>,
+}
+
+impl Replay {
+    pub fn new(campaign_id: &str, level_index: usize, seed: u64) -> Self {
+        Self {
+            campaign_id: campaign_id.to_owned(),
+            level_index,
+            seed,
+            ticks: 0,
+            inputs: vec![],
+        }
+    }
+
+    pub fn load(filename: &str) -> Option<Self> {
+        let data = std::fs::read_to_string(filename).ok()?;
+        serde_json::from_str(&data).ok()
+    }
+
+    pub fn save(&self, filename: &str) -> std::io::Result<()> {
+        std::fs::write(filename, serde_json::to_string(self)?)
+    }
+
+    pub fn record(&mut self, tick: u32, input: &PlayerInput) {
+        let record = InputRecord::new(tick, input);
+
+        if self
+            .inputs
+            .last()
+            .is_none_or(|last| !last.same_input(&record))
+        {
+            self.inputs.push(record);
+        }
+
+        self.ticks = tick + 1;
+    }
+
+    /// Input which was active in the given tick
+    pub fn input_at(&self, tick: u32) -> PlayerInput {
+        let idx = self.inputs.partition_point(|record| record.tick <= tick);
+
+        idx.checked_sub(1)
+            .map_or_else(PlayerInput::new, |idx| self.inputs[idx].to_input())
+    }
+}
+
+/// Records every level attempt and plays back a loaded replay instead of the live input
+pub struct ReplaySystem {
+    recording: Option<Replay>,
+    playback: Option<Replay>,
+    pending_playback: Option<Replay>,
+}
+
+impl ReplaySystem {
+    pub fn new() -> Self {
+        Self {
+            recording: None,
+            playback: None,
+            pending_playback: None,
+        }
+    }
+
+    /// Queues the replay, it is played back in the next attempt of its level
+    pub fn queue_playback(&mut self, replay: Replay) {
+        self.pending_playback = Some(replay);
+    }
+
+    pub fn cancel_playback(&mut self) {
+        self.pending_playback = None;
+    }
+
+    /// Campaign and level of the queued replay which still has to be loaded
+    pub fn pending_level(&self) -> Option<(&str, usize)> {
+        self.pending_playback
+            .as_ref()
+            .map(|replay| (replay.campaign_id.as_str(), replay.level_index))
+    }
+
+    /// Ends the previous attempt if it is still running and returns the seed of the new one
+    pub fn start_attempt(&mut self, campaign_id: &str, level_index: usize, seed: u64) -> u64 {
+        self.finish_attempt();
+        self.playback = None;
+
+        if let Some(replay) = self.pending_playback.take() {
+            if replay.campaign_id == campaign_id && replay.level_index == level_index {
+                let seed = replay.seed;
+                self.playback = Some(replay);
+
+                return seed;
+            }
+        }
+
+        self.recording = Some(Replay::new(campaign_id, level_index, seed));
+
+        seed
+    }
+
+    /// Returns the input for the tick, the recorded one while playing back and the live one otherwise
+    pub fn tick_input(&mut self, tick: u32, input: &PlayerInput) -> PlayerInput {
+        if let Some(playback) = &self.playback {
+            return playback.input_at(tick);
+        }
+
+        if let Some(recording) = &mut self.recording {
+            recording.record(tick, input);
+        }
+
+        input.clone()
+    }
+
+    pub fn is_playback_finished(&self, tick: u32) -> bool {
+        self.playback
+            .as_ref()
+            .is_some_and(|playback| tick >= playback.ticks)
+    }
+
+    pub fn stop_playback(&mut self) {
+        self.playback = None;
+    }
+
+    /// Saves the recording of the attempt, it is called when the player exits or is detected
+    pub fn finish_attempt(&mut self) {
+        if let Some(recording) = self.take_recording() {
+            if recording.ticks > 0 {
+                let _ = recording.save(&replay_filename(
+                    &recording.campaign_id,
+                    recording.level_index,
+                    recording.seed,
+                ));
+            }
+        }
+    }
+
+    /// Ends the recording without saving it
+    pub fn take_recording(&mut self) -> Option<Replay> {
+        self.recording.take()
+    }
+}
+
+impl Default for ReplaySystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Every attempt is kept, the seed tells the attempts of a level apart and is also the replay ID of
+/// the leaderboard entries
+pub fn replay_filename(campaign_id: &str, level_index: usize, seed: u64) -> String {
+    util::home_filename(&format!(
+        "{}_{}_{}_{:016x}.json",
+        constants::REPLAY_FILENAME_PREFIX,
+        campaign_id,
+        level_index,
+        seed
+    ))
+}
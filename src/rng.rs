@@ -0,0 +1,52 @@
+const MULTIPLIER: u64 = 6364136223846793005;
+const INCREMENT: u64 = 1442695040888963407;
+
+/// Random number generator of the simulation, the same seed always produces the same numbers.
+/// It is the PCG32 generator, the same one `quad_rand` uses for its global state.
+pub struct GameRng {
+    state: u64,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Self { state: 0 };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+
+        rng
+    }
+
+    /// Seed from the current time, for attempts which do not need to be reproduced
+    pub fn time_seed() -> u64 {
+        (ggez::miniquad::date::now() * 1000.) as u64
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old_state = self.state;
+        self.state = old_state.wrapping_mul(MULTIPLIER).wrapping_add(INCREMENT);
+
+        let xorshifted = (((old_state >> 18) ^ old_state) >> 27) as u32;
+        let rot = (old_state >> 59) as u32;
+        xorshifted.rotate_right(rot)
+    }
+
+    /// Returns a number in `[low, high)`
+    pub fn gen_range(&mut self, low: f32, high: f32) -> f32 {
+        let r = self.next_u32() as f64 / (u32::MAX as f64 + 1.);
+        let value = (low as f64 + (high as f64 - low as f64) * r) as f32;
+
+        // The rounding to f32 can reach `high`
+        value.min(high.next_down()).max(low)
+    }
+
+    /// Returns an index in `[0, len)`
+    pub fn gen_index(&mut self, len: usize) -> usize {
+        ((self.next_u32() as u64 * len as u64) >> 32) as usize
+    }
+
+    #[inline]
+    pub fn gen_bool(&mut self) -> bool {
+        self.next_u32() & 1 == 1
+    }
+}
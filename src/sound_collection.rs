@@ -1,3 +1,5 @@
+use crate::load_error::LoadError;
+
 use ggez::{audio, GameResult};
 
 pub struct SoundCollection {
@@ -27,3 +29,10 @@ impl SoundCollection {
         self.sounds[index].set_volume(ctx, volume)
     }
 }
+
+pub fn load_sound(ctx: &mut ggez::Context, filename: &str) -> Result<audio::Source, LoadError> {
+    audio::Source::new(ctx, filename).map_err(|err| LoadError::InvalidSound {
+        filename: filename.to_owned(),
+        reason: err.to_string(),
+    })
+}
@@ -0,0 +1,526 @@
+use crate::campaign::Campaign;
+use crate::constants;
+use crate::util;
+
+use std::fmt::Write;
+
+/// Splits in the LiveSplit `.lss` format, one segment per timed level of a campaign
+pub struct Splits {
+    pub game_name: String,
+    pub category_name: String,
+    pub attempt_count: u32,
+    pub attempts: Vec<Attempt>,
+    pub segments: Vec<Segment>,
+}
+
+pub struct Attempt {
+    pub id: u32,
+    pub started: String,
+    pub ended: String,
+    pub time: Option<f32>, // None if the run was reset
+}
+
+pub struct Segment {
+    pub name: String,
+    pub pb_split: Option<f32>, // Time from the start of the run to the end of the segment
+    pub best_segment: Option<f32>,
+    pub history: Vec<(u32, f32)>, // Segment time of each attempt which reached the segment
+}
+
+impl Splits {
+    pub fn new(segment_count: usize) -> Self {
+        Self {
+            game_name: constants::SPLITS_GAME_NAME.to_owned(),
+            category_name: constants::SPLITS_CATEGORY_NAME.to_owned(),
+            attempt_count: 0,
+            attempts: vec![],
+            segments: (1..=segment_count)
+                .map(|n| Segment {
+                    name: format!("Level {}", n),
+                    pb_split: None,
+                    best_segment: None,
+                    history: vec![],
+                })
+                .collect(),
+        }
+    }
+
+    pub fn load(filename: &str, segment_count: usize) -> Result<Self, String> {
+        let data = std::fs::read_to_string(filename).map_err(|err| err.to_string())?;
+        Self::from_lss(&data, segment_count)
+    }
+
+    pub fn save(&self, filename: &str) -> std::io::Result<()> {
+        std::fs::write(filename, self.to_lss())
+    }
+
+    pub fn from_lss(data: &str, segment_count: usize) -> Result<Self, String> {
+        let run = tag_content(data, "Run").ok_or("missing <Run> element")?;
+
+        let mut splits = Self::new(segment_count);
+        splits.game_name = tag_content(run, "GameName").map_or(splits.game_name, xml_unescape);
+        splits.category_name =
+            tag_content(run, "CategoryName").map_or(splits.category_name, xml_unescape);
+        splits.attempt_count = tag_content(run, "AttemptCount")
+            .map(|count| count.trim().parse().map_err(|_| "invalid <AttemptCount>"))
+            .transpose()?
+            .unwrap_or(0);
+
+        if let Some(history) = tag_content(run, "AttemptHistory") {
+            splits.attempts = elements(history, "Attempt")
+                .map(|(attrs, content)| {
+                    Ok(Attempt {
+                        id: attribute(attrs, "id")
+                            .and_then(|id| id.parse().ok())
+                            .ok_or("invalid attempt id")?,
+                        started: attribute(attrs, "started").unwrap_or_default().to_owned(),
+                        ended: attribute(attrs, "ended").unwrap_or_default().to_owned(),
+                        time: real_time(content)?,
+                    })
+                })
+                .collect::<Result<Vec<Attempt>, String>>()?;
+        }
+
+        let segments = tag_content(run, "Segments").ok_or("missing <Segments> element")?;
+        let imported = elements(segments, "Segment")
+            .map(|(_, content)| {
+                let pb_split = elements(
+                    tag_content(content, "SplitTimes").unwrap_or(""),
+                    "SplitTime",
+                )
+                .find(|(attrs, _)| attribute(attrs, "name") == Some("Personal Best"))
+                .map(|(_, split_time)| real_time(split_time))
+                .transpose()?
+                .flatten();
+
+                let best_segment = tag_content(content, "BestSegmentTime")
+                    .map(real_time)
+                    .transpose()?
+                    .flatten();
+
+                let history =
+                    elements(tag_content(content, "SegmentHistory").unwrap_or(""), "Time")
+                        .filter_map(|(attrs, time)| {
+                            let id = attribute(attrs, "id")?.parse().ok()?;
+                            real_time(time).ok().flatten().map(|time| (id, time))
+                        })
+                        .collect();
+
+                Ok(Segment {
+                    name: tag_content(content, "Name").map_or(String::new(), xml_unescape),
+                    pb_split,
+                    best_segment,
+                    history,
+                })
+            })
+            .collect::<Result<Vec<Segment>, String>>()?;
+
+        // Segments which do not match a level of the game are dropped
+        splits
+            .segments
+            .iter_mut()
+            .zip(imported)
+            .for_each(|(segment, imported)| *segment = imported);
+
+        Ok(splits)
+    }
+
+    pub fn to_lss(&self) -> String {
+        let mut lss = String::new();
+
+        lss += "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n";
+        lss += "<Run version=\"1.7.0\">\n";
+        lss += "  <GameIcon />\n";
+        let _ = writeln!(
+            lss,
+            "  <GameName>{}</GameName>",
+            xml_escape(&self.game_name)
+        );
+        let _ = writeln!(
+            lss,
+            "  <CategoryName>{}</CategoryName>",
+            xml_escape(&self.category_name)
+        );
+        lss += "  <Offset>00:00:00</Offset>\n";
+        let _ = writeln!(lss, "  <AttemptCount>{}</AttemptCount>", self.attempt_count);
+
+        lss += "  <AttemptHistory>\n";
+        for attempt in self.attempts.iter() {
+            let _ = write!(
+                lss,
+                "    <Attempt id=\"{}\" started=\"{}\" isStartedSynced=\"True\" ended=\"{}\" isEndedSynced=\"True\">",
+                attempt.id, attempt.started, attempt.ended
+            );
+            if let Some(time) = attempt.time {
+                let _ = write!(
+                    lss,
+                    "\n      <RealTime>{}</RealTime>\n    ",
+                    format_time(time)
+                );
+            }
+            lss += "</Attempt>\n";
+        }
+        lss += "  </AttemptHistory>\n";
+
+        lss += "  <Segments>\n";
+        for segment in self.segments.iter() {
+            lss += "    <Segment>\n";
+            let _ = writeln!(lss, "      <Name>{}</Name>", xml_escape(&segment.name));
+            lss += "      <Icon />\n";
+
+            lss += "      <SplitTimes>\n";
+            lss += "        <SplitTime name=\"Personal Best\"";
+            match segment.pb_split {
+                Some(time) => {
+                    let _ = write!(
+                        lss,
+                        ">\n          <RealTime>{}</RealTime>\n        </SplitTime>\n",
+                        format_time(time)
+                    );
+                }
+                None => lss += " />\n",
+            }
+            lss += "      </SplitTimes>\n";
+
+            match segment.best_segment {
+                Some(time) => {
+                    let _ = writeln!(
+                        lss,
+                        "      <BestSegmentTime>\n        <RealTime>{}</RealTime>\n      </BestSegmentTime>",
+                        format_time(time)
+                    );
+                }
+                None => lss += "      <BestSegmentTime />\n",
+            }
+
+            lss += "      <SegmentHistory>\n";
+            for (id, time) in segment.history.iter() {
+                let _ = writeln!(
+                    lss,
+                    "        <Time id=\"{}\">\n          <RealTime>{}</RealTime>\n        </Time>",
+                    id,
+                    format_time(*time)
+                );
+            }
+            lss += "      </SegmentHistory>\n";
+            lss += "    </Segment>\n";
+        }
+        lss += "  </Segments>\n";
+
+        lss += "  <AutoSplitterSettings />\n";
+        lss += "</Run>\n";
+
+        lss
+    }
+}
+
+/// Tracks the splits of the current run and compares them against the personal best
+pub struct SpeedrunSystem {
+    pub splits: Splits,
+    campaign_id: String,
+    tutorial_count: usize,
+    run_started: Option<u64>,
+    segment_times: Vec<f32>,
+}
+
+impl SpeedrunSystem {
+    pub fn new(splits: Splits, campaign: &Campaign) -> Self {
+        Self {
+            splits,
+            campaign_id: campaign.id.clone(),
+            tutorial_count: campaign.tutorial_count(),
+            run_started: None,
+            segment_times: vec![],
+        }
+    }
+
+    /// Loads the saved splits of the campaign
+    pub fn load(campaign: &Campaign) -> Self {
+        let segment_count = campaign.level_count() - campaign.tutorial_count();
+        let splits = Splits::load(&splits_filename(&campaign.id), segment_count)
+            .unwrap_or_else(|_| Splits::new(segment_count));
+
+        Self::new(splits, campaign)
+    }
+
+    /// Ends the segment of the level, the run starts with the first timed level
+    pub fn split(&mut self, level_index: usize, segment_time: f32) {
+        let Some(segment_idx) = level_index.checked_sub(self.tutorial_count) else {
+            return;
+        };
+
+        if segment_idx == 0 {
+            self.reset_run();
+            self.splits.attempt_count += 1;
+            self.run_started = Some(util::unix_time().saturating_sub(segment_time as u64));
+        } else if self.run_started.is_none() || segment_idx != self.segment_times.len() {
+            // The run did not start at the first level, it is not timed
+            return;
+        }
+
+        let attempt_id = self.splits.attempt_count;
+        let segment = &mut self.splits.segments[segment_idx];
+        segment.history.push((attempt_id, segment_time));
+        segment.best_segment = Some(
+            segment
+                .best_segment
+                .map_or(segment_time, |best| best.min(segment_time)),
+        );
+
+        self.segment_times.push(segment_time);
+
+        if self.segment_times.len() == self.splits.segments.len() {
+            self.finish_run();
+        }
+    }
+
+    /// Current time of the run and the difference to the personal best at the current segment
+    pub fn comparison(&self, curr_level_time: f32) -> Option<(f32, Option<f32>)> {
+        self.run_started?;
+
+        let run_time = self.segment_times.iter().sum::<f32>() + curr_level_time;
+        let delta = self
+            .splits
+            .segments
+            .get(self.segment_times.len())
+            .and_then(|segment| segment.pb_split)
+            .map(|pb_split| run_time - pb_split);
+
+        Some((run_time, delta))
+    }
+
+    /// Ends an unfinished run, it is kept in the attempt history without a time
+    pub fn reset_run(&mut self) {
+        if let Some(started) = self.run_started.take() {
+            self.splits.attempts.push(Attempt {
+                id: self.splits.attempt_count,
+                started: format_timestamp(started),
+                ended: format_timestamp(util::unix_time()),
+                time: None,
+            });
+
+            let _ = self.splits.save(&splits_filename(&self.campaign_id));
+        }
+
+        self.segment_times.clear();
+    }
+
+    fn finish_run(&mut self) {
+        let started = self.run_started.take().unwrap_or_else(util::unix_time);
+        let ended = util::unix_time();
+        let total_time = self.segment_times.iter().sum::<f32>();
+
+        self.splits.attempts.push(Attempt {
+            id: self.splits.attempt_count,
+            started: format_timestamp(started),
+            ended: format_timestamp(ended),
+            time: Some(total_time),
+        });
+
+        let is_pb = self
+            .splits
+            .segments
+            .last()
+            .and_then(|segment| segment.pb_split)
+            .is_none_or(|pb_time| total_time < pb_time);
+
+        if is_pb {
+            let mut split_time = 0.;
+            for (segment, segment_time) in self.splits.segments.iter_mut().zip(&self.segment_times)
+            {
+                split_time += segment_time;
+                segment.pb_split = Some(split_time);
+            }
+        }
+
+        let _ = self.splits.save(&splits_filename(&self.campaign_id));
+        let _ = save_run(
+            &self.segment_times,
+            started,
+            &self.splits,
+            &self.campaign_id,
+        );
+
+        self.segment_times.clear();
+    }
+}
+
+pub fn splits_filename(campaign_id: &str) -> String {
+    util::home_filename(&format!(
+        "{}_{}.lss",
+        constants::SPLITS_FILENAME_PREFIX,
+        campaign_id
+    ))
+}
+
+/// Saves the finished run into the runs directory, named by the time it was started
+fn save_run(
+    segment_times: &[f32],
+    started: u64,
+    splits: &Splits,
+    campaign_id: &str,
+) -> std::io::Result<()> {
+    let runs_dir = util::home_filename(constants::RUNS_DIRNAME);
+    std::fs::create_dir_all(&runs_dir)?;
+
+    let mut run = Splits::new(segment_times.len());
+    run.game_name = splits.game_name.clone();
+    run.category_name = splits.category_name.clone();
+    run.attempt_count = 1;
+    run.attempts.push(Attempt {
+        id: 1,
+        started: format_timestamp(started),
+        ended: format_timestamp(util::unix_time()),
+        time: Some(segment_times.iter().sum()),
+    });
+
+    let mut split_time = 0.;
+    for (segment, segment_time) in run.segments.iter_mut().zip(segment_times) {
+        split_time += segment_time;
+        segment.pb_split = Some(split_time);
+        segment.best_segment = Some(*segment_time);
+        segment.history.push((1, *segment_time));
+    }
+
+    let filename =
+        std::path::Path::new(&runs_dir).join(format!("run_{}_{}.lss", campaign_id, started));
+    run.save(&filename.to_string_lossy())
+}
+
+/// Formats the time as `[-]HH:MM:SS.fffffff`
+pub fn format_time(time: f32) -> String {
+    let ticks = (time.abs() as f64 * 10_000_000.).round() as u64;
+    let secs = ticks / 10_000_000;
+
+    format!(
+        "{}{:02}:{:02}:{:02}.{:07}",
+        if time < 0. && ticks > 0 { "-" } else { "" },
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        ticks % 10_000_000
+    )
+}
+
+/// Parses a time in the `[-][D.]HH:MM:SS[.fffffff]` format
+pub fn parse_time(value: &str) -> Option<f32> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(value) => (true, value),
+        None => (false, value),
+    };
+
+    let mut parts = value.split(':');
+    let first = parts.next()?;
+    let (days, hours) = match first.split_once('.') {
+        Some((days, hours)) => (days.parse::<f64>().ok()?, hours.parse::<f64>().ok()?),
+        None => (0., first.parse::<f64>().ok()?),
+    };
+    let minutes = parts.next()?.parse::<f64>().ok()?;
+    let seconds = parts.next()?.parse::<f64>().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let time = ((days * 24. + hours) * 60. + minutes) * 60. + seconds;
+    Some((if negative { -time } else { time }) as f32)
+}
+
+/// Formats a unix timestamp as `MM/DD/YYYY HH:MM:SS` in UTC, the way LiveSplit stores attempts
+fn format_timestamp(timestamp: u64) -> String {
+    let days = timestamp / 86400;
+    let secs = timestamp % 86400;
+
+    // Days to the civil date, from Howard Hinnant's date algorithms
+    let z = days + 719468;
+    let era = z / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as u64;
+
+    format!(
+        "{:02}/{:02}/{:04} {:02}:{:02}:{:02}",
+        month,
+        day,
+        year,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+fn real_time(content: &str) -> Result<Option<f32>, String> {
+    tag_content(content, "RealTime")
+        .map(|time| parse_time(time).ok_or(format!("invalid time: '{}'", time)))
+        .transpose()
+}
+
+/// Content of the first element with the tag, an empty element has no content
+fn tag_content<'a>(data: &'a str, tag: &str) -> Option<&'a str> {
+    elements(data, tag).next().map(|(_, content)| content)
+}
+
+/// Attributes and contents of the elements with the tag, nested elements with the same tag are not supported
+fn elements<'a>(data: &'a str, tag: &str) -> impl Iterator<Item = (&'a str, &'a str)> + 'a {
+    let open = format!("<{}", tag);
+    let close = format!("</{}>", tag);
+    let mut rest = data;
+
+    std::iter::from_fn(move || loop {
+        let start = rest.find(&open)?;
+        let after_name = &rest[start + open.len()..];
+
+        // Skip longer tags which start with the same name
+        if !after_name.starts_with(|c: char| c == '>' || c == '/' || c.is_whitespace()) {
+            rest = after_name;
+            continue;
+        }
+
+        let tag_end = after_name.find('>')?;
+        let attrs = &after_name[..tag_end];
+
+        if let Some(attrs) = attrs.strip_suffix('/') {
+            rest = &after_name[tag_end + 1..];
+            return Some((attrs, ""));
+        }
+
+        let content_start = &after_name[tag_end + 1..];
+        let content_end = content_start.find(&close)?;
+        rest = &content_start[content_end + close.len()..];
+
+        return Some((attrs, &content_start[..content_end]));
+    })
+}
+
+fn attribute<'a>(attrs: &'a str, name: &str) -> Option<&'a str> {
+    let pattern = format!("{}=\"", name);
+    let start = attrs
+        .match_indices(&pattern)
+        .find(|(idx, _)| *idx == 0 || attrs[..*idx].ends_with(char::is_whitespace))?
+        .0
+        + pattern.len();
+
+    attrs[start..].split('"').next()
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn xml_unescape(value: &str) -> String {
+    value
+        .trim()
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
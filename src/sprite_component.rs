@@ -21,11 +21,6 @@ impl SpriteComponent {
         self
     }
 
-    #[inline]
-    pub fn set_color(&mut self, color: graphics::Color) {
-        self.color = color;
-    }
-
     #[inline]
     pub fn set_scale(&mut self, scale: f32) {
         self.scale = glam::Vec2::splat(scale);
@@ -59,6 +54,21 @@ pub fn render_sprite(
     )
 }
 
+/// Renders the image centered at the destination
+pub fn render_image(
+    ctx: &mut Context,
+    quad_ctx: &mut miniquad::Context,
+    image: &graphics::Image,
+    draw_param: graphics::DrawParam,
+) -> GameResult {
+    graphics::draw(
+        ctx,
+        quad_ctx,
+        image,
+        draw_param.offset(glam::vec2(0.5, 0.5)),
+    )
+}
+
 pub fn render_mesh(
     ctx: &mut Context,
     quad_ctx: &mut miniquad::Context,
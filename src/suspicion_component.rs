@@ -0,0 +1,30 @@
+use crate::constants;
+
+pub struct SuspicionComponent {
+    pub suspicion: f32, // 0 - 1, player is detected at 1
+}
+
+impl SuspicionComponent {
+    pub fn new() -> Self {
+        Self { suspicion: 0. }
+    }
+
+    /// Fills the meter by `fill_rate` per second, or decays it if there is nothing to fill it
+    #[inline]
+    pub fn update(&mut self, fill_rate: Option<f32>, dt: f32) {
+        let rate = fill_rate.unwrap_or(-constants::SUSPICION_DECAY_RATE);
+
+        self.suspicion = (self.suspicion + rate * dt).clamp(0., 1.);
+    }
+
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.suspicion >= 1.
+    }
+}
+
+impl Default for SuspicionComponent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
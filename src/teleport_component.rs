@@ -1,17 +1,12 @@
-use crate::sprite_component::SpriteComponent;
 use crate::transform_component::TransformComponent;
 
 pub struct TeleportComponent {
     pub location: Option<TransformComponent>,
-    pub sprite: SpriteComponent,
 }
 
 impl TeleportComponent {
-    pub fn new(sprite: SpriteComponent) -> Self {
-        Self {
-            location: None,
-            sprite,
-        }
+    pub fn new() -> Self {
+        Self { location: None }
     }
 
     #[inline]
@@ -19,3 +14,9 @@ impl TeleportComponent {
         self.location = Some(location);
     }
 }
+
+impl Default for TeleportComponent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,65 @@
+use crate::constants;
+
+pub struct Projectile {
+    pub start: glam::Vec2,
+    pub end: glam::Vec2,
+    pub elapsed: f32,
+    pub duration: f32,
+}
+
+pub struct ThrowComponent {
+    pub projectile: Option<Projectile>,
+}
+
+impl ThrowComponent {
+    pub fn new() -> Self {
+        Self { projectile: None }
+    }
+
+    #[inline]
+    pub fn is_flying(&self) -> bool {
+        self.projectile.is_some()
+    }
+
+    pub fn throw(&mut self, start: glam::Vec2, end: glam::Vec2) {
+        self.projectile = Some(Projectile {
+            start,
+            end,
+            elapsed: 0.,
+            duration: ((end - start).length() / constants::THROW_SPEED).max(0.1),
+        });
+    }
+
+    /// Returns the landing position once the projectile lands
+    pub fn update(&mut self, dt: f32) -> Option<glam::Vec2> {
+        let projectile = self.projectile.as_mut()?;
+        projectile.elapsed += dt;
+
+        if projectile.elapsed < projectile.duration {
+            return None;
+        }
+
+        let landing_position = projectile.end;
+        self.projectile = None;
+
+        Some(landing_position)
+    }
+
+    /// Returns the ground position of the projectile and its height above the ground
+    pub fn get_position(&self) -> Option<(glam::Vec2, f32)> {
+        self.projectile.as_ref().map(|projectile| {
+            let t = projectile.elapsed / projectile.duration;
+
+            (
+                projectile.start.lerp(projectile.end, t),
+                (t * constants::PI).sin() * constants::THROW_ARC_HEIGHT,
+            )
+        })
+    }
+}
+
+impl Default for ThrowComponent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
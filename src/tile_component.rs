@@ -1,26 +1,34 @@
-use crate::constants;
-use crate::util;
-use crate::SpriteComponent;
-use crate::TransformComponent;
+use crate::transform_component::TransformComponent;
+
+/// Texture of a level block, looked up in the tileset when drawing
+#[derive(Clone, Copy, PartialEq)]
+pub enum TileKind {
+    Wall,
+    Box1,
+    Box2,
+    Floor1,
+    Floor2,
+}
 
 pub struct TileComponent {
     pub transform: TransformComponent,
-    pub sprite: SpriteComponent,
+    pub kind: TileKind,
+    pub size: glam::Vec2,
     pub brightness: f32,
 }
 
 impl TileComponent {
-    pub fn new(position: glam::Vec2, width: f32, height: f32, sprite: SpriteComponent) -> Self {
+    pub fn new(
+        position: glam::Vec2,
+        width: f32,
+        height: f32,
+        kind: TileKind,
+        world_width: u32,
+    ) -> Self {
         Self {
-            transform: TransformComponent::new(
-                position,
-                width * height,
-                util::compute_grid_index(&position),
-            ),
-            sprite: sprite.scale(glam::vec2(
-                width / constants::SPRITE_SIZE as f32,
-                height / constants::SPRITE_SIZE as f32,
-            )),
+            transform: TransformComponent::new(position, width * height, world_width),
+            kind,
+            size: glam::vec2(width, height),
             brightness: 0.6,
         }
     }
@@ -4,30 +4,48 @@ use crate::util;
 #[derive(Clone)]
 pub struct TransformComponent {
     pub position: glam::Vec2,
+    pub prev_position: glam::Vec2, // Position at the start of the current tick
     pub size: f32,
     pub angle: f32,
     pub grid_index: isize,
+    pub world_width: u32,
 }
 
 impl TransformComponent {
     #[inline]
-    pub fn new(position: glam::Vec2, size: f32, grid_index: isize) -> Self {
+    pub fn new(position: glam::Vec2, size: f32, world_width: u32) -> Self {
         Self {
             position,
+            prev_position: position,
             size,
             angle: 0.,
-            grid_index,
+            grid_index: util::compute_grid_index(&position, world_width),
+            world_width,
         }
     }
 
     #[inline]
     pub fn set(&mut self, dest: glam::Vec2) {
         self.position = dest;
+        self.grid_index = util::compute_grid_index(&self.position, self.world_width);
+    }
+
+    /// Moves to `dest` without interpolating from the previous position
+    #[inline]
+    pub fn jump_to(&mut self, dest: glam::Vec2) {
+        self.set(dest);
+        self.prev_position = dest;
+    }
+
+    /// Position between the previous and the current tick, used for drawing
+    #[inline]
+    pub fn interpolate(&self, alpha: f32) -> glam::Vec2 {
+        self.prev_position.lerp(self.position, alpha)
     }
 
     #[inline]
     pub fn update(&mut self, dir: glam::Vec2, dt: f32) {
         self.position += dir * dt * constants::TARGET_FPS;
-        self.grid_index = util::compute_grid_index(&self.position);
+        self.grid_index = util::compute_grid_index(&self.position, self.world_width);
     }
 }
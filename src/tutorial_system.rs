@@ -0,0 +1,200 @@
+use crate::alert_system::AlertLevel;
+use crate::constants;
+use crate::util;
+use crate::world::World;
+use crate::Game;
+
+/// Sets up the world of a tutorial level after it was loaded
+pub fn prepare_world(world: &mut World, level_id: &str) {
+    // The fourth tutorial shows what happens when a guard finds a body
+    if level_id == "tutorial4" {
+        if let Some(guard) = world.guards_basic.get_mut(1) {
+            guard.guard.set_dead(true);
+        }
+    }
+}
+
+/// Overlays which guide the player through the tutorial levels
+pub fn system(game_state: &mut Game) {
+    let level = &game_state.campaigns[game_state.campaign_idx].levels[game_state.level_idx];
+    if !level.is_tutorial {
+        return;
+    }
+
+    match level.id.clone().as_str() {
+        "tutorial1" => {
+            let overlay_active = !game_state.world.exit.player_exited;
+            game_state.overlay_system.set_active_at(1, overlay_active);
+            game_state.overlay_system.set_active_at(2, overlay_active);
+
+            let mut pos = game_state.world.player.transform.position;
+            let mut text = "This is you";
+            if game_state.world.target.is_dead() {
+                pos = game_state.world.exit.transform.position;
+                text = "Go through the exit to get to the next level"
+            } else if (game_state.world.player.transform.position - glam::vec2(580., 420.)).length()
+                > 50.
+            {
+                pos = game_state.world.target.transform.position;
+                text = "This is your target\nEliminate the target to pass the level"
+            }
+
+            game_state.overlay_system.set_pos_at(
+                1,
+                pos + glam::vec2(-constants::ENTITY_SIZE * 4., constants::ENTITY_SIZE * 4.),
+            );
+            game_state.overlay_system.set_rot_at(1, -constants::PI / 4.);
+
+            game_state
+                .overlay_system
+                .set_pos_at(2, pos + glam::vec2(0., constants::ENTITY_SIZE * 3.));
+
+            game_state.overlay_system.set_text_at(2, text);
+        }
+        "tutorial2" => {
+            let overlay_active = !game_state.world.target.is_dead()
+                || game_state.world.player.teleport.location.is_some();
+            game_state.overlay_system.set_active_at(1, overlay_active);
+            game_state.overlay_system.set_active_at(2, overlay_active);
+
+            let teleport_key = game_state
+                .keybind_map
+                .get("teleport")
+                .unwrap_or(&util::MyKeyCode(ggez::event::KeyCode::F))
+                .to_string();
+            let sprint_key = game_state
+                .keybind_map
+                .get("sprint")
+                .unwrap_or(&util::MyKeyCode(ggez::event::KeyCode::Space))
+                .to_string();
+
+            let mut pos = game_state.world.exit.transform.position
+                + glam::vec2(0., constants::ENTITY_SIZE * 5.);
+
+            let mut text = format!("You also have some special abilities which drain your stamina\nOne of them is teleport\nTry pressing the {} key somewhere here to place the marker", teleport_key);
+
+            if game_state.world.target.is_dead() {
+                pos = game_state.world.player.transform.position;
+                text = format!("To teleport back\npress the {} key", teleport_key);
+            } else if game_state.world.player.teleport.location.is_some() {
+                pos = game_state.world.level_header.world_size() / 2.;
+                text = format!("You can sprint by\nholding the {} key", sprint_key);
+            }
+
+            game_state.overlay_system.set_pos_at(
+                1,
+                pos + glam::vec2(-constants::ENTITY_SIZE * 4., constants::ENTITY_SIZE * 4.),
+            );
+            game_state.overlay_system.set_rot_at(1, -constants::PI / 4.);
+
+            game_state
+                .overlay_system
+                .set_pos_at(2, pos + glam::vec2(0., constants::ENTITY_SIZE * 3.));
+
+            game_state.overlay_system.set_text_at(2, &text);
+        }
+        "tutorial3" => {
+            let overlay_active = !game_state.world.guards_basic[0].is_dead();
+            game_state.overlay_system.set_active_at(1, overlay_active);
+            game_state.overlay_system.set_active_at(2, overlay_active);
+
+            let sneak_key = game_state
+                .keybind_map
+                .get("sneak")
+                .unwrap_or(&util::MyKeyCode(ggez::event::KeyCode::S))
+                .to_string();
+            let attack_key = game_state
+                .keybind_map
+                .get("attack")
+                .unwrap_or(&util::MyKeyCode(ggez::event::KeyCode::A))
+                .to_string();
+
+            let mut pos = game_state.world.player.transform.position
+                + glam::vec2(0., constants::ENTITY_SIZE * 3.);
+            let mut text = "Target will usually have some guards\nYou can avoid them\nor eliminate them aswell\nBe wary\nguards can hear your footsteps".to_owned();
+
+            if (game_state.world.player.transform.position - glam::vec2(500., 460.)).length() > 50.
+            {
+                pos = game_state.world.guards_basic[0].guard.transform.position
+                    + glam::vec2(0., constants::ENTITY_SIZE * 3.);
+                text = format!( "Try to eliminate this guard\nApproach silently by holding the {} key\nEliminate with {} key", sneak_key, attack_key);
+            }
+
+            game_state.overlay_system.set_pos_at(
+                1,
+                pos + glam::vec2(-constants::ENTITY_SIZE * 4., constants::ENTITY_SIZE * 4.),
+            );
+            game_state.overlay_system.set_rot_at(1, -constants::PI / 4.);
+
+            game_state
+                .overlay_system
+                .set_pos_at(2, pos + glam::vec2(0., constants::ENTITY_SIZE * 3.));
+
+            game_state.overlay_system.set_text_at(2, &text);
+        }
+        "tutorial4" => {
+            let overlay_active = !game_state.world.exit.player_exited;
+            game_state.overlay_system.set_active_at(1, overlay_active);
+            game_state.overlay_system.set_active_at(2, overlay_active);
+
+            let mut pos = game_state.world.guards_basic[1].guard.transform.position
+                + glam::vec2(0., constants::ENTITY_SIZE * 3.);
+            let mut text = "If a guard finds a dead body\nit will inspect it and radio an alert";
+            if game_state.world.alert_system.alert_level != AlertLevel::Calm {
+                pos = game_state.world.target.transform.position
+                    + glam::vec2(0., constants::ENTITY_SIZE * 3.);
+                text = "Try to bypass the guards\neliminate the target and escape";
+            }
+
+            game_state.overlay_system.set_pos_at(
+                1,
+                pos + glam::vec2(-constants::ENTITY_SIZE * 4., constants::ENTITY_SIZE * 4.),
+            );
+            game_state.overlay_system.set_rot_at(1, -constants::PI / 4.);
+
+            game_state
+                .overlay_system
+                .set_pos_at(2, pos + glam::vec2(0., constants::ENTITY_SIZE * 3.));
+
+            game_state.overlay_system.set_text_at(2, text);
+        }
+        "tutorial5" => {
+            if game_state.world.exit.player_exited {
+                game_state.overlay_system.set_active_at(1, false);
+                game_state.overlay_system.set_active_at(2, false);
+
+                game_state.is_skip_tutorial = true;
+                game_state.write_config(&util::config_filename());
+            } else if game_state.world.target.is_dead()
+                || (game_state.world.player.transform.position - glam::vec2(100., 380.)).length()
+                    < 30.
+            {
+                game_state.overlay_system.set_active_at(1, false);
+                game_state.overlay_system.set_active_at(2, true);
+            } else {
+                game_state.overlay_system.set_active_at(2, false);
+            }
+
+            let stealth_key = game_state
+                .keybind_map
+                .get("stealth")
+                .unwrap_or(&util::MyKeyCode(ggez::event::KeyCode::A))
+                .to_string();
+
+            let mut pos = game_state.world.player.transform.position;
+            let mut text =
+                format!("Your second ability is stealth\nActivate it by holding the {} key\nYou cannot move while being stealth\nTry to eliminate the guard", stealth_key);
+
+            if game_state.world.target.is_dead() {
+                pos = glam::vec2(645., 80.);
+                text = "Remember, your score is determined\nby the amount of time needed\nfor completing each level\nso try to complete it as fast as possible".to_owned();
+            }
+
+            game_state
+                .overlay_system
+                .set_pos_at(2, pos + glam::vec2(0., constants::ENTITY_SIZE * 3.));
+            game_state.overlay_system.set_text_at(2, &text);
+        }
+        _ => (),
+    }
+}
@@ -2,10 +2,8 @@ use std::collections::HashMap;
 use std::fmt;
 
 use crate::animation_component::AnimationComponent;
-use crate::assets::Assets;
 use crate::constants;
 use crate::look_component::LookComponent;
-use crate::sprite_component::SpriteComponent;
 use crate::transform_component::TransformComponent;
 
 pub fn make_text(s: String, scale: f32) -> ggez::graphics::Text {
@@ -31,16 +29,27 @@ pub fn check_spotted(
     dest: &TransformComponent,
     rect_objects: &Vec<&ggez::graphics::Rect>,
 ) -> bool {
+    compute_exposure(look, source, dest, rect_objects).is_some()
+}
+
+/// Returns how exposed dest is to source, in range [0, 1], where 1 means close and
+/// in the center of the view. Returns `None` if source does not see dest.
+pub fn compute_exposure(
+    look: &LookComponent,
+    source: &TransformComponent,
+    dest: &TransformComponent,
+    rect_objects: &Vec<&ggez::graphics::Rect>,
+) -> Option<f32> {
     let vec_to_dest = dest.position - source.position;
     let len_to_dest_sq = vec_to_dest.length_squared();
 
     // Source and dest are colliding
     if len_to_dest_sq <= (source.size + dest.size).powi(2) {
-        return true;
+        return Some(1.);
     }
     // Dest is out of view distance
     if len_to_dest_sq > look.view_distance.powi(2) {
-        return false;
+        return None;
     }
 
     // Check if there is an collision object between the source and destination
@@ -60,13 +69,21 @@ pub fn check_spotted(
         )
     });
     if line_of_sight_blocked {
-        return false;
+        return None;
     }
 
     // Check spotted
+    let len_to_dest = len_to_dest_sq.sqrt();
     let dot = look.look_at.dot(vec_to_dest);
-    let angle = (dot / (look.look_at.length() * len_to_dest_sq.sqrt())).acos();
-    return angle <= look.fov;
+    let angle = (dot / (look.look_at.length() * len_to_dest)).acos();
+    if angle.is_nan() || angle > look.fov {
+        return None;
+    }
+
+    let distance_factor = 1. - len_to_dest / look.view_distance;
+    let center_factor = 1. - angle / look.fov;
+
+    Some((distance_factor + center_factor) / 2.)
 }
 
 pub fn line_line_intersects(
@@ -272,33 +289,8 @@ pub fn make_particle_image(
     ggez::graphics::Image::from_rgba8(ctx, quad_ctx, 1, 1, &bytes).unwrap()
 }
 
-pub fn build_walk_animation(
-    assets: &Assets,
-    duration: f32,
-    color: ggez::graphics::Color,
-) -> AnimationComponent {
-    let s1 = SpriteComponent::new(assets.stand.clone(), color).scale(constants::SPRITE_SCALE);
-    let s2 = SpriteComponent::new(assets.walk_l1.clone(), color).scale(constants::SPRITE_SCALE);
-    let s3 = SpriteComponent::new(assets.walk_l2.clone(), color).scale(constants::SPRITE_SCALE);
-    let s4 = SpriteComponent::new(assets.walk_l3.clone(), color).scale(constants::SPRITE_SCALE);
-    let s5 = SpriteComponent::new(assets.walk_l4.clone(), color).scale(constants::SPRITE_SCALE);
-    let s6 = SpriteComponent::new(assets.walk_l3.clone(), color).scale(constants::SPRITE_SCALE);
-    let s7 = SpriteComponent::new(assets.walk_l2.clone(), color).scale(constants::SPRITE_SCALE);
-    let s8 = SpriteComponent::new(assets.walk_l1.clone(), color).scale(constants::SPRITE_SCALE);
-    let s9 = SpriteComponent::new(assets.stand.clone(), color).scale(constants::SPRITE_SCALE);
-    let s10 = SpriteComponent::new(assets.walk_r1.clone(), color).scale(constants::SPRITE_SCALE);
-    let s11 = SpriteComponent::new(assets.walk_r2.clone(), color).scale(constants::SPRITE_SCALE);
-    let s12 = SpriteComponent::new(assets.walk_r3.clone(), color).scale(constants::SPRITE_SCALE);
-    let s13 = SpriteComponent::new(assets.walk_r4.clone(), color).scale(constants::SPRITE_SCALE);
-    let s14 = SpriteComponent::new(assets.walk_r3.clone(), color).scale(constants::SPRITE_SCALE);
-    let s15 = SpriteComponent::new(assets.walk_r2.clone(), color).scale(constants::SPRITE_SCALE);
-    let s16 = SpriteComponent::new(assets.walk_r1.clone(), color).scale(constants::SPRITE_SCALE);
-
-    let animation = vec![
-        s1, s2, s3, s4, s5, s6, s7, s8, s9, s10, s11, s12, s13, s14, s15, s16,
-    ];
-
-    AnimationComponent::new(animation, duration)
+pub fn build_walk_animation(duration: f32, color: ggez::graphics::Color) -> AnimationComponent {
+    AnimationComponent::new(constants::WALK_ANIMATION_FRAMES, duration, color)
 }
 
 #[inline]
@@ -331,30 +323,59 @@ pub fn clamp(v: f32, min: f32, max: f32) -> f32 {
     v.min(max).max(min)
 }
 
-pub fn compute_grid_index(position: &glam::Vec2) -> isize {
+pub fn compute_grid_index(position: &glam::Vec2, world_width: u32) -> isize {
     let x = position.x as usize / constants::GRID_CELL_SIZE;
     let y = position.y as usize / constants::GRID_CELL_SIZE;
 
-    (x + y * constants::MAX_WORLD_X as usize / constants::GRID_CELL_SIZE) as isize
+    (x + y * grid_row_len(world_width) as usize) as isize
+}
+
+/// Number of grid cells in one row of a world which is `world_width` pixels wide,
+/// the last cell of the row can be partial
+#[inline]
+pub fn grid_row_len(world_width: u32) -> isize {
+    (world_width as usize).div_ceil(constants::GRID_CELL_SIZE) as isize
+}
+
+pub fn unix_time() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
 }
 
 pub fn config_filename() -> String {
+    home_filename(constants::CONFIG_FILENAME)
+}
+
+/// Path of the file in the home directory of the user
+pub fn home_filename(filename: &str) -> String {
     if cfg!(windows) {
         let win_home = std::env::var("userprofile").unwrap_or_default();
         if win_home.len() > 0 {
-            return format!("{}\\{}", win_home, constants::CONFIG_FILENAME);
+            return format!("{}\\{}", win_home, filename);
         }
     } else if cfg!(unix) {
         let unix_user = std::env::var("USER").unwrap_or_default();
         if unix_user.len() > 0 {
-            return format!("/home/{}/{}", unix_user, constants::CONFIG_FILENAME);
+            return format!("/home/{}/{}", unix_user, filename);
         }
     }
 
-    constants::CONFIG_FILENAME.to_owned()
+    filename.to_owned()
 }
 
-pub fn read_config(filename: &str) -> (bool, bool, bool, HashMap<String, MyKeyCode>) {
+/// Reads a string setting, which is not part of the settings in `read_config`
+pub fn read_config_str(filename: &str, key: &str) -> Option<String> {
+    let data = std::fs::read_to_string(filename).unwrap_or_default();
+    let config: serde_json::Value = serde_json::from_str(&data).unwrap_or_default();
+
+    config
+        .get(key)
+        .and_then(|val| val.as_str())
+        .map(|val| val.to_owned())
+}
+
+pub fn read_config(filename: &str) -> (bool, bool, bool, bool, HashMap<String, MyKeyCode>) {
     let data = std::fs::read_to_string(filename).unwrap_or_default();
     let config: serde_json::Value = serde_json::from_str(&data).unwrap_or_default();
 
@@ -377,6 +398,10 @@ pub fn read_config(filename: &str) -> (bool, bool, bool, HashMap<String, MyKeyCo
                 .and_then(|val| Some(val.as_bool()))
                 .unwrap_or(Some(false))
                 .unwrap_or(false);
+            let is_speedrun = m
+                .get("speedrun")
+                .and_then(|val| val.as_bool())
+                .unwrap_or(false);
 
             keybind_map.insert("up".to_owned(), get_keycode(&m, "up", 59));
             keybind_map.insert("left".to_owned(), get_keycode(&m, "left", 57));
@@ -393,13 +418,14 @@ pub fn read_config(filename: &str) -> (bool, bool, bool, HashMap<String, MyKeyCo
                 is_muted,
                 are_particles_activated,
                 is_skip_tutorial,
+                is_speedrun,
                 keybind_map,
             );
         }
         _ => (),
     }
 
-    (false, true, false, HashMap::new())
+    (false, true, false, false, HashMap::new())
 }
 
 fn get_keycode(
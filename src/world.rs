@@ -0,0 +1,284 @@
+use crate::alert_system::AlertSystem;
+use crate::constants;
+use crate::entities;
+use crate::entities::player::MoveType;
+use crate::level::LevelHeader;
+use crate::look_component;
+use crate::navigation_system::NavigationSystem;
+use crate::noise_system::NoiseSystem;
+use crate::rng::GameRng;
+use crate::tile_component::TileComponent;
+
+/// Input of the player for a single simulation step
+#[derive(Clone)]
+pub struct PlayerInput {
+    pub move_dir: glam::Vec2,
+    pub move_type: MoveType,
+    pub stealth: bool,
+
+    // Actions are only applied in the step in which they were triggered
+    pub teleport: bool,
+    pub attack: bool,
+    pub throw_target: Option<glam::Vec2>,
+}
+
+impl PlayerInput {
+    pub fn new() -> Self {
+        Self {
+            move_dir: glam::Vec2::ZERO,
+            move_type: MoveType::Normal,
+            stealth: false,
+            teleport: false,
+            attack: false,
+            throw_target: None,
+        }
+    }
+
+    #[inline]
+    pub fn set_x_dir(&mut self, x_dir: f32) {
+        self.move_dir = glam::vec2(x_dir, self.move_dir.y).normalize_or_zero();
+    }
+
+    #[inline]
+    pub fn set_y_dir(&mut self, y_dir: f32) {
+        self.move_dir = glam::vec2(self.move_dir.x, y_dir).normalize_or_zero();
+    }
+
+    pub fn clear_actions(&mut self) {
+        self.teleport = false;
+        self.attack = false;
+        self.throw_target = None;
+    }
+}
+
+impl Default for PlayerInput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sounds of the game, in the order in which they are loaded into the sound collection
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sound {
+    Stealth,
+    Unstealth,
+    TeleportInitial,
+    Teleport,
+    PlayerDead,
+    TargetKilled,
+    DeadTargetDetected,
+    LevelExit,
+    Footstep,
+}
+
+/// Particle emitters of the game, in the order in which they are added to the particle system
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Emitter {
+    PlayerMove,
+    PlayerFootstep,
+    Kill,
+    Teleport,
+}
+
+/// Side effects of a step which are played back by the game, since the world
+/// has no access to the audio or the particle system
+#[derive(Debug, PartialEq)]
+pub enum WorldEvent {
+    /// The sound and the volume to set before playing it
+    Sound(Sound, Option<f32>),
+    /// The emitter, position, number of particles and the scale to set before emitting
+    Particles(Emitter, glam::Vec2, u32, Option<f32>),
+}
+
+/// Gameplay state of the current level, it can be stepped without a window or a graphics context
+pub struct World {
+    pub player: entities::player::Player,
+    pub target: entities::target::Target,
+    pub guards_basic: Vec<entities::guards::guard_basic::GuardBasic>,
+    pub guards_scout: Vec<entities::guards::guard_scout::GuardScout>,
+    pub guards_heavy: Vec<entities::guards::guard_heavy::GuardHeavy>,
+    pub walls: Vec<entities::wall::Wall>,
+    pub floor_tiles: Vec<TileComponent>,
+    pub hiding_spots: Vec<TileComponent>,
+    pub exit: entities::exit::Exit,
+    pub navigation_system: NavigationSystem,
+    pub alert_system: AlertSystem,
+    pub noise_system: NoiseSystem,
+    pub level_header: LevelHeader,
+    pub curr_level_time: f32,
+    pub tick: u32, // Number of steps in the current attempt
+    pub kills: u32,
+    pub seed: u64, // Seed of the current attempt, together with the inputs it reproduces the run
+    pub rng: GameRng,
+    pub is_player_detected: bool,
+    pub events: Vec<WorldEvent>,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self {
+            player: entities::player::Player::new(
+                glam::Vec2::ZERO,
+                ggez::graphics::Color::BLACK,
+                constants::MAX_WORLD_X,
+            ),
+            target: entities::target::Target::new(
+                glam::Vec2::ZERO,
+                ggez::graphics::Color::GREEN,
+                constants::MAX_WORLD_X,
+            ),
+            guards_basic: vec![],
+            guards_scout: vec![],
+            guards_heavy: vec![],
+            walls: vec![],
+            floor_tiles: vec![],
+            hiding_spots: vec![],
+            exit: entities::exit::Exit::new(glam::Vec2::ZERO, constants::MAX_WORLD_X),
+            navigation_system: NavigationSystem::new(),
+            alert_system: AlertSystem::new(),
+            noise_system: NoiseSystem::new(),
+            level_header: LevelHeader::default(),
+            curr_level_time: 0.,
+            tick: 0,
+            kills: 0,
+            seed: 0,
+            rng: GameRng::new(0),
+            is_player_detected: false,
+            events: vec![],
+        }
+    }
+
+    pub fn reset(&mut self, is_proceed: bool, seed: u64) {
+        self.seed = seed;
+        self.rng = GameRng::new(seed);
+        self.tick = 0;
+        self.kills = 0;
+
+        self.player.is_stealth = false;
+        self.player.stealth_intent = false;
+
+        self.target.set_dead(false);
+        self.alert_system.clear();
+        self.noise_system.clear();
+
+        self.exit.player_exited = false;
+        self.is_player_detected = false;
+
+        self.guards_basic.clear();
+        self.guards_scout.clear();
+        self.guards_heavy.clear();
+
+        self.walls.clear();
+
+        self.floor_tiles.clear();
+        self.hiding_spots.clear();
+
+        self.events.clear();
+
+        if is_proceed {
+            self.curr_level_time = 0.;
+        }
+    }
+
+    /// Advances the simulation by `dt`, returns the events which happened during the step
+    pub fn step(&mut self, input: &PlayerInput, dt: f32) -> Vec<WorldEvent> {
+        self.store_previous_positions();
+
+        entities::player::apply_input(self, input);
+
+        entities::wall::check_collision(self);
+
+        entities::player::system(self, dt);
+
+        entities::target::system(self, dt);
+
+        entities::guards::system(self, dt);
+
+        look_component::system(self);
+
+        entities::exit::system(self, dt);
+
+        self.curr_level_time += dt;
+        self.tick += 1;
+
+        std::mem::take(&mut self.events)
+    }
+
+    /// Remembers where the characters were before the step, so that drawing can interpolate
+    fn store_previous_positions(&mut self) {
+        self.player.transform.prev_position = self.player.transform.position;
+        self.target.transform.prev_position = self.target.transform.position;
+
+        for guard in self.get_all_guards_mut() {
+            guard.transform.prev_position = guard.transform.position;
+        }
+    }
+
+    /// Checks whether the position lies in one of the hiding spots
+    pub fn is_hidden(&self, position: glam::Vec2) -> bool {
+        let block_size = constants::LEVEL_BLOCK_SIZE as f32;
+
+        self.hiding_spots.iter().any(|hiding_spot| {
+            let offset = position - hiding_spot.transform.position;
+            offset.x >= 0. && offset.y >= 0. && offset.x < block_size && offset.y < block_size
+        })
+    }
+
+    // Collect all common guard objects into one vector
+    pub fn get_all_guards(&self) -> Vec<&entities::guards::Guard> {
+        let guards_b = self
+            .guards_basic
+            .iter()
+            .map(|guard_basic| &guard_basic.guard);
+
+        let guards_s = self
+            .guards_scout
+            .iter()
+            .map(|guard_sniper| &guard_sniper.guard);
+
+        let guards_h = self
+            .guards_heavy
+            .iter()
+            .map(|guard_heavy| &guard_heavy.guard);
+
+        guards_b
+            .chain(guards_s)
+            .chain(guards_h)
+            .collect::<Vec<&entities::guards::Guard>>()
+    }
+
+    pub fn get_all_guards_mut(&mut self) -> Vec<&mut entities::guards::Guard> {
+        let guards_b = self
+            .guards_basic
+            .iter_mut()
+            .map(|guard_basic| &mut guard_basic.guard);
+
+        let guards_s = self
+            .guards_scout
+            .iter_mut()
+            .map(|guard_sniper| &mut guard_sniper.guard);
+
+        let guards_h = self
+            .guards_heavy
+            .iter_mut()
+            .map(|guard_heavy| &mut guard_heavy.guard);
+
+        guards_b
+            .chain(guards_s)
+            .chain(guards_h)
+            .collect::<Vec<&mut entities::guards::Guard>>()
+    }
+
+    pub fn play_kill_effect(&mut self, pos: glam::Vec2) {
+        self.events
+            .push(WorldEvent::Sound(Sound::TargetKilled, None));
+        self.events
+            .push(WorldEvent::Particles(Emitter::Kill, pos, 50, None));
+    }
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,136 @@
+use ultimate_ninja::editor_system::EditorSystem;
+use ultimate_ninja::level;
+use ultimate_ninja::patrol_component::PatrolMode;
+
+/// Level file in a directory of its own, so that the tests do not share files
+fn level_file(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "ultimate_ninja_editor_{}_{}",
+        name,
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    dir.join(format!("{}.txt", name))
+}
+
+fn open(name: &str, source: &str) -> EditorSystem {
+    let filename = level_file(name);
+    std::fs::write(&filename, source).unwrap();
+
+    EditorSystem::open(filename.to_str().unwrap())
+}
+
+const LEVEL: &str = "\
+version: 2
+name: Editor test
+---
+xxxxxxxx
+xp-g--tx
+x-----ex
+xxxxxxxx
+---
+patrol 3,1 pingpong: 4,1 wait=2 -> 4,2 look=90
+";
+
+#[test]
+fn paint_moves_unique_glyphs() {
+    let mut editor = open("unique", LEVEL);
+
+    assert!(editor.paint((2, 2), 'p'));
+    assert_eq!(editor.glyph_at((1, 1)), Some('-'));
+    assert_eq!(editor.glyph_at((2, 2)), Some('p'));
+    assert!(editor.is_modified);
+
+    // Painting the same glyph again changes nothing
+    assert!(!editor.paint((2, 2), 'p'));
+    // Blocks outside of the level are ignored
+    assert!(!editor.paint((20, 20), 'x'));
+}
+
+#[test]
+fn removed_guard_drops_its_route() {
+    let mut editor = open("route", LEVEL);
+    editor.patrol_click((3, 1));
+    assert_eq!(editor.selected_guard, Some((3, 1)));
+
+    // Another guard glyph keeps the route
+    assert!(editor.paint((3, 1), 's'));
+    assert!(editor.patrol((3, 1)).is_some());
+
+    assert!(editor.paint((3, 1), '-'));
+    assert!(editor.patrol((3, 1)).is_none());
+    assert!(editor.level.patrols.is_empty());
+    assert_eq!(editor.selected_guard, None);
+}
+
+#[test]
+fn patrol_click_edits_the_route_of_the_selected_guard() {
+    let mut editor = open("patrol", LEVEL);
+
+    // A waypoint needs a selected guard
+    assert!(!editor.patrol_click((2, 2)));
+
+    assert!(!editor.patrol_click((3, 1)));
+    assert!(editor.patrol_click((2, 2)));
+    // Walls cannot be waypoints
+    assert!(!editor.patrol_click((0, 0)));
+
+    let cells = |editor: &EditorSystem| {
+        editor
+            .patrol((3, 1))
+            .unwrap()
+            .waypoints
+            .iter()
+            .map(|waypoint| waypoint.cell)
+            .collect::<Vec<(u32, u32)>>()
+    };
+    assert_eq!(cells(&editor), vec![(4, 1), (4, 2), (2, 2)]);
+
+    assert!(editor.toggle_patrol_mode());
+    assert!(editor.patrol((3, 1)).unwrap().mode == PatrolMode::Loop);
+
+    assert!(editor.remove_waypoint());
+    assert_eq!(cells(&editor), vec![(4, 1), (4, 2)]);
+    assert!(editor.remove_waypoint());
+    assert!(editor.remove_waypoint());
+    assert!(editor.patrol((3, 1)).is_none());
+}
+
+#[test]
+fn level_text_round_trip() {
+    let level_data = level::parse_level(LEVEL).unwrap();
+    let text = level::level_to_string(&level_data);
+
+    let parsed = level::parse_level(&text).unwrap();
+    assert_eq!(level::level_to_string(&parsed), text);
+    assert_eq!(parsed.header.name, "Editor test");
+    assert_eq!(parsed.rows, level_data.rows);
+
+    let patrol = parsed.patrol_at((3, 1)).unwrap();
+    assert!(patrol.mode == PatrolMode::PingPong);
+    assert_eq!(patrol.waypoints[0].wait_time, 2.);
+    assert_eq!(patrol.waypoints[1].look_angle, Some(90.));
+
+    // The editor writes the same text
+    let editor = open("round_trip", LEVEL);
+    assert_eq!(editor.level_text(), text);
+}
+
+#[test]
+fn broken_file_is_not_overwritten_without_confirmation() {
+    let broken = "version: 2\nsize: wide\n---\nxxx\n";
+    let mut editor = open("broken", broken);
+    assert!(editor.is_overwrite_protected);
+
+    editor.save();
+    assert_eq!(std::fs::read_to_string(&editor.filename).unwrap(), broken);
+
+    // The second save is the confirmation
+    editor.save();
+    assert_eq!(
+        std::fs::read_to_string(&editor.filename).unwrap(),
+        editor.level_text()
+    );
+    assert!(!editor.is_modified);
+}
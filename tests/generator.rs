@@ -0,0 +1,67 @@
+use ultimate_ninja::level;
+use ultimate_ninja::level_generator::{self, GeneratorParams};
+use ultimate_ninja::level_lint;
+
+fn generate(seed: u64, difficulty: f32) -> Result<String, String> {
+    level_generator::generate_level(&GeneratorParams::with_difficulty(seed, difficulty))
+        .map(|level_data| level::level_to_string(&level_data))
+}
+
+#[test]
+fn generated_levels_are_valid() {
+    for seed in 0..40 {
+        for difficulty in [0., 0.5, 1.] {
+            let level = generate(seed, difficulty).unwrap();
+
+            assert_eq!(
+                level_lint::lint_level(&level, "generated", false)
+                    .iter()
+                    .map(|diagnostic| diagnostic.to_string())
+                    .collect::<Vec<String>>(),
+                Vec::<String>::new(),
+                "seed {}, difficulty {}:\n{}",
+                seed,
+                difficulty,
+                level
+            );
+        }
+    }
+}
+
+#[test]
+fn generated_level_can_be_loaded() {
+    let level = generate(7, 1.).unwrap();
+    let level_data = level::parse_level(&level).unwrap();
+
+    assert_eq!(level_data.header.name, "Generated 0000000000000007");
+    assert!(!level_data.patrols.is_empty());
+    assert_eq!(level::level_to_string(&level_data), level);
+}
+
+#[test]
+fn same_seed_same_level() {
+    assert_eq!(generate(1234, 0.7), generate(1234, 0.7));
+    assert_ne!(generate(1234, 0.7), generate(1235, 0.7));
+}
+
+#[test]
+fn generated_level_has_every_kind_of_guard() {
+    let level = (0..20)
+        .map(|seed| generate(seed, 1.))
+        .collect::<Result<String, String>>()
+        .unwrap();
+
+    for guard in ['g', 's', 'h'] {
+        assert!(level.contains(guard), "no '{}' guard", guard);
+    }
+}
+
+#[test]
+fn too_small_level_is_rejected() {
+    let params = GeneratorParams {
+        size: (8, 8),
+        ..GeneratorParams::with_difficulty(0, 0.5)
+    };
+
+    assert!(level_generator::generate_level(&params).is_err());
+}
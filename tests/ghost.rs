@@ -0,0 +1,104 @@
+use ultimate_ninja::constants;
+use ultimate_ninja::ghost_system::{GhostSample, GhostTrace};
+
+fn trace(points: &[(f32, f32)]) -> GhostTrace {
+    GhostTrace {
+        time: points.len() as f32,
+        samples: points
+            .iter()
+            .enumerate()
+            .map(|(idx, &position)| GhostSample {
+                time: idx as f32,
+                position,
+                angle: 0.,
+                frame: idx,
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn pose_is_interpolated_between_samples() {
+    let mut ghost = trace(&[(0., 0.), (10., 0.), (10., 20.)]);
+    ghost.samples[0].angle = constants::PI - 0.1;
+    ghost.samples[1].angle = -constants::PI + 0.1;
+
+    let (position, angle, frame) = ghost.pose_at(0.5).unwrap();
+    assert_eq!(position, glam::vec2(5., 0.));
+    // The ghost turns the short way around, through PI and not through 0
+    assert!((angle - constants::PI).abs() < 1e-4);
+    assert_eq!(frame, 0);
+
+    let (position, _, frame) = ghost.pose_at(1.25).unwrap();
+    assert_eq!(position, glam::vec2(10., 5.));
+    assert_eq!(frame, 1);
+
+    // There is no ghost before the first sample or after the last one
+    assert!(ghost.pose_at(-1.).is_none());
+    assert!(ghost.pose_at(2.).is_none());
+    assert!(GhostTrace::default().pose_at(0.).is_none());
+}
+
+#[test]
+fn split_follows_the_route() {
+    // Out along the corridor and back through the same cells
+    let ghost = trace(&[
+        (0., 0.),
+        (10., 0.),
+        (20., 0.),
+        (30., 0.),
+        (20., 0.),
+        (10., 0.),
+    ]);
+    let mut cursor = 0;
+
+    assert_eq!(
+        ghost.split_delta(&mut cursor, glam::vec2(0., 0.), 0.5),
+        Some(0.5)
+    );
+    assert_eq!(
+        ghost.split_delta(&mut cursor, glam::vec2(19., 0.), 1.5),
+        Some(-0.5)
+    );
+    assert_eq!(
+        ghost.split_delta(&mut cursor, glam::vec2(30., 0.), 3.),
+        Some(0.)
+    );
+    // On the way back the same position is compared with the later samples
+    assert_eq!(
+        ghost.split_delta(&mut cursor, glam::vec2(20., 0.), 5.),
+        Some(1.)
+    );
+    assert_eq!(
+        ghost.split_delta(&mut cursor, glam::vec2(10., 0.), 5.),
+        Some(0.)
+    );
+    assert_eq!(cursor, 5);
+
+    assert!(GhostTrace::default()
+        .split_delta(&mut 0, glam::Vec2::ZERO, 0.)
+        .is_none());
+}
+
+#[test]
+fn split_does_not_jump_ahead_where_the_route_crosses_itself() {
+    // A loop which returns to the start before going on
+    let mut points = vec![(0., 0.), (10., 0.), (10., 10.), (0., 10.), (0., 1.)];
+    points.extend((1..40).map(|idx| (0., -(idx as f32) * 10.)));
+    let ghost = trace(&points);
+
+    let mut cursor = 0;
+    // Close to the end of the loop, but the run has just started
+    assert_eq!(
+        ghost.split_delta(&mut cursor, glam::vec2(1., 0.), 0.2),
+        Some(0.2)
+    );
+    assert_eq!(cursor, 0);
+
+    // Positions far along the route are out of reach until the cursor gets there
+    let delta = ghost
+        .split_delta(&mut cursor, glam::vec2(0., -390.), 1.)
+        .unwrap();
+    assert_eq!(cursor, constants::GHOST_SPLIT_WINDOW - 1);
+    assert_eq!(delta, 1. - cursor as f32);
+}
@@ -0,0 +1,196 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::{Child, ChildStdout, Command, Stdio};
+use ultimate_ninja::leaderboard_backend::{
+    block_on, HttpBackend, LeaderboardBackend, LeaderboardError, LocalBackend, PlayerEntry,
+    Spawner, ThreadSpawner,
+};
+
+fn leaderboard_file(name: &str) -> String {
+    let filename = std::env::temp_dir().join(format!(
+        "ultimate_ninja_leaderboard_{}_{}.json",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&filename);
+
+    filename.to_str().unwrap().to_owned()
+}
+
+fn entry(username: &str, time: f32) -> PlayerEntry {
+    level_entry(username, time, "level1", "hash")
+}
+
+fn level_entry(username: &str, time: f32, level_id: &str, level_hash: &str) -> PlayerEntry {
+    PlayerEntry {
+        username: username.to_owned(),
+        time,
+        level_id: level_id.to_owned(),
+        level_hash: level_hash.to_owned(),
+        timestamp: 0,
+        game_version: String::new(),
+        kills: 0,
+        detections: 0,
+        replay: None,
+    }
+}
+
+#[test]
+fn local_backend_keeps_concurrent_submits() {
+    const N: usize = 32;
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let backend = LocalBackend::new(leaderboard_file("concurrent"));
+
+    let submits = (0..N)
+        .map(|idx| runtime.spawn(backend.submit_time(entry(&format!("player{}", idx), idx as f32))))
+        .collect::<Vec<_>>();
+    for submit in submits {
+        runtime.block_on(submit).unwrap().unwrap();
+    }
+
+    let entries = runtime
+        .block_on(backend.request_leaderboard("level1", "hash"))
+        .unwrap();
+    assert_eq!(entries.len(), N);
+    assert!(entries.windows(2).all(|pair| pair[0].time <= pair[1].time));
+
+    // Other levels and other versions of the level are filtered out
+    let other = runtime
+        .block_on(backend.request_leaderboard("level1", "other hash"))
+        .unwrap();
+    assert!(other.is_empty());
+}
+
+#[test]
+fn local_backend_runs_without_a_runtime() {
+    let backend = LocalBackend::new(leaderboard_file("no_runtime"));
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let submit = backend.submit_time(entry("player", 10.));
+    ThreadSpawner.spawn(Box::pin(async move {
+        let _ = sender.send(submit.await);
+    }));
+    receiver.recv().unwrap().unwrap();
+
+    let entries = block_on(backend.request_leaderboard("level1", "hash")).unwrap();
+    assert_eq!(entries.len(), 1);
+}
+
+/// The leaderboard server binary, it is killed when the test ends. Its output is kept open so that
+/// it can keep logging the requests
+struct Server {
+    child: Child,
+    _stdout: BufReader<ChildStdout>,
+}
+
+impl Server {
+    /// Starts the server on a free port and returns its URL
+    fn start(api_key: &str) -> (Self, String) {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_leaderboard_server"))
+            .args(["--address", "127.0.0.1:0", "--api-key", api_key])
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+        let mut line = String::new();
+        stdout.read_line(&mut line).unwrap();
+        let url = line.split_whitespace().last().unwrap().to_owned();
+
+        (
+            Self {
+                child,
+                _stdout: stdout,
+            },
+            url,
+        )
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn http_backend_round_trip() {
+    let (_server, url) = Server::start("secret");
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let backend = HttpBackend::new(url.clone(), "secret".to_owned()).unwrap();
+
+    // The query values are percent-encoded by the client
+    let (level_id, level_hash) = ("campaign/level 1&2", "a+b=c");
+    runtime
+        .block_on(backend.submit_time(level_entry("slow", 30., level_id, level_hash)))
+        .unwrap();
+    runtime
+        .block_on(backend.submit_time(level_entry("fast", 12.5, level_id, level_hash)))
+        .unwrap();
+    runtime
+        .block_on(backend.submit_time(level_entry("other", 1., "level1", level_hash)))
+        .unwrap();
+
+    let entries = runtime
+        .block_on(backend.request_leaderboard(level_id, level_hash))
+        .unwrap();
+    let usernames = entries
+        .iter()
+        .map(|entry| entry.username.as_str())
+        .collect::<Vec<&str>>();
+    assert_eq!(usernames, vec!["fast", "slow"]);
+    assert_eq!(entries[0].time, 12.5);
+
+    let wrong_key = HttpBackend::new(url, "guess".to_owned()).unwrap();
+    assert!(matches!(
+        runtime.block_on(wrong_key.request_leaderboard(level_id, level_hash)),
+        Err(LeaderboardError::Status(401))
+    ));
+}
+
+#[test]
+fn only_invalid_entries_are_rejected() {
+    for status in [400, 422] {
+        assert!(LeaderboardError::Status(status).is_rejection());
+        assert!(!LeaderboardError::Status(status).is_config_error());
+    }
+    // A wrong API key or URL keeps the times until the settings are fixed
+    for status in [401, 403, 404] {
+        assert!(LeaderboardError::Status(status).is_config_error());
+        assert!(!LeaderboardError::Status(status).is_rejection());
+    }
+    // The others are retried
+    for err in [
+        LeaderboardError::Status(429),
+        LeaderboardError::Status(503),
+        LeaderboardError::Timeout,
+        LeaderboardError::Connection(String::new()),
+    ] {
+        assert!(!err.is_rejection() && !err.is_config_error(), "{}", err);
+    }
+}
+
+#[test]
+fn server_drops_silent_and_oversized_requests() {
+    let (_server, url) = Server::start("secret");
+    let address = url.trim_start_matches("http://");
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let backend = HttpBackend::new(url.clone(), "secret".to_owned()).unwrap();
+
+    // A client which connects and sends nothing only holds the server until it times out
+    let _silent = TcpStream::connect(address).unwrap();
+    runtime
+        .block_on(backend.submit_time(entry("player", 10.)))
+        .unwrap();
+
+    // The body is refused from its length alone
+    let mut stream = TcpStream::connect(address).unwrap();
+    stream
+        .write_all(b"PUT /put HTTP/1.1\r\nApi-Key: secret\r\nContent-Length: 1000000000\r\n\r\n")
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 413"), "{}", response);
+}
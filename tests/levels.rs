@@ -0,0 +1,264 @@
+use ultimate_ninja::campaign::{Campaign, LevelSource};
+use ultimate_ninja::level::{self, ParseError};
+use ultimate_ninja::level_lint::{self, Severity};
+
+fn errors(source: &str, level_id: &str) -> Vec<String> {
+    tutorial_errors(source, level_id, false)
+}
+
+fn tutorial_errors(source: &str, level_id: &str, is_tutorial: bool) -> Vec<String> {
+    level_lint::lint_level(source, level_id, is_tutorial)
+        .into_iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .map(|diagnostic| diagnostic.to_string())
+        .collect()
+}
+
+#[test]
+fn shipped_levels_have_no_errors() {
+    let resources = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("resources");
+    let campaign = Campaign::load_file(&resources.join("campaigns/main.json"), &resources).unwrap();
+
+    for level in &campaign.levels {
+        let LevelSource::File(path) = &level.source else {
+            panic!("{} is not a file", level.id);
+        };
+        let source = std::fs::read_to_string(path).unwrap();
+
+        assert_eq!(
+            tutorial_errors(&source, &level.id, level.is_tutorial),
+            Vec::<String>::new(),
+            "{}",
+            path.display()
+        );
+    }
+
+    assert!(campaign.tutorial_count() > 0);
+}
+
+#[test]
+fn valid_level() {
+    let level = "\
+xxxxxxx
+xp--t-x
+x----ex
+xxxxxxx";
+
+    assert!(level_lint::lint_level(level, "test", false).is_empty());
+}
+
+#[test]
+fn duplicate_and_missing_spawns() {
+    let level = "\
+xxxxxxx
+xp--tpx
+x-----x
+xxxxxxx";
+
+    assert_eq!(
+        errors(level, "test"),
+        vec![
+            "1:1: error: level has no exit 'e'",
+            "2:6: error: more than one player 'p' in the level",
+        ]
+    );
+}
+
+#[test]
+fn ragged_rows() {
+    let level = "\
+xxxxxxx
+xp--t-x
+x----ex
+xxxxxx";
+
+    assert_eq!(
+        errors(level, "test"),
+        vec!["4:7: error: row is 6 blocks wide, expected 7"]
+    );
+}
+
+#[test]
+fn open_border() {
+    let level = "\
+xxxxxxx
+xp--t--
+x----ex
+xxxxxxx";
+
+    assert_eq!(
+        errors(level, "test"),
+        vec!["2:7: error: level is not enclosed by walls here"]
+    );
+}
+
+#[test]
+fn walled_off_target_and_exit() {
+    let level = "\
+xxxxxxxxx
+xp-xt-xex
+xxxxxxxxx";
+
+    assert_eq!(
+        errors(level, "test"),
+        vec![
+            "2:5: error: target cannot be reached from the player",
+            "2:8: error: exit cannot be reached from the target",
+        ]
+    );
+}
+
+#[test]
+fn guard_with_player_in_view() {
+    let level = "\
+xxxxxxxxxx
+xp--g--tex
+xxxxxxxxxx";
+
+    let diagnostics = level_lint::lint_level(level, "test", false);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "2:5: warning: guard 'g' spawns with the player in view"
+    );
+
+    // A wall blocks the view
+    let level = "\
+xxxxxxxxxx
+xp-xg--tex
+x--x----xx
+x-------xx
+xxxxxxxxxx";
+
+    assert!(level_lint::lint_level(level, "test", false).is_empty());
+}
+
+#[test]
+fn header_and_patrols() {
+    let level = "\
+version: 2
+size: 8x3
+---
+xxxxxxx
+xpg-t-x
+x----ex
+xxxxxxx
+---
+patrol 2,1 loop: 3,1 -> 0,0";
+
+    assert_eq!(
+        errors(level, "test"),
+        vec![
+            "4:1: error: header size 8x3 does not match the grid size 7x4",
+            "9:1: error: waypoint 0,0 is not on a walkable block",
+        ]
+    );
+}
+
+/// The linter reports the same parse errors as the loader, at the line of the problem
+#[test]
+fn parse_errors() {
+    let grid = "\
+xxxxxxx
+xpg-t-x
+x----ex
+xxxxxxx";
+
+    for (level, line, message) in [
+        (
+            format!("version: 2\npar_time: fast\n---\n{}", grid),
+            2,
+            "invalid par time: 'fast'",
+        ),
+        (
+            format!("version: 9\n---\n{}", grid),
+            1,
+            "unsupported level version: 9",
+        ),
+        (
+            format!("version: 2\nname: Unterminated\n{}", grid),
+            3,
+            "invalid header line: 'xxxxxxx'",
+        ),
+        (
+            "version: 2\nname: Unterminated".to_owned(),
+            2,
+            "header is not terminated by '---'",
+        ),
+        (
+            format!("version: 2\n---\n{}\n---\npatrol 1,1 loop: 1,2", grid),
+            8,
+            "patrol at 1,1 does not belong to a guard",
+        ),
+        (
+            format!("version: 2\n---\n{}\n---\n\npatrol 2,1 zigzag: 1,2", grid),
+            9,
+            "invalid patrol mode: 'zigzag'",
+        ),
+    ] {
+        assert_eq!(
+            errors(&level, "test"),
+            vec![format!("{}:1: error: {}", line, message)]
+        );
+        assert_eq!(
+            level::parse_level(&level).err(),
+            Some(ParseError {
+                line,
+                message: message.to_owned()
+            })
+        );
+    }
+}
+
+#[test]
+fn tutorial_guards() {
+    let level = "\
+xxxxxxx
+xp--t-x
+x-g--ex
+xxxxxxx";
+
+    assert_eq!(
+        tutorial_errors(level, "tutorial4", true),
+        vec!["1:1: error: tutorial4 needs at least 2 basic guards 'g', it has 1"]
+    );
+
+    // A pack level which happens to share the name is not a tutorial
+    assert_eq!(errors(level, "tutorial4"), Vec::<String>::new());
+}
+
+#[test]
+fn unknown_glyph() {
+    let level = "\
+xxxxxxx
+xp--t-x
+x-?--ex
+xxxxxxx";
+
+    assert_eq!(errors(level, "test"), vec!["3:3: error: unknown glyph '?'"]);
+}
+
+#[test]
+fn campaign_manifest_lists_shipped_levels() {
+    let resources = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("resources");
+    let manifest = std::fs::read_to_string(resources.join("campaigns/main.json")).unwrap();
+    let manifest: serde_json::Value = serde_json::from_str(&manifest).unwrap();
+
+    let mut listed = manifest["chapters"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .flat_map(|chapter| chapter["levels"].as_array().unwrap())
+        .map(|level| level.as_str().unwrap().to_owned())
+        .collect::<Vec<String>>();
+    listed.sort();
+
+    let mut shipped = std::fs::read_dir(resources.join("levels"))
+        .unwrap()
+        .map(|entry| format!("levels/{}", entry.unwrap().file_name().to_string_lossy()))
+        .filter(|filename| filename.ends_with(".txt"))
+        .collect::<Vec<String>>();
+    shipped.sort();
+
+    assert_eq!(listed, shipped);
+}
@@ -0,0 +1,111 @@
+use ultimate_ninja::entities::player::MoveType;
+use ultimate_ninja::replay_system::{self, Replay, ReplaySystem};
+use ultimate_ninja::world::PlayerInput;
+
+/// Live input of each tick: walking right, an attack, sprinting and a throw
+fn live_input(tick: u32) -> PlayerInput {
+    let mut input = PlayerInput::new();
+    if tick >= 2 {
+        input.move_dir = glam::vec2(1., 0.);
+    }
+    input.attack = tick == 4;
+    if (6..8).contains(&tick) {
+        input.move_type = MoveType::Sprint;
+    }
+    if tick == 9 {
+        input.throw_target = Some(glam::vec2(40., 60.));
+    }
+
+    input
+}
+
+fn same_input(input1: &PlayerInput, input2: &PlayerInput) -> bool {
+    input1.move_dir == input2.move_dir
+        && input1.move_type == input2.move_type
+        && input1.stealth == input2.stealth
+        && input1.teleport == input2.teleport
+        && input1.attack == input2.attack
+        && input1.throw_target == input2.throw_target
+}
+
+fn record(ticks: u32) -> Replay {
+    let mut replay_system = ReplaySystem::new();
+    assert_eq!(replay_system.start_attempt("main", 3, 42), 42);
+
+    for tick in 0..ticks {
+        let input = replay_system.tick_input(tick, &live_input(tick));
+        assert!(same_input(&input, &live_input(tick)));
+    }
+
+    replay_system.take_recording().unwrap()
+}
+
+#[test]
+fn only_input_edges_are_recorded() {
+    let replay = record(12);
+
+    let ticks = replay
+        .inputs
+        .iter()
+        .map(|record| record.tick)
+        .collect::<Vec<u32>>();
+    assert_eq!(ticks, vec![0, 2, 4, 5, 6, 8, 9, 10]);
+    assert_eq!(replay.ticks, 12);
+
+    for tick in 0..12 {
+        assert!(
+            same_input(&replay.input_at(tick), &live_input(tick)),
+            "tick {}",
+            tick
+        );
+    }
+    // The last input is held after the end of the recording
+    assert!(same_input(&replay.input_at(20), &live_input(11)));
+}
+
+#[test]
+fn replay_plays_back_the_recorded_inputs() {
+    let filename =
+        std::env::temp_dir().join(format!("ultimate_ninja_replay_{}.json", std::process::id()));
+    let filename = filename.to_str().unwrap();
+    record(12).save(filename).unwrap();
+    let replay = Replay::load(filename).unwrap();
+
+    let mut replay_system = ReplaySystem::new();
+    replay_system.queue_playback(replay);
+    assert_eq!(replay_system.pending_level(), Some(("main", 3)));
+
+    // The replay waits for the attempt of its level, which gets the recorded seed
+    assert_eq!(replay_system.start_attempt("main", 3, 7), 42);
+    assert_eq!(replay_system.pending_level(), None);
+
+    for tick in 0..12 {
+        assert!(!replay_system.is_playback_finished(tick));
+        let input = replay_system.tick_input(tick, &PlayerInput::new());
+        assert!(same_input(&input, &live_input(tick)), "tick {}", tick);
+    }
+    assert!(replay_system.is_playback_finished(12));
+    // Nothing is recorded while playing back
+    assert!(replay_system.take_recording().is_none());
+}
+
+#[test]
+fn replay_of_another_level_is_not_played() {
+    let mut replay_system = ReplaySystem::new();
+    replay_system.queue_playback(record(12));
+
+    assert_eq!(replay_system.start_attempt("main", 4, 7), 7);
+    assert!(!replay_system.is_playback_finished(100));
+    assert!(replay_system.take_recording().is_some());
+}
+
+#[test]
+fn attempts_are_saved_to_their_own_files() {
+    assert_ne!(
+        replay_system::replay_filename("main", 3, 1),
+        replay_system::replay_filename("main", 3, 2)
+    );
+    assert!(
+        replay_system::replay_filename("main", 3, 42).ends_with("_main_3_000000000000002a.json")
+    );
+}
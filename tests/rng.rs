@@ -0,0 +1,69 @@
+use ultimate_ninja::rng::GameRng;
+
+#[test]
+fn same_seed_same_sequence() {
+    for seed in [0, 1, 42, u64::MAX] {
+        let (mut rng1, mut rng2) = (GameRng::new(seed), GameRng::new(seed));
+        for _ in 0..100 {
+            assert_eq!(rng1.next_u32(), rng2.next_u32());
+            assert_eq!(rng1.gen_range(-3., 5.), rng2.gen_range(-3., 5.));
+            assert_eq!(rng1.gen_index(7), rng2.gen_index(7));
+            assert_eq!(rng1.gen_bool(), rng2.gen_bool());
+        }
+    }
+
+    let mut rng1 = GameRng::new(1);
+    let mut rng2 = GameRng::new(2);
+    assert!((0..10).any(|_| rng1.next_u32() != rng2.next_u32()));
+}
+
+#[test]
+fn known_output_for_seed_0() {
+    let mut rng = GameRng::new(0);
+    let values = (0..5).map(|_| rng.next_u32()).collect::<Vec<u32>>();
+
+    assert_eq!(
+        values,
+        vec![3894649422, 2055130073, 2315086854, 2925816488, 3443325253]
+    );
+
+    // It is the generator of quad_rand
+    quad_rand::srand(0);
+    assert_eq!(
+        values,
+        (0..5).map(|_| quad_rand::rand()).collect::<Vec<u32>>()
+    );
+}
+
+#[test]
+fn gen_range_bounds() {
+    let mut rng = GameRng::new(7);
+    for _ in 0..10_000 {
+        let value = rng.gen_range(-2., 3.);
+        assert!((-2. ..3.).contains(&value), "{}", value);
+    }
+
+    // The f32 values around 1e8 are 8 apart, the upper bound is still excluded
+    for _ in 0..100 {
+        assert_eq!(rng.gen_range(1e8, 1e8 + 8.), 1e8);
+    }
+    assert_eq!(rng.gen_range(4., 4.), 4.);
+}
+
+#[test]
+fn gen_index_bounds() {
+    let mut rng = GameRng::new(7);
+
+    let mut counts = [0; 5];
+    for _ in 0..10_000 {
+        counts[rng.gen_index(5)] += 1;
+    }
+    // Every index is reached, roughly as often as the others
+    assert!(
+        counts.iter().all(|&count| (1800..2200).contains(&count)),
+        "{:?}",
+        counts
+    );
+
+    assert!((0..100).all(|_| rng.gen_index(1) == 0));
+}
@@ -0,0 +1,131 @@
+use ultimate_ninja::splits_system::{self, Attempt, Splits};
+
+fn splits() -> Splits {
+    let mut splits = Splits::new(3);
+    splits.game_name = "Ultimate <Ninja> & \"Friends\"".to_owned();
+    splits.category_name = "Any% 'glitchless'".to_owned();
+    splits.attempt_count = 2;
+    splits.attempts = vec![
+        Attempt {
+            id: 1,
+            started: "01/02/2024 10:00:00".to_owned(),
+            ended: "01/02/2024 10:05:00".to_owned(),
+            time: None,
+        },
+        Attempt {
+            id: 2,
+            started: "01/02/2024 11:00:00".to_owned(),
+            ended: "01/02/2024 11:05:00".to_owned(),
+            time: Some(61.5),
+        },
+    ];
+    splits.segments[0].name = "Rooftops & <Alleys>".to_owned();
+    for (idx, segment) in splits.segments.iter_mut().enumerate() {
+        segment.pb_split = Some(20.5 * (idx + 1) as f32);
+        segment.best_segment = Some(19.25);
+        segment.history = vec![(1, 21.), (2, 20.5)];
+    }
+    splits.segments[2].history.truncate(1);
+    splits.segments[2].best_segment = None;
+
+    splits
+}
+
+#[test]
+fn lss_round_trip() {
+    let splits = splits();
+    let lss = splits.to_lss();
+    let loaded = Splits::from_lss(&lss, 3).unwrap();
+
+    assert_eq!(loaded.to_lss(), lss);
+    assert_eq!(loaded.game_name, splits.game_name);
+    assert_eq!(loaded.category_name, splits.category_name);
+    assert_eq!(loaded.attempt_count, 2);
+    assert_eq!(loaded.attempts.len(), 2);
+    assert_eq!(loaded.attempts[0].time, None);
+    assert_eq!(loaded.attempts[1].time, Some(61.5));
+    assert_eq!(loaded.attempts[1].started, "01/02/2024 11:00:00");
+
+    for (loaded, segment) in loaded.segments.iter().zip(&splits.segments) {
+        assert_eq!(loaded.name, segment.name);
+        assert_eq!(loaded.pb_split, segment.pb_split);
+        assert_eq!(loaded.best_segment, segment.best_segment);
+        assert_eq!(loaded.history, segment.history);
+    }
+}
+
+#[test]
+fn names_are_escaped() {
+    let lss = splits().to_lss();
+
+    assert!(lss.contains("<GameName>Ultimate &lt;Ninja&gt; &amp; &quot;Friends&quot;</GameName>"));
+    assert!(lss.contains("<Name>Rooftops &amp; &lt;Alleys&gt;</Name>"));
+
+    let lss = lss.replace("'glitchless'", "&apos;glitchless&apos;");
+    assert_eq!(
+        Splits::from_lss(&lss, 3).unwrap().category_name,
+        "Any% 'glitchless'"
+    );
+}
+
+#[test]
+fn segments_are_matched_to_the_levels() {
+    let lss = splits().to_lss();
+
+    // Segments of levels which are not in the campaign anymore are dropped
+    let fewer = Splits::from_lss(&lss, 2).unwrap();
+    assert_eq!(fewer.segments.len(), 2);
+    assert_eq!(fewer.segments[1].pb_split, Some(41.));
+
+    // New levels get empty segments
+    let more = Splits::from_lss(&lss, 5).unwrap();
+    assert_eq!(more.segments.len(), 5);
+    assert_eq!(more.segments[2].pb_split, Some(61.5));
+    assert_eq!(more.segments[4].name, "Level 5");
+    assert_eq!(more.segments[4].pb_split, None);
+    assert!(more.segments[4].history.is_empty());
+}
+
+#[test]
+fn invalid_lss() {
+    assert!(Splits::from_lss("", 1).is_err());
+    assert!(Splits::from_lss("<Run></Run>", 1).is_err());
+
+    let lss = splits().to_lss().replace("00:00:20.5000000", "20 seconds");
+    assert_eq!(
+        Splits::from_lss(&lss, 3).err(),
+        Some("invalid time: '20 seconds'".to_owned())
+    );
+}
+
+#[test]
+fn time_round_trip() {
+    for time in [
+        0., 0.25, 59.5, 61.125, 3599.75, 3600., 7384.5, -1.5, -3725.25,
+    ] {
+        let formatted = splits_system::format_time(time);
+        assert_eq!(
+            splits_system::parse_time(&formatted),
+            Some(time),
+            "{}",
+            formatted
+        );
+    }
+
+    assert_eq!(splits_system::format_time(3725.5), "01:02:05.5000000");
+    assert_eq!(splits_system::format_time(-0.5), "-00:00:00.5000000");
+    assert_eq!(splits_system::format_time(-0.), "00:00:00.0000000");
+}
+
+#[test]
+fn parse_time_formats() {
+    assert_eq!(splits_system::parse_time("00:00:00"), Some(0.));
+    assert_eq!(splits_system::parse_time(" 00:01:02.5 "), Some(62.5));
+    assert_eq!(splits_system::parse_time("1.02:00:00"), Some(93600.));
+    assert_eq!(splits_system::parse_time("-00:00:01.5"), Some(-1.5));
+
+    assert_eq!(splits_system::parse_time(""), None);
+    assert_eq!(splits_system::parse_time("01:02"), None);
+    assert_eq!(splits_system::parse_time("00:00:00:00"), None);
+    assert_eq!(splits_system::parse_time("aa:00:00"), None);
+}
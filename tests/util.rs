@@ -0,0 +1,26 @@
+use ultimate_ninja::constants::GRID_CELL_SIZE;
+use ultimate_ninja::util;
+
+#[test]
+fn grid_rows_include_partial_cells() {
+    let cell = GRID_CELL_SIZE as u32;
+
+    assert_eq!(util::grid_row_len(2 * cell), 2);
+    assert_eq!(util::grid_row_len(2 * cell + 1), 3);
+    assert_eq!(util::grid_row_len(cell / 2), 1);
+    assert_eq!(util::grid_row_len(0), 0);
+}
+
+#[test]
+fn grid_indices_are_unique() {
+    // The last cell of each row is partial
+    let world_width = 2 * GRID_CELL_SIZE as u32 + GRID_CELL_SIZE as u32 / 2;
+    let cell = GRID_CELL_SIZE as f32;
+
+    let indices = (0..3)
+        .flat_map(|y| (0..3).map(move |x| glam::vec2(x as f32, y as f32) * cell + 1.))
+        .map(|position| util::compute_grid_index(&position, world_width))
+        .collect::<Vec<isize>>();
+
+    assert_eq!(indices, (0..9).collect::<Vec<isize>>());
+}
@@ -0,0 +1,257 @@
+use ultimate_ninja::level;
+use ultimate_ninja::world::{PlayerInput, Sound, World, WorldEvent};
+
+const DT: f32 = ultimate_ninja::constants::FIXED_DT;
+
+fn load(grid: &str) -> World {
+    let mut world = World::new();
+    world.reset(true, 0);
+    level::load_world(&mut world, level::parse_level(grid).unwrap(), false);
+
+    world
+}
+
+fn move_input(move_dir: glam::Vec2) -> PlayerInput {
+    PlayerInput {
+        move_dir: move_dir.normalize_or_zero(),
+        ..PlayerInput::new()
+    }
+}
+
+/// Input which walks the player straight towards the position
+fn move_towards(world: &World, position: glam::Vec2) -> PlayerInput {
+    move_input(position - world.player.transform.position)
+}
+
+/// Steps until the condition holds, returns the events of every step
+fn step_until(
+    world: &mut World,
+    input: impl Fn(&World) -> PlayerInput,
+    max_steps: u32,
+    condition: impl Fn(&World) -> bool,
+) -> Option<Vec<WorldEvent>> {
+    let mut events = vec![];
+    for _ in 0..max_steps {
+        let input = input(world);
+        events.extend(world.step(&input, DT));
+        if condition(world) {
+            return Some(events);
+        }
+    }
+
+    None
+}
+
+#[test]
+fn player_kills_target_and_exits() {
+    let mut world = load(
+        "\
+xxxxxxxxxx
+xp--t---ex
+xxxxxxxxxx
+",
+    );
+
+    let events = step_until(
+        &mut world,
+        |world| move_towards(world, world.target.transform.position),
+        600,
+        |world| world.target.is_dead(),
+    )
+    .expect("target was not killed");
+    assert_eq!(world.kills, 1);
+    assert!(events.contains(&WorldEvent::Sound(Sound::TargetKilled, None)));
+
+    let events = step_until(
+        &mut world,
+        |world| move_towards(world, world.exit.transform.position),
+        600,
+        |world| world.exit.player_exited,
+    )
+    .expect("player did not exit");
+    assert!(events.contains(&WorldEvent::Sound(Sound::LevelExit, None)));
+    assert!(!world.is_player_detected);
+}
+
+#[test]
+fn exit_needs_dead_target() {
+    let mut world = load(
+        "\
+xxxxxxxxx
+xt--p--ex
+xxxxxxxxx
+",
+    );
+
+    let reached_exit = step_until(
+        &mut world,
+        |_| move_input(glam::Vec2::X),
+        300,
+        |world| world.player.transform.position.x > world.exit.transform.position.x,
+    );
+
+    assert!(reached_exit.is_some());
+    assert!(!world.target.is_dead());
+    assert!(!world.exit.player_exited);
+}
+
+#[test]
+fn guard_detects_player_in_plain_sight() {
+    let mut world = load(
+        "\
+xxxxxxxxxxx
+x---------x
+xp---g---tx
+x---------x
+xxxxxxxxxxx
+",
+    );
+
+    let events = step_until(
+        &mut world,
+        |world| move_towards(world, world.get_all_guards()[0].transform.position),
+        600,
+        |world| world.is_player_detected,
+    );
+
+    assert!(events.is_some(), "player was not detected");
+    assert!(!world.target.is_dead());
+}
+
+#[test]
+fn guard_does_not_see_through_walls() {
+    let mut world = load(
+        "\
+xxxxxxxxxxx
+x---x-----x
+xp--x-g--tx
+x---x-----x
+xxxxxxxxxxx
+",
+    );
+
+    let events = step_until(
+        &mut world,
+        |_| PlayerInput::new(),
+        600,
+        |world| world.is_player_detected,
+    );
+
+    assert!(events.is_none(), "player was detected through a wall");
+}
+
+#[test]
+fn same_seed_same_run() {
+    let grid = "\
+xxxxxxxxxxxx
+xp---------x
+x---g---s--x
+x--------t-x
+xxxxxxx---ex
+";
+    let inputs = [
+        move_input(glam::Vec2::X),
+        move_input(glam::Vec2::Y),
+        move_input(glam::Vec2::ONE),
+    ];
+
+    let run = || {
+        let mut world = load(grid);
+        for input in inputs.iter().cycle().take(300) {
+            world.step(input, DT);
+        }
+
+        let guards = world
+            .get_all_guards()
+            .iter()
+            .map(|guard| guard.transform.position)
+            .collect::<Vec<glam::Vec2>>();
+        (world.player.transform.position, guards, world.tick)
+    };
+
+    assert_eq!(run(), run());
+}
+
+#[test]
+fn dragged_body_stays_out_of_walls() {
+    let mut world = load(
+        "\
+xxxxxxx
+x-----x
+x-p-t-x
+x-----x
+xxxxxxx
+",
+    );
+
+    step_until(
+        &mut world,
+        |world| move_towards(world, world.target.transform.position),
+        600,
+        |world| world.target.is_dead(),
+    )
+    .expect("target was not killed");
+
+    let pick_up = PlayerInput {
+        attack: true,
+        ..PlayerInput::new()
+    };
+    world.step(&pick_up, DT);
+    assert!(world.player.dragged_body.is_some());
+
+    // Turning around against a wall swings the body behind the player towards the wall
+    let overlaps_wall = |world: &World| {
+        world
+            .walls
+            .iter()
+            .any(|wall| wall.aabb.rect.overlaps(&world.target.aabb.rect))
+    };
+    for move_dir in [
+        glam::vec2(1., 0.),
+        glam::vec2(-1., 0.),
+        glam::vec2(0., 1.),
+        glam::vec2(0., -1.),
+    ] {
+        for _ in 0..120 {
+            world.step(&move_input(move_dir), DT);
+            assert!(
+                !overlaps_wall(&world),
+                "body in a wall at {}",
+                world.target.transform.position
+            );
+        }
+    }
+}
+
+#[test]
+fn guard_skips_unreachable_waypoints() {
+    let mut world = load(
+        "\
+version: 2
+---
+xxxxxxxxxx
+xg---x--px
+x----xxxxx
+xxxxxxxxxx
+---
+patrol 1,1 loop: 6,1 -> 3,2 wait=100
+",
+    );
+    let reachable_waypoint =
+        glam::vec2(3.5, 2.5) * ultimate_ninja::constants::LEVEL_BLOCK_SIZE as f32;
+
+    step_until(
+        &mut world,
+        |_| PlayerInput::new(),
+        600,
+        |world| {
+            world.get_all_guards()[0]
+                .transform
+                .position
+                .distance(reachable_waypoint)
+                < ultimate_ninja::constants::PATH_POINT_RADIUS
+        },
+    )
+    .expect("guard did not walk on to the reachable waypoint");
+    assert!(!world.is_player_detected);
+}